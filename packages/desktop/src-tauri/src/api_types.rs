@@ -8,6 +8,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+
 // ============================================================================
 // Metadata API Types
 // ============================================================================
@@ -168,6 +170,37 @@ pub struct SpotifyPlaylistTrack {
     pub release_date: String,
 }
 
+// Album and playlist tracks carry the same fields as a single-track response
+impl From<&SpotifyAlbumTrack> for SpotifyTrackMetadata {
+    fn from(track: &SpotifyAlbumTrack) -> Self {
+        SpotifyTrackMetadata {
+            track_id: track.track_id.clone(),
+            name: track.name.clone(),
+            artist: track.artists.clone(),
+            album: track.album.clone(),
+            isrc: track.isrc.clone(),
+            duration_ms: track.duration_ms,
+            release_date: track.release_date.clone(),
+            image_url: track.image_url.clone(),
+        }
+    }
+}
+
+impl From<&SpotifyPlaylistTrack> for SpotifyTrackMetadata {
+    fn from(track: &SpotifyPlaylistTrack) -> Self {
+        SpotifyTrackMetadata {
+            track_id: track.track_id.clone(),
+            name: track.name.clone(),
+            artist: track.artists.clone(),
+            album: track.album.clone(),
+            isrc: track.isrc.clone(),
+            duration_ms: track.duration_ms,
+            release_date: track.release_date.clone(),
+            image_url: track.image_url.clone(),
+        }
+    }
+}
+
 // ============================================================================
 // Transliteration API Types
 // ============================================================================
@@ -268,7 +301,7 @@ impl HasodApiClient {
     }
 
     /// Get complete Spotify track metadata
-    pub async fn get_spotify_metadata(&self, spotify_url: &str) -> Result<SpotifyTrackMetadata, String> {
+    pub async fn get_spotify_metadata(&self, spotify_url: &str) -> AppResult<SpotifyTrackMetadata> {
         let url = format!("{}/metadata/spotify", self.base_url);
 
         let request = SpotifyMetadataRequest {
@@ -280,28 +313,28 @@ impl HasodApiClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("API request failed: {}", e))?;
+            .or_error(ErrorKind::Network, "API request failed")?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(format!("API returned status {}: {}", status, body));
+            return Err(AppError::http(status, format!("API returned status {}: {}", status, body)));
         }
 
         let api_response: SpotifyMetadataResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse API response: {}", e))?;
+            .or_error(ErrorKind::Parse, "Failed to parse API response")?;
 
         if !api_response.success {
-            return Err("API returned success=false".to_string());
+            return Err(AppError::new(ErrorKind::ServerError, "API returned success=false"));
         }
 
         Ok(api_response.metadata)
     }
 
     /// Get complete album metadata with all tracks and ISRCs
-    pub async fn get_spotify_album_metadata(&self, spotify_url: &str) -> Result<SpotifyAlbumMetadataResponse, String> {
+    pub async fn get_spotify_album_metadata(&self, spotify_url: &str) -> AppResult<SpotifyAlbumMetadataResponse> {
         let url = format!("{}/metadata/spotify/album", self.base_url);
 
         let request = SpotifyAlbumMetadataRequest {
@@ -313,28 +346,28 @@ impl HasodApiClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Album API request failed: {}", e))?;
+            .or_error(ErrorKind::Network, "Album API request failed")?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(format!("Album API returned status {}: {}", status, body));
+            return Err(AppError::http(status, format!("Album API returned status {}: {}", status, body)));
         }
 
         let api_response: SpotifyAlbumMetadataResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse album API response: {}", e))?;
+            .or_error(ErrorKind::Parse, "Failed to parse album API response")?;
 
         if !api_response.success {
-            return Err("Album API returned success=false".to_string());
+            return Err(AppError::new(ErrorKind::ServerError, "Album API returned success=false"));
         }
 
         Ok(api_response)
     }
 
     /// Get complete playlist metadata with all tracks and ISRCs
-    pub async fn get_spotify_playlist_metadata(&self, spotify_url: &str) -> Result<SpotifyPlaylistMetadataResponse, String> {
+    pub async fn get_spotify_playlist_metadata(&self, spotify_url: &str) -> AppResult<SpotifyPlaylistMetadataResponse> {
         let url = format!("{}/metadata/spotify/playlist", self.base_url);
 
         let request = SpotifyPlaylistMetadataRequest {
@@ -346,21 +379,21 @@ impl HasodApiClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Playlist API request failed: {}", e))?;
+            .or_error(ErrorKind::Network, "Playlist API request failed")?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(format!("Playlist API returned status {}: {}", status, body));
+            return Err(AppError::http(status, format!("Playlist API returned status {}: {}", status, body)));
         }
 
         let api_response: SpotifyPlaylistMetadataResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse playlist API response: {}", e))?;
+            .or_error(ErrorKind::Parse, "Failed to parse playlist API response")?;
 
         if !api_response.success {
-            return Err("Playlist API returned success=false".to_string());
+            return Err(AppError::new(ErrorKind::ServerError, "Playlist API returned success=false"));
         }
 
         Ok(api_response)
@@ -373,7 +406,7 @@ impl HasodApiClient {
         isrc: &str,
         auth_token: &str,
         quality: Option<DeezerQuality>,
-    ) -> Result<DeezerDownloadUrlResponse, String> {
+    ) -> AppResult<DeezerDownloadUrlResponse> {
         let url = format!("{}/download/deezer/isrc", self.base_url);
 
         let request = DeezerIsrcRequest {
@@ -387,21 +420,21 @@ impl HasodApiClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Deezer API request failed: {}", e))?;
+            .or_error(ErrorKind::Network, "Deezer API request failed")?;
 
         if !response.status().is_success() {
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
-            return Err(format!("Deezer API returned status {}: {}", status, body));
+            return Err(AppError::http(status, format!("Deezer API returned status {}: {}", status, body)));
         }
 
         let api_response: DeezerDownloadUrlResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse Deezer API response: {}", e))?;
+            .or_error(ErrorKind::Parse, "Failed to parse Deezer API response")?;
 
         if !api_response.success {
-            return Err("Deezer API returned success=false".to_string());
+            return Err(AppError::new(ErrorKind::ServerError, "Deezer API returned success=false"));
         }
 
         Ok(api_response)
@@ -413,7 +446,7 @@ impl HasodApiClient {
         &self,
         items: Vec<MediaItem>,
         auth_token: &str,
-    ) -> Result<TransliterateResponse, String> {
+    ) -> AppResult<TransliterateResponse> {
         let url = format!("{}/transliterate", self.base_url);
 
         let request = TransliterateRequest { items };
@@ -425,19 +458,20 @@ impl HasodApiClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Transliteration API request failed: {}", e))?;
+            .or_error(ErrorKind::Network, "Transliteration API request failed")?;
 
         if !response.status().is_success() {
-            return Err(format!("Transliteration API failed with status: {}", response.status()));
+            let status = response.status();
+            return Err(AppError::http(status, format!("Transliteration API failed with status: {}", status)));
         }
 
         let api_response: TransliterateResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse transliteration API response: {}", e))?;
+            .or_error(ErrorKind::Parse, "Failed to parse transliteration API response")?;
 
         if !api_response.success {
-            return Err("Transliteration API returned success=false".to_string());
+            return Err(AppError::new(ErrorKind::ServerError, "Transliteration API returned success=false"));
         }
 
         Ok(api_response)
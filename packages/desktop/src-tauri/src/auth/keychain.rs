@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::error::{AppResult, ErrorKind, ResultExt};
+
 /// Stored authentication data
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StoredAuth {
@@ -23,13 +25,12 @@ fn get_auth_file_path() -> PathBuf {
 }
 
 /// Save authentication data to a file
-pub fn save_auth_to_keychain(auth: &StoredAuth) -> Result<(), String> {
+pub fn save_auth_to_keychain(auth: &StoredAuth) -> AppResult<()> {
     let path = get_auth_file_path();
-    let json = serde_json::to_string_pretty(auth)
-        .map_err(|e| format!("JSON serialize error: {}", e))?;
+    let json = serde_json::to_string_pretty(auth)?;
 
     fs::write(&path, json)
-        .map_err(|e| format!("Failed to write auth file: {}", e))?;
+        .or_error(ErrorKind::Io, "Failed to write auth file")?;
 
     println!("[Auth] Saved auth to file: {:?}", path);
     Ok(())
@@ -48,12 +49,12 @@ pub fn get_auth_from_keychain() -> Option<StoredAuth> {
 }
 
 /// Clear authentication data from file
-pub fn clear_auth_from_keychain() -> Result<(), String> {
+pub fn clear_auth_from_keychain() -> AppResult<()> {
     let path = get_auth_file_path();
 
     if path.exists() {
         fs::remove_file(&path)
-            .map_err(|e| format!("Failed to delete auth file: {}", e))?;
+            .or_error(ErrorKind::Io, "Failed to delete auth file")?;
     }
 
     println!("[Auth] Cleared auth file");
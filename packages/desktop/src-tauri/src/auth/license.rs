@@ -3,12 +3,17 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::sync::Mutex;
 
+use crate::error::AppResult;
 use crate::utils::get_config_dir;
 
 const API_BASE_URL: &str = "https://us-central1-hasod-41a23.cloudfunctions.net/api";
 const REQUIRED_SERVICE_ID: &str = "hasod-downloader";
 
+/// Result of the last license check
+static LICENSE_CACHE: Mutex<Option<LicenseStatus>> = Mutex::new(None);
+
 // ============================================================================
 // Types
 // ============================================================================
@@ -103,8 +108,24 @@ pub fn get_registration_url(device_uuid: &str) -> String {
 }
 
 /// Check if the user has a valid license for the hasod-downloader service
-/// Returns LicenseStatus with detailed information
-pub async fn check_license(user_email: Option<String>, device_uuid: String) -> Result<LicenseStatus, String> {
+/// Returns LicenseStatus with detailed information; the result is cached for background features
+pub async fn check_license(user_email: Option<String>, device_uuid: String) -> AppResult<LicenseStatus> {
+    let status = fetch_license(user_email, device_uuid).await?;
+    *LICENSE_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = Some(status.clone());
+    Ok(status)
+}
+
+/// Whether the last license check found a valid license
+/// For work done without the UI (the clipboard watcher); false until the first check
+pub fn has_valid_cached_license() -> bool {
+    LICENSE_CACHE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|status| status.is_valid)
+}
+
+async fn fetch_license(user_email: Option<String>, device_uuid: String) -> AppResult<LicenseStatus> {
     let auth_token = get_auth_token();
 
     // If no auth token and no email, return not registered
@@ -15,4 +15,4 @@ pub use oauth::{
     refresh_auth_token,
     logout,
 };
-pub use license::{LicenseStatus, check_license, get_registration_url, save_auth_token};
+pub use license::{LicenseStatus, check_license, get_registration_url, has_valid_cached_license, save_auth_token};
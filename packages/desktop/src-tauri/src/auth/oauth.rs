@@ -12,6 +12,7 @@ use tiny_http::{Response, Server};
 use url::Url;
 
 use super::keychain::{StoredAuth, save_auth_to_keychain, get_auth_from_keychain, clear_auth_from_keychain};
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
 use crate::utils::get_hardware_id;
 
 /// OAuth state stored during the authentication flow
@@ -69,7 +70,7 @@ fn generate_state() -> String {
 /// Returns the authorization URL and state for verification
 pub fn start_google_login(
     google_client_id: &str,
-) -> Result<OAuthStartResult, String> {
+) -> AppResult<OAuthStartResult> {
     // Generate PKCE values
     let code_verifier = generate_code_verifier();
     let code_challenge = generate_code_challenge(&code_verifier);
@@ -77,7 +78,7 @@ pub fn start_google_login(
 
     // Store OAuth state for later verification
     {
-        let mut oauth_state = OAUTH_STATE.lock().unwrap();
+        let mut oauth_state = OAUTH_STATE.lock()?;
         *oauth_state = Some(OAuthState {
             code_verifier: code_verifier.clone(),
             state: state.clone(),
@@ -114,12 +115,12 @@ pub fn start_google_login(
 
 /// Wait for the OAuth callback from Google
 /// Starts a local HTTP server and waits for the authorization code
-pub async fn wait_for_oauth_callback(app: AppHandle) -> Result<String, String> {
+pub async fn wait_for_oauth_callback(app: AppHandle) -> AppResult<String> {
     println!("[OAuth] Starting callback server on port {}", OAUTH_CALLBACK_PORT);
 
     // Start local HTTP server to receive callback
     let server = Server::http(format!("0.0.0.0:{}", OAUTH_CALLBACK_PORT))
-        .map_err(|e| format!("Failed to start callback server: {}", e))?;
+        .map_err(|e| AppError::new(ErrorKind::Network, format!("Failed to start callback server: {}", e)))?;
 
     println!("[OAuth] Server started, waiting for callback...");
 
@@ -130,7 +131,7 @@ pub async fn wait_for_oauth_callback(app: AppHandle) -> Result<String, String> {
     loop {
         // Check timeout
         if start_time.elapsed() > timeout_duration {
-            return Err("OAuth callback timed out after 5 minutes".to_string());
+            return Err(AppError::new(ErrorKind::Network, "OAuth callback timed out after 5 minutes"));
         }
 
         // Non-blocking receive with short timeout
@@ -159,7 +160,7 @@ pub async fn wait_for_oauth_callback(app: AppHandle) -> Result<String, String> {
                     ));
                     request.respond(response).ok();
 
-                    return Err(format!("OAuth error: {}", error_desc));
+                    return Err(AppError::new(ErrorKind::AuthRequired, format!("OAuth error: {}", error_desc)));
                 }
 
                 // Get authorization code
@@ -168,7 +169,7 @@ pub async fn wait_for_oauth_callback(app: AppHandle) -> Result<String, String> {
 
                     // Verify state
                     let expected_state = {
-                        let oauth_state = OAUTH_STATE.lock().unwrap();
+                        let oauth_state = OAUTH_STATE.lock()?;
                         oauth_state.as_ref().map(|s| s.state.clone())
                     };
 
@@ -177,7 +178,10 @@ pub async fn wait_for_oauth_callback(app: AppHandle) -> Result<String, String> {
                             "<html><body><h1>Login Failed</h1><p>Invalid state parameter</p></body></html>",
                         );
                         request.respond(response).ok();
-                        return Err("OAuth state mismatch - possible CSRF attack".to_string());
+                        return Err(AppError::new(
+                            ErrorKind::AuthRequired,
+                            "OAuth state mismatch - possible CSRF attack",
+                        ));
                     }
 
                     // Send success response to browser
@@ -223,16 +227,16 @@ pub async fn exchange_oauth_code(
     google_client_id: &str,
     google_client_secret: &str,
     firebase_api_key: &str,
-) -> Result<StoredAuth, String> {
+) -> AppResult<StoredAuth> {
     println!("[OAuth] Exchanging authorization code for tokens");
 
     // Get code verifier from stored state
     let code_verifier = {
-        let oauth_state = OAUTH_STATE.lock().unwrap();
+        let oauth_state = OAUTH_STATE.lock()?;
         oauth_state
             .as_ref()
             .map(|s| s.code_verifier.clone())
-            .ok_or("No OAuth state found - login flow not started")?
+            .ok_or_else(|| AppError::new(ErrorKind::AuthRequired, "No OAuth state found - login flow not started"))?
     };
 
     let redirect_uri = format!("http://localhost:{}/callback", OAUTH_CALLBACK_PORT);
@@ -241,7 +245,7 @@ pub async fn exchange_oauth_code(
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        .or_error(ErrorKind::Internal, "Failed to create HTTP client")?;
 
     println!("[OAuth] Sending token exchange request to Google...");
     let token_response = client
@@ -256,14 +260,15 @@ pub async fn exchange_oauth_code(
         ])
         .send()
         .await
-        .map_err(|e| format!("Token exchange request failed: {}", e))?;
+        .or_error(ErrorKind::Network, "Token exchange request failed")?;
 
     println!("[OAuth] Got response with status: {}", token_response.status());
 
-    if !token_response.status().is_success() {
+    let token_status = token_response.status();
+    if !token_status.is_success() {
         let error_text = token_response.text().await.unwrap_or_default();
         println!("[OAuth] Token exchange error: {}", error_text);
-        return Err(format!("Token exchange failed: {}", error_text));
+        return Err(AppError::http(token_status, format!("Token exchange failed: {}", error_text)));
     }
 
     #[derive(Deserialize)]
@@ -280,7 +285,7 @@ pub async fn exchange_oauth_code(
     let google_tokens: GoogleTokenResponse = token_response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse token response: {}", e))?;
+        .or_error(ErrorKind::Parse, "Failed to parse token response")?;
 
     println!("[OAuth] Got Google tokens, now signing in to Firebase");
 
@@ -298,7 +303,7 @@ pub async fn exchange_oauth_code(
         }))
         .send()
         .await
-        .map_err(|e| format!("Firebase sign-in failed: {}", e))?;
+        .or_error(ErrorKind::Network, "Firebase sign-in failed")?;
 
     let firebase_status = firebase_response.status();
     println!("[OAuth] Firebase response status: {}", firebase_status);
@@ -306,7 +311,7 @@ pub async fn exchange_oauth_code(
     if !firebase_status.is_success() {
         let error_text = firebase_response.text().await.unwrap_or_default();
         println!("[OAuth] Firebase error: {}", error_text);
-        return Err(format!("Firebase sign-in failed: {}", error_text));
+        return Err(AppError::http(firebase_status, format!("Firebase sign-in failed: {}", error_text)));
     }
 
     // Get response text first for debugging
@@ -328,7 +333,7 @@ pub async fn exchange_oauth_code(
     }
 
     let firebase_auth: FirebaseSignInResponse = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse Firebase response: {}", e))?;
+        .or_error(ErrorKind::Parse, "Failed to parse Firebase response")?;
 
     let user_email = firebase_auth.email.unwrap_or_else(|| "unknown@email.com".to_string());
     println!("[OAuth] Firebase sign-in successful for: {}", user_email);
@@ -352,7 +357,7 @@ pub async fn exchange_oauth_code(
 
     // Clear OAuth state
     {
-        let mut oauth_state = OAUTH_STATE.lock().unwrap();
+        let mut oauth_state = OAUTH_STATE.lock()?;
         *oauth_state = None;
     }
 
@@ -377,8 +382,9 @@ pub fn get_stored_auth() -> Option<StoredAuth> {
 }
 
 /// Refresh the authentication token using the refresh token
-pub async fn refresh_auth_token(firebase_api_key: &str) -> Result<StoredAuth, String> {
-    let current_auth = get_auth_from_keychain().ok_or("No stored auth found")?;
+pub async fn refresh_auth_token(firebase_api_key: &str) -> AppResult<StoredAuth> {
+    let current_auth = get_auth_from_keychain()
+        .ok_or_else(|| AppError::new(ErrorKind::AuthRequired, "No stored auth found"))?;
 
     println!("[OAuth] Refreshing auth token for: {}", current_auth.email);
 
@@ -394,13 +400,14 @@ pub async fn refresh_auth_token(firebase_api_key: &str) -> Result<StoredAuth, St
         ])
         .send()
         .await
-        .map_err(|e| format!("Token refresh request failed: {}", e))?;
+        .or_error(ErrorKind::Network, "Token refresh request failed")?;
 
-    if !response.status().is_success() {
+    let status = response.status();
+    if !status.is_success() {
         let error_text = response.text().await.unwrap_or_default();
         // Clear invalid auth
         clear_auth_from_keychain().ok();
-        return Err(format!("Token refresh failed: {}", error_text));
+        return Err(AppError::http(status, format!("Token refresh failed: {}", error_text)));
     }
 
     #[derive(Deserialize)]
@@ -413,7 +420,7 @@ pub async fn refresh_auth_token(firebase_api_key: &str) -> Result<StoredAuth, St
     let refresh_data: RefreshResponse = response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse refresh response: {}", e))?;
+        .or_error(ErrorKind::Parse, "Failed to parse refresh response")?;
 
     let expires_in_secs: i64 = refresh_data.expires_in.parse().unwrap_or(3600);
     let expires_at = chrono::Utc::now().timestamp() + expires_in_secs;
@@ -434,13 +441,13 @@ pub async fn refresh_auth_token(firebase_api_key: &str) -> Result<StoredAuth, St
 }
 
 /// Logout the user by clearing stored authentication data
-pub fn logout() -> Result<(), String> {
+pub fn logout() -> AppResult<()> {
     println!("[OAuth] Logging out - clearing keychain");
     clear_auth_from_keychain()?;
 
     // Clear OAuth state
     {
-        let mut oauth_state = OAUTH_STATE.lock().unwrap();
+        let mut oauth_state = OAUTH_STATE.lock()?;
         *oauth_state = None;
     }
 
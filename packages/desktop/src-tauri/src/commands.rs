@@ -3,9 +3,10 @@
 use tauri::AppHandle;
 
 use crate::auth::{LicenseStatus, OAuthStartResult, StoredAuth};
-use crate::download::{DownloadJob, QueueStatus, DownloadContext, TrackMetadata};
-use crate::download::queue::{DOWNLOAD_QUEUE};
-use crate::api_types::HasodApiClient;
+use crate::download::services::{collection_source_for_url, ArtistReleaseFilter, Collection, SpotifyArtistReleases, SpotifyDownloader};
+use crate::download::links::{normalize_link, LinkPreview};
+use crate::download::{DownloadJob, QueueStatus, DownloadContext, HistoryEntry, HistoryFilter, ParsedLink};
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
 use crate::utils::{get_or_create_device_uuid, get_hardware_id};
 
 // Constants needed for commands
@@ -28,7 +29,7 @@ pub fn get_hardware_device_id() -> String {
 }
 
 #[tauri::command]
-pub fn set_auth_token(token: String) -> Result<(), String> {
+pub fn set_auth_token(token: String) -> AppResult<()> {
     let uuid = get_or_create_device_uuid();
     crate::auth::save_auth_token(&token, &uuid);
     Ok(())
@@ -41,7 +42,7 @@ pub fn get_registration_url() -> String {
 }
 
 #[tauri::command]
-pub async fn check_license(user_email: Option<String>) -> Result<LicenseStatus, String> {
+pub async fn check_license(user_email: Option<String>) -> AppResult<LicenseStatus> {
     let device_uuid = get_or_create_device_uuid();
     crate::auth::check_license(user_email, device_uuid).await
 }
@@ -51,17 +52,17 @@ pub async fn check_license(user_email: Option<String>) -> Result<LicenseStatus,
 // ============================================================================
 
 #[tauri::command]
-pub fn start_google_login() -> Result<OAuthStartResult, String> {
+pub fn start_google_login() -> AppResult<OAuthStartResult> {
     crate::auth::start_google_login(GOOGLE_OAUTH_CLIENT_ID)
 }
 
 #[tauri::command]
-pub async fn wait_for_oauth_callback(app: AppHandle) -> Result<String, String> {
+pub async fn wait_for_oauth_callback(app: AppHandle) -> AppResult<String> {
     crate::auth::wait_for_oauth_callback(app).await
 }
 
 #[tauri::command]
-pub async fn exchange_oauth_code(code: String) -> Result<StoredAuth, String> {
+pub async fn exchange_oauth_code(code: String) -> AppResult<StoredAuth> {
     crate::auth::exchange_oauth_code(code, GOOGLE_OAUTH_CLIENT_ID, GOOGLE_OAUTH_CLIENT_SECRET, FIREBASE_API_KEY).await
 }
 
@@ -71,12 +72,12 @@ pub fn get_stored_auth() -> Option<StoredAuth> {
 }
 
 #[tauri::command]
-pub async fn refresh_auth_token() -> Result<StoredAuth, String> {
+pub async fn refresh_auth_token() -> AppResult<StoredAuth> {
     crate::auth::refresh_auth_token(FIREBASE_API_KEY).await
 }
 
 #[tauri::command]
-pub fn logout() -> Result<(), String> {
+pub fn logout() -> AppResult<()> {
     crate::auth::logout()
 }
 
@@ -84,158 +85,368 @@ pub fn logout() -> Result<(), String> {
 // Download Queue Commands
 // ============================================================================
 
+/// Validate a download directory override and route the jobs to it
+/// `output_dir` of None (or empty) keeps the directory from settings
+fn apply_output_dir(app: &AppHandle, jobs: &mut [DownloadJob], output_dir: Option<String>) -> AppResult<()> {
+    let Some(output_dir) = output_dir.filter(|dir| !dir.trim().is_empty()) else {
+        return Ok(());
+    };
+
+    let output_dir = crate::utils::validate_download_dir(&output_dir)?
+        .to_string_lossy()
+        .to_string();
+    crate::utils::filesystem::allow_asset_directory(app, &output_dir);
+
+    for job in jobs.iter_mut() {
+        job.output_dir = Some(output_dir.clone());
+    }
+    Ok(())
+}
+
 #[tauri::command]
-pub fn add_to_queue(url: String) -> Result<DownloadJob, String> {
-    let job = crate::download::DownloadJob::new(url);
-    let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-    queue.push(job.clone());
-    Ok(job)
+pub async fn add_to_queue(app: AppHandle, url: String, output_dir: Option<String>) -> AppResult<DownloadJob> {
+    let url = normalize_link(&url).await;
+    let mut job = crate::download::DownloadJob::new(url);
+    apply_output_dir(&app, std::slice::from_mut(&mut job), output_dir)?;
+    crate::download::QueueManager::add_job(job)
 }
 
 #[tauri::command]
-pub fn add_multiple_to_queue(urls: Vec<String>) -> Result<Vec<DownloadJob>, String> {
-    let mut jobs = Vec::new();
-    let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-    
+pub async fn add_multiple_to_queue(
+    app: AppHandle,
+    urls: Vec<String>,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    let mut jobs = Vec::with_capacity(urls.len());
     for url in urls {
-        let job = crate::download::DownloadJob::new(url);
-        queue.push(job.clone());
-        jobs.push(job);
+        jobs.push(crate::download::DownloadJob::new(normalize_link(&url).await));
     }
-    
-    Ok(jobs)
+    apply_output_dir(&app, &mut jobs, output_dir)?;
+
+    crate::download::QueueManager::add_jobs(jobs)
 }
 
-#[tauri::command]
-pub async fn add_spotify_album_to_queue(album_url: String) -> Result<Vec<DownloadJob>, String> {
-    println!("[Album] Processing Spotify album: {}", album_url);
+/// Expand an album/playlist link through its source and queue every track
+async fn queue_collection(app: &AppHandle, url: &str, output_dir: Option<String>) -> AppResult<Vec<DownloadJob>> {
+    let url = normalize_link(url).await;
+    let source = collection_source_for_url(&url)?;
+    let collection = source.expand_collection(app, &url).await?;
+    queue_expanded(app, collection, output_dir)
+}
 
-    let api_client = HasodApiClient::production();
-    let album_metadata = api_client.get_spotify_album_metadata(&album_url).await?;
+fn queue_expanded(app: &AppHandle, collection: Collection, output_dir: Option<String>) -> AppResult<Vec<DownloadJob>> {
+    let mut jobs = collection.jobs;
+    apply_output_dir(app, &mut jobs, output_dir)?;
+    let jobs = crate::download::QueueManager::add_jobs(jobs)?;
+    println!("[Queue] ✅ Queued {} tracks from '{}'", jobs.len(), collection.name);
+    Ok(jobs)
+}
 
-    println!("[Album] Album: '{}' by '{}' ({} tracks)",
-             album_metadata.album.name,
-             album_metadata.album.artist,
-             album_metadata.tracks.len());
+#[tauri::command]
+pub async fn add_collection_to_queue(
+    app: AppHandle,
+    url: String,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    queue_collection(&app, &url, output_dir).await
+}
 
-    let mut jobs = Vec::new();
-    let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
+#[tauri::command]
+pub async fn add_spotify_album_to_queue(
+    app: AppHandle,
+    album_url: String,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    queue_collection(&app, &album_url, output_dir).await
+}
 
-    let album_context = DownloadContext::Album(album_metadata.album.name.clone());
+#[tauri::command]
+pub async fn add_spotify_playlist_to_queue(
+    app: AppHandle,
+    playlist_url: String,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    queue_collection(&app, &playlist_url, output_dir).await
+}
 
-    for track in album_metadata.tracks {
-        let track_url = format!("https://open.spotify.com/track/{}", track.track_id);
-        let mut job = crate::download::DownloadJob::new(track_url);
+/// Queue links picked from pasted text: albums/playlists/artists are expanded, tracks queued as-is
+/// A link that fails doesn't stop the others; the command fails only if nothing was queued
+#[tauri::command]
+pub async fn add_links_to_queue(
+    app: AppHandle,
+    urls: Vec<String>,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    queue_links(&app, urls, output_dir, &DownloadContext::Single).await
+}
+
+/// Shared by add_links_to_queue, playlist import and the clipboard watcher
+/// Track links get `context`; albums/playlists keep their own
+pub(crate) async fn queue_links(
+    app: &AppHandle,
+    urls: Vec<String>,
+    output_dir: Option<String>,
+    context: &DownloadContext,
+) -> AppResult<Vec<DownloadJob>> {
+    let mut queued = Vec::new();
+    let mut tracks = Vec::new();
+    let mut first_error = None;
 
-        job.metadata = TrackMetadata {
-            title: track.name,
-            artist: track.artists,
-            album: track.album,
-            duration: Some((track.duration_ms / 1000) as u32),
-            thumbnail: Some(track.image_url),
+    for url in urls {
+        let link = match crate::download::links::resolve_link(&url).await {
+            Ok(link) => link,
+            Err(e) => {
+                println!("[Queue] Skipping {}: {}", url, e);
+                first_error.get_or_insert(e);
+                continue;
+            }
         };
-        job.download_context = Some(album_context.clone());
+        if link.kind.is_collection() {
+            match queue_collection(app, &link.url, output_dir.clone()).await {
+                Ok(jobs) => queued.extend(jobs),
+                Err(e) => {
+                    println!("[Queue] Failed to expand {}: {}", link.url, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        } else {
+            let mut job = DownloadJob::new(link.url);
+            job.download_context = Some(context.clone());
+            tracks.push(job);
+        }
+    }
 
-        queue.push(job.clone());
-        jobs.push(job);
+    if !tracks.is_empty() {
+        apply_output_dir(app, &mut tracks, output_dir)?;
+        queued.extend(crate::download::QueueManager::add_jobs(tracks)?);
     }
 
-    println!("[Album] ✅ Queued {} tracks from album", jobs.len());
-    Ok(jobs)
+    match first_error {
+        Some(e) if queued.is_empty() => Err(e),
+        _ => Ok(queued),
+    }
 }
 
+/// Import a playlist file exported from another player (M3U/M3U8, CSV or a text list)
+/// The frontend reads the file and sends its name and contents. Rows with links are queued
+/// like pasted links; rows with only artist/title are found on YouTube when they run.
+/// Tracks are grouped in a playlist named after the file.
 #[tauri::command]
-pub async fn add_spotify_playlist_to_queue(playlist_url: String) -> Result<Vec<DownloadJob>, String> {
-    println!("[Playlist] Processing Spotify playlist: {}", playlist_url);
+pub async fn import_playlist(
+    app: AppHandle,
+    file_name: String,
+    contents: String,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    use crate::download::import::{parse_playlist, ImportRow};
+
+    let playlist = parse_playlist(&file_name, &contents);
+    let context = DownloadContext::Playlist(playlist.name.clone());
+
+    let mut urls = Vec::new();
+    let mut searches = Vec::new();
+    for row in playlist.rows {
+        match row {
+            ImportRow::Link(url) => urls.push(url),
+            ImportRow::Track(track) => {
+                let mut job = DownloadJob::for_search(track.to_metadata());
+                job.download_context = Some(context.clone());
+                searches.push(job);
+            }
+        }
+    }
+    if urls.is_empty() && searches.is_empty() {
+        return Err(AppError::new(ErrorKind::InvalidInput, format!("No tracks found in {}", file_name)));
+    }
 
-    let api_client = HasodApiClient::production();
-    let playlist_metadata = api_client.get_spotify_playlist_metadata(&playlist_url).await?;
+    let mut queued = Vec::new();
+    if !urls.is_empty() {
+        match queue_links(&app, urls, output_dir.clone(), &context).await {
+            Ok(jobs) => queued = jobs,
+            Err(e) if searches.is_empty() => return Err(e),
+            Err(e) => println!("[Import] No links from '{}' could be queued: {}", playlist.name, e),
+        }
+    }
+    if !searches.is_empty() {
+        apply_output_dir(&app, &mut searches, output_dir)?;
+        queued.extend(crate::download::QueueManager::add_jobs(searches)?);
+    }
 
-    println!("[Playlist] Playlist: '{}' by '{}' ({} tracks)",
-             playlist_metadata.playlist.name,
-             playlist_metadata.playlist.owner,
-             playlist_metadata.tracks.len());
+    println!("[Import] ✅ Queued {} jobs from '{}'", queued.len(), playlist.name);
+    Ok(queued)
+}
 
-    let mut jobs = Vec::new();
-    let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
+/// Title and author of a link, shown before it is queued (e.g. in the smart paste picker)
+#[tauri::command]
+pub async fn preview_link(url: String) -> AppResult<LinkPreview> {
+    crate::download::links::preview_link(&url).await
+}
 
-    let playlist_context = DownloadContext::Playlist(playlist_metadata.playlist.name.clone());
+/// List an artist's releases so the user can choose which to download
+#[tauri::command]
+pub async fn get_spotify_artist_releases(
+    artist_url: String,
+    filter: Option<ArtistReleaseFilter>,
+) -> AppResult<SpotifyArtistReleases> {
+    let artist_url = normalize_link(&artist_url).await;
+    SpotifyDownloader::get_artist_releases(&artist_url, &filter.unwrap_or_default()).await
+}
 
-    for track in playlist_metadata.tracks {
-        let track_url = format!("https://open.spotify.com/track/{}", track.track_id);
-        let mut job = crate::download::DownloadJob::new(track_url);
+/// Queue every track of the chosen releases, skipping tracks already queued from another release
+#[tauri::command]
+pub async fn add_spotify_artist_releases_to_queue(
+    app: AppHandle,
+    artist_name: String,
+    release_urls: Vec<String>,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    let collection = SpotifyDownloader::expand_releases(&artist_name, &release_urls).await?;
+    queue_expanded(&app, collection, output_dir)
+}
 
-        job.metadata = TrackMetadata {
-            title: track.name,
-            artist: track.artists,
-            album: track.album,
-            duration: Some((track.duration_ms / 1000) as u32),
-            thumbnail: Some(track.image_url),
-        };
-        job.download_context = Some(playlist_context.clone());
+#[tauri::command]
+pub async fn add_spotify_artist_top_tracks_to_queue(
+    app: AppHandle,
+    artist_url: String,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    let artist_url = normalize_link(&artist_url).await;
+    let collection = SpotifyDownloader::expand_top_tracks(&artist_url).await?;
+    queue_expanded(&app, collection, output_dir)
+}
 
-        queue.push(job.clone());
-        jobs.push(job);
-    }
+#[tauri::command]
+pub async fn add_apple_music_album_to_queue(
+    app: AppHandle,
+    album_url: String,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    queue_collection(&app, &album_url, output_dir).await
+}
 
-    println!("[Playlist] ✅ Queued {} tracks from playlist", jobs.len());
-    Ok(jobs)
+#[tauri::command]
+pub async fn add_apple_music_playlist_to_queue(
+    app: AppHandle,
+    playlist_url: String,
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    queue_collection(&app, &playlist_url, output_dir).await
 }
 
 #[tauri::command]
 pub async fn add_youtube_playlist_to_queue(
     app: AppHandle,
     playlist_url: String,
-) -> Result<Vec<DownloadJob>, String> {
-    let (playlist_name, video_urls) = crate::download::services::YouTubeDownloader::extract_playlist_urls(&app, &playlist_url).await?;
+    output_dir: Option<String>,
+) -> AppResult<Vec<DownloadJob>> {
+    queue_collection(&app, &playlist_url, output_dir).await
+}
 
-    let mut jobs = Vec::new();
-    let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
+#[tauri::command]
+pub fn get_queue_status() -> AppResult<QueueStatus> {
+    crate::download::QueueManager::get_status()
+}
+
+#[tauri::command]
+pub fn clear_completed_jobs() -> AppResult<usize> {
+    crate::download::QueueManager::clear_completed()
+}
 
-    let playlist_context = DownloadContext::Playlist(playlist_name.clone());
+#[tauri::command]
+pub fn remove_from_queue(job_id: String) -> AppResult<bool> {
+    crate::download::QueueManager::remove_job(&job_id)
+}
 
-    for video_url in video_urls {
-        let mut job = crate::download::DownloadJob::new(video_url);
-        job.download_context = Some(playlist_context.clone());
-        queue.push(job.clone());
-        jobs.push(job);
-    }
+#[tauri::command]
+pub fn cancel_job(app: AppHandle, job_id: String) -> AppResult<()> {
+    crate::download::QueueManager::cancel_job(&job_id)?;
+    crate::download::QueueManager::emit_update(&app);
+    Ok(())
+}
 
-    println!("[YouTube Playlist] ✅ Queued {} videos from playlist", jobs.len());
-    Ok(jobs)
+#[tauri::command]
+pub fn pause_job(app: AppHandle, job_id: String) -> AppResult<()> {
+    crate::download::QueueManager::pause_job(&job_id)?;
+    crate::download::QueueManager::emit_update(&app);
+    Ok(())
 }
 
 #[tauri::command]
-pub fn get_queue_status() -> Result<QueueStatus, String> {
-    crate::download::QueueManager::get_status()
+pub fn resume_job(app: AppHandle, job_id: String) -> AppResult<()> {
+    crate::download::QueueManager::resume_job(&job_id)?;
+    crate::download::QueueManager::emit_update(&app);
+
+    // Make sure a worker picks the job up (no-op if the queue is already running)
+    tauri::async_runtime::spawn(crate::download::QueueManager::start_processing(app));
+    Ok(())
 }
 
+/// Pick the source for a job waiting for review (one of its candidates or another YouTube link)
 #[tauri::command]
-pub fn clear_completed_jobs() -> Result<usize, String> {
-    crate::download::QueueManager::clear_completed()
+pub fn resolve_job_source(app: AppHandle, job_id: String, candidate_url: String) -> AppResult<()> {
+    crate::download::QueueManager::resolve_job_source(&job_id, &candidate_url)?;
+    crate::download::QueueManager::emit_update(&app);
+
+    tauri::async_runtime::spawn(crate::download::QueueManager::start_processing(app));
+    Ok(())
 }
 
 #[tauri::command]
-pub fn remove_from_queue(job_id: String) -> Result<bool, String> {
-    crate::download::QueueManager::remove_job(&job_id)
+pub fn retry_failed_jobs(app: AppHandle) -> AppResult<usize> {
+    let count = crate::download::QueueManager::retry_failed()?;
+    crate::download::QueueManager::emit_update(&app);
+
+    if count > 0 {
+        tauri::async_runtime::spawn(crate::download::QueueManager::start_processing(app));
+    }
+    Ok(count)
 }
 
 #[tauri::command]
-pub fn clear_all_queue() -> Result<usize, String> {
+pub fn clear_all_queue() -> AppResult<usize> {
     crate::download::QueueManager::clear_all()
 }
 
 #[tauri::command]
-pub async fn start_queue_processing(app: AppHandle) -> Result<(), String> {
+pub async fn start_queue_processing(app: AppHandle) -> AppResult<()> {
     crate::download::QueueManager::start_processing(app).await
 }
 
+// ============================================================================
+// Download History Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_download_history(filter: Option<HistoryFilter>) -> AppResult<Vec<HistoryEntry>> {
+    crate::download::DownloadHistory::query(&filter.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn remove_history_entry(entry_id: String) -> AppResult<bool> {
+    crate::download::DownloadHistory::remove(&entry_id)
+}
+
+#[tauri::command]
+pub fn clear_download_history() -> AppResult<usize> {
+    crate::download::DownloadHistory::clear()
+}
+
+#[tauri::command]
+pub fn redownload_from_history(app: AppHandle, entry_id: String) -> AppResult<DownloadJob> {
+    let job = crate::download::DownloadHistory::job_for_redownload(&entry_id)?;
+    let job = crate::download::QueueManager::add_job(job)?;
+    crate::download::QueueManager::emit_update(&app);
+
+    tauri::async_runtime::spawn(crate::download::QueueManager::start_processing(app));
+    Ok(job)
+}
+
 // ============================================================================
 // Filesystem Commands
 // ============================================================================
 
 #[tauri::command]
-pub async fn open_file_location(file_path: String) -> Result<(), String> {
+pub async fn open_file_location(file_path: String) -> AppResult<()> {
     use std::process::Command;
 
     #[cfg(target_os = "macos")]
@@ -244,7 +455,7 @@ pub async fn open_file_location(file_path: String) -> Result<(), String> {
             .arg("-R")
             .arg(&file_path)
             .spawn()
-            .map_err(|e| format!("Failed to open file location: {}", e))?;
+            .or_error(ErrorKind::Io, "Failed to open file location")?;
     }
 
     #[cfg(target_os = "windows")]
@@ -253,7 +464,7 @@ pub async fn open_file_location(file_path: String) -> Result<(), String> {
             .arg("/select,")
             .arg(&file_path)
             .spawn()
-            .map_err(|e| format!("Failed to open file location: {}", e))?;
+            .or_error(ErrorKind::Io, "Failed to open file location")?;
     }
 
     #[cfg(target_os = "linux")]
@@ -265,7 +476,7 @@ pub async fn open_file_location(file_path: String) -> Result<(), String> {
             Command::new("xdg-open")
                 .arg(parent)
                 .spawn()
-                .map_err(|e| format!("Failed to open file location: {}", e))?;
+                .or_error(ErrorKind::Io, "Failed to open file location")?;
         }
     }
 
@@ -278,24 +489,40 @@ pub fn get_download_dir() -> String {
 }
 
 #[tauri::command]
-pub fn create_download_dir() -> Result<String, String> {
+pub fn create_download_dir() -> AppResult<String> {
     crate::utils::create_download_dir()
 }
 
+/// Change the download directory (None resets to the default) and return the directory now in use
+#[tauri::command]
+pub fn set_download_dir(app: AppHandle, dir: Option<String>) -> AppResult<String> {
+    crate::utils::set_custom_download_dir(dir)?;
+    let download_dir = crate::utils::get_download_dir();
+    crate::utils::filesystem::allow_asset_directory(&app, &download_dir);
+    Ok(download_dir)
+}
+
+/// Check a directory without saving it (writable, enough free space)
+#[tauri::command]
+pub fn validate_download_dir(dir: String) -> AppResult<String> {
+    let path = crate::utils::validate_download_dir(&dir)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 // ============================================================================
 // Legacy Download Commands (backward compatibility)
 // ============================================================================
 
 #[tauri::command]
-pub async fn download_youtube(app: AppHandle, url: String, _output_dir: String) -> Result<String, String> {
-    let job = add_to_queue(url)?;
+pub async fn download_youtube(app: AppHandle, url: String, output_dir: String) -> AppResult<String> {
+    let job = add_to_queue(app.clone(), url, Some(output_dir))?;
     crate::download::QueueManager::start_processing(app).await?;
     Ok(format!("Added to queue: {}", job.id))
 }
 
 #[tauri::command]
-pub async fn download_spotify(app: AppHandle, url: String, _output_dir: String) -> Result<String, String> {
-    let job = add_to_queue(url)?;
+pub async fn download_spotify(app: AppHandle, url: String, output_dir: String) -> AppResult<String> {
+    let job = add_to_queue(app.clone(), url, Some(output_dir))?;
     crate::download::QueueManager::start_processing(app).await?;
     Ok(format!("Added to queue: {}", job.id))
 }
@@ -307,7 +534,7 @@ pub async fn download_spotify(app: AppHandle, url: String, _output_dir: String)
 /// Toggle the floating panel window
 /// Works on all platforms (macOS: native NSPanel, Windows/Linux: Tauri window)
 #[tauri::command]
-pub fn toggle_floating_window(app: AppHandle) -> Result<(), String> {
+pub fn toggle_floating_window(app: AppHandle) -> AppResult<()> {
     use crate::platform::FloatingPanelManager;
     FloatingPanelManager::toggle(app)
 }
@@ -331,29 +558,57 @@ pub fn is_floating_window_open(app: AppHandle) -> bool {
 /// Get URL from clipboard (cross-platform)
 /// macOS: pbpaste, Windows: PowerShell, Linux: xclip
 #[tauri::command]
-pub async fn get_clipboard_url() -> Result<String, String> {
+pub async fn get_clipboard_url() -> AppResult<String> {
     use crate::platform::ClipboardManager;
     ClipboardManager::get_url().await
 }
 
 #[tauri::command]
-pub fn handle_dropped_link(url: String) -> Result<String, String> {
-    // Normalize Spotify URIs to URLs if needed
-    let normalized_url = if url.starts_with("spotify:") {
-        let parts: Vec<&str> = url.split(':').collect();
-        if parts.len() >= 3 {
-            format!("https://open.spotify.com/{}/{}", parts[1], parts[2])
-        } else {
-            url
-        }
-    } else {
-        url
-    };
-
+pub async fn handle_dropped_link(url: String) -> AppResult<String> {
+    let normalized_url = normalize_link(&url).await;
     println!("[DragDrop] Normalized URL: {}", normalized_url);
     Ok(normalized_url)
 }
 
+/// Service, kind and ID of a link (short links are followed), for routing it in the UI
+#[tauri::command]
+pub async fn parse_link(url: String) -> AppResult<ParsedLink> {
+    crate::download::links::resolve_link(&url).await
+}
+
+/// Every music link in a block of text (a copied chat message, a list), for the user to confirm
+#[tauri::command]
+pub async fn extract_links(text: String) -> AppResult<Vec<ParsedLink>> {
+    Ok(crate::download::links::resolve_links(&text).await)
+}
+
+/// Offer the music links in text dropped on the floating button for confirmation
+/// The main window's link picker gets them ("dropped-links" event) and is brought to the front,
+/// so a dropped chat message doesn't queue whole albums or artists unseen
+#[tauri::command]
+pub async fn offer_dropped_links(app: AppHandle, text: String) -> AppResult<usize> {
+    use tauri::{Emitter, Manager};
+
+    let links = crate::download::links::resolve_links(&text).await;
+    if !links.is_empty() {
+        println!("[Queue] Offering {} dropped link(s)", links.len());
+        if let Some(window) = app.get_webview_window("main") {
+            window.show().ok();
+            window.set_focus().ok();
+        }
+        app.emit("dropped-links", &links).ok();
+    }
+    Ok(links.len())
+}
+
+/// Every music link in the clipboard's text
+#[tauri::command]
+pub async fn extract_clipboard_links() -> AppResult<Vec<ParsedLink>> {
+    use crate::platform::ClipboardManager;
+    let text = ClipboardManager::get_text().await?;
+    Ok(crate::download::links::resolve_links(&text).await)
+}
+
 // ============================================================================
 // Settings Commands
 // ============================================================================
@@ -364,6 +619,92 @@ pub fn get_english_only_mode() -> bool {
 }
 
 #[tauri::command]
-pub fn set_english_only_mode(enabled: bool) -> Result<(), String> {
+pub fn set_english_only_mode(enabled: bool) -> AppResult<()> {
     crate::utils::set_english_only_mode(enabled)
 }
+
+#[tauri::command]
+pub fn get_max_concurrent_downloads() -> usize {
+    crate::utils::get_max_concurrent_downloads()
+}
+
+#[tauri::command]
+pub fn set_max_concurrent_downloads(value: usize) -> AppResult<()> {
+    crate::utils::set_max_concurrent_downloads(value)
+}
+
+#[tauri::command]
+pub fn get_resource_limits() -> crate::utils::ResourceLimits {
+    crate::utils::get_resource_limits()
+}
+
+#[tauri::command]
+pub fn set_resource_limits(limits: crate::utils::ResourceLimits) -> AppResult<()> {
+    crate::utils::set_resource_limits(limits)
+}
+
+#[tauri::command]
+pub fn get_duplicate_policy() -> crate::utils::DuplicatePolicy {
+    crate::utils::get_duplicate_policy()
+}
+
+#[tauri::command]
+pub fn set_duplicate_policy(policy: crate::utils::DuplicatePolicy) -> AppResult<()> {
+    crate::utils::set_duplicate_policy(policy)
+}
+
+#[tauri::command]
+pub fn get_output_templates() -> crate::utils::OutputTemplates {
+    crate::utils::get_output_templates()
+}
+
+#[tauri::command]
+pub fn set_output_templates(templates: crate::utils::OutputTemplates) -> AppResult<()> {
+    crate::utils::set_output_templates(templates)
+}
+
+/// Render a template with sample metadata so the settings screen can show the resulting path
+#[tauri::command]
+pub fn preview_output_path(template: String, context: Option<DownloadContext>) -> AppResult<String> {
+    let context = context.unwrap_or(DownloadContext::Single);
+    let path = crate::utils::filesystem::render_output_path(
+        &crate::utils::get_download_dir(),
+        &template,
+        &crate::utils::template::sample_metadata(),
+        &context,
+        crate::utils::get_audio_format().extension(),
+    )?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn get_audio_format() -> crate::download::AudioFormat {
+    crate::utils::get_audio_format()
+}
+
+#[tauri::command]
+pub fn set_audio_format(format: crate::download::AudioFormat) -> AppResult<()> {
+    crate::utils::set_audio_format(format)
+}
+
+#[tauri::command]
+pub fn get_lyrics_settings() -> crate::utils::LyricsSettings {
+    crate::utils::get_lyrics_settings()
+}
+
+#[tauri::command]
+pub fn set_lyrics_settings(lyrics: crate::utils::LyricsSettings) -> AppResult<()> {
+    crate::utils::set_lyrics_settings(lyrics)
+}
+
+#[tauri::command]
+pub fn get_clipboard_watch_settings() -> crate::utils::ClipboardWatchSettings {
+    crate::utils::get_clipboard_watch_settings()
+}
+
+#[tauri::command]
+pub fn set_clipboard_watch_settings(watch: crate::utils::ClipboardWatchSettings) -> AppResult<()> {
+    crate::utils::set_clipboard_watch_settings(watch)?;
+    crate::platform::ClipboardWatcher::update_settings(crate::utils::get_clipboard_watch_settings());
+    Ok(())
+}
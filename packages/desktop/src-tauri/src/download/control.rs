@@ -0,0 +1,232 @@
+// Per-job control signals for in-flight downloads (pause / cancel)
+// Workers register a job when they claim it; service download loops watch the signal
+// and kill their sidecar process or drop their HTTP stream when asked to stop
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+use crate::error::{AppError, AppResult, ErrorKind};
+
+// Signal senders for jobs currently held by a worker
+static JOB_SIGNALS: std::sync::LazyLock<Mutex<HashMap<String, watch::Sender<JobSignal>>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Partial files written by each job, removed when the job is cancelled
+static PARTIAL_FILES: std::sync::LazyLock<Mutex<HashMap<String, Vec<PathBuf>>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Requested state for an in-flight job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobSignal {
+    Run,
+    Pause,
+    Cancel,
+}
+
+impl JobSignal {
+    /// Human-readable description of why a download stopped
+    pub fn stop_message(&self) -> &'static str {
+        match self {
+            JobSignal::Run => "Download stopped",
+            JobSignal::Pause => "Download paused",
+            JobSignal::Cancel => "Download cancelled",
+        }
+    }
+
+    /// Error returned by a download that stopped because of this signal
+    pub fn stop_error(&self) -> AppError {
+        let kind = match self {
+            JobSignal::Pause => ErrorKind::Paused,
+            JobSignal::Run | JobSignal::Cancel => ErrorKind::Cancelled,
+        };
+        AppError::new(kind, self.stop_message())
+    }
+}
+
+/// Receiving side of a job's control signal
+pub struct JobSignalReceiver(watch::Receiver<JobSignal>);
+
+impl JobSignalReceiver {
+    /// Resolves once the job is asked to pause or cancel (immediately if it already was)
+    /// Never resolves for jobs that aren't registered
+    pub async fn stopped(&mut self) -> JobSignal {
+        loop {
+            let current = *self.0.borrow_and_update();
+            if current != JobSignal::Run {
+                return current;
+            }
+            if self.0.changed().await.is_err() {
+                // Sender dropped - the job can no longer be signalled
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Job Control
+// ============================================================================
+
+pub struct JobControl;
+
+impl JobControl {
+    /// Register a job as in flight so it can be paused or cancelled
+    pub fn register(job_id: &str) {
+        if let Ok(mut signals) = JOB_SIGNALS.lock() {
+            let (sender, _) = watch::channel(JobSignal::Run);
+            signals.insert(job_id.to_string(), sender);
+        }
+    }
+
+    /// Unregister a job once its worker is done with it
+    pub fn unregister(job_id: &str) {
+        if let Ok(mut signals) = JOB_SIGNALS.lock() {
+            signals.remove(job_id);
+        }
+    }
+
+    /// Subscribe to a job's control signal
+    pub fn subscribe(job_id: &str) -> JobSignalReceiver {
+        let receiver = JOB_SIGNALS
+            .lock()
+            .ok()
+            .and_then(|signals| signals.get(job_id).map(|sender| sender.subscribe()));
+
+        match receiver {
+            Some(receiver) => JobSignalReceiver(receiver),
+            None => {
+                // Not registered (e.g. legacy direct download) - receiver that never fires
+                let (_, receiver) = watch::channel(JobSignal::Run);
+                JobSignalReceiver(receiver)
+            }
+        }
+    }
+
+    /// Send a signal to an in-flight job
+    /// Returns false if the job isn't currently held by a worker
+    pub fn signal(job_id: &str, signal: JobSignal) -> bool {
+        JOB_SIGNALS
+            .lock()
+            .ok()
+            .and_then(|signals| signals.get(job_id).map(|sender| sender.send_replace(signal)))
+            .is_some()
+    }
+
+    /// Get the signal most recently sent to a job (Run if none)
+    pub fn requested(job_id: &str) -> JobSignal {
+        JOB_SIGNALS
+            .lock()
+            .ok()
+            .and_then(|signals| signals.get(job_id).map(|sender| *sender.borrow()))
+            .unwrap_or(JobSignal::Run)
+    }
+
+    /// Err with the stop error if the job was asked to pause or cancel
+    /// Called between download phases (lookup, search, download, tagging) so a stopped job
+    /// never goes on to the next one
+    pub fn check(job_id: &str) -> AppResult<()> {
+        match Self::requested(job_id) {
+            JobSignal::Run => Ok(()),
+            signal => Err(signal.stop_error()),
+        }
+    }
+
+    /// Record a file the job is writing so it can be removed if the job is cancelled
+    pub fn track_partial_file(job_id: &str, path: impl Into<PathBuf>) {
+        if let Ok(mut files) = PARTIAL_FILES.lock() {
+            let paths = files.entry(job_id.to_string()).or_default();
+            let path = path.into();
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+
+    /// Record the destination from a yt-dlp output line, if it announces one
+    /// e.g. "[download] Destination: /path/Title.webm"
+    pub fn track_ytdlp_destination(job_id: &str, line: &str) {
+        if let Some(path) = line.split("Destination:").nth(1) {
+            let path = path.trim();
+            if !path.is_empty() {
+                Self::track_partial_file(job_id, path);
+            }
+        }
+    }
+
+    /// Delete every partial file written by a job (including yt-dlp .part/.ytdl leftovers)
+    pub fn cleanup_partial_files(job_id: &str) {
+        let paths = PARTIAL_FILES
+            .lock()
+            .ok()
+            .and_then(|mut files| files.remove(job_id))
+            .unwrap_or_default();
+
+        for path in paths {
+            let path_str = path.to_string_lossy().to_string();
+            for candidate in [path_str.clone(), format!("{}.part", path_str), format!("{}.ytdl", path_str)] {
+                if std::fs::remove_file(&candidate).is_ok() {
+                    println!("[Control] Removed partial file: {}", candidate);
+                }
+            }
+        }
+    }
+
+    /// Forget tracked partial files without deleting them (job finished normally)
+    pub fn forget_partial_files(job_id: &str) {
+        if let Ok(mut files) = PARTIAL_FILES.lock() {
+            files.remove(job_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_and_check() {
+        let job_id = format!("control_test_{}", uuid::Uuid::new_v4());
+
+        // Unregistered jobs can't be signalled and always run
+        assert!(!JobControl::signal(&job_id, JobSignal::Cancel));
+        assert!(JobControl::check(&job_id).is_ok());
+
+        JobControl::register(&job_id);
+        assert!(JobControl::check(&job_id).is_ok());
+        assert!(JobControl::signal(&job_id, JobSignal::Pause));
+        assert_eq!(JobControl::check(&job_id).unwrap_err().kind(), ErrorKind::Paused);
+        JobControl::signal(&job_id, JobSignal::Cancel);
+        assert_eq!(JobControl::check(&job_id).unwrap_err().kind(), ErrorKind::Cancelled);
+
+        JobControl::unregister(&job_id);
+        assert_eq!(JobControl::requested(&job_id), JobSignal::Run);
+    }
+
+    #[tokio::test]
+    async fn test_stopped_resolves_on_signal() {
+        let job_id = format!("control_test_{}", uuid::Uuid::new_v4());
+        JobControl::register(&job_id);
+        let mut control = JobControl::subscribe(&job_id);
+
+        let waiting = tokio::time::timeout(std::time::Duration::from_millis(20), control.stopped()).await;
+        assert!(waiting.is_err(), "a running job isn't stopped");
+
+        JobControl::signal(&job_id, JobSignal::Cancel);
+        assert_eq!(control.stopped().await, JobSignal::Cancel);
+        JobControl::unregister(&job_id);
+    }
+
+    #[test]
+    fn test_cleanup_partial_files() {
+        let job_id = format!("control_test_{}", uuid::Uuid::new_v4());
+        let path = std::env::temp_dir().join(format!("hasod_partial_{}.webm", uuid::Uuid::new_v4()));
+        let part = format!("{}.part", path.to_string_lossy());
+        std::fs::write(&part, b"partial").unwrap();
+
+        JobControl::track_ytdlp_destination(&job_id, &format!("[download] Destination: {}", path.to_string_lossy()));
+        JobControl::cleanup_partial_files(&job_id);
+        assert!(!std::path::Path::new(&part).exists());
+    }
+}
@@ -0,0 +1,300 @@
+// Duplicate detection - keeps the same track from being queued or downloaded twice
+// Duplicates of jobs still waiting or downloading are matched by ISRC, Spotify track ID or
+// normalized URL and always skipped.
+// Files already on disk are handled according to the duplicate policy setting.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::download::links::{parse_link, LinkKind};
+use crate::download::{DownloadHistory, DownloadJob, DownloadStatus, QueueManager};
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+use crate::utils::filesystem::{find_audio_file, split_audio_extension};
+use crate::utils::DuplicatePolicy;
+
+// Query parameters that only track where a link was shared from
+const TRACKING_PARAMS: &[&str] = &["si", "feature", "fbclid", "igshid", "ref", "nd", "context"];
+
+/// Canonical form of a URL for comparison
+/// Links of supported services use the link parser's canonical URL; for anything else
+/// tracking parameters, fragments, "www."/"m." prefixes and trailing slashes are dropped
+pub fn normalize_url(url: &str) -> String {
+    let trimmed = url.trim();
+
+    if let Some(link) = parse_link(trimmed).filter(|link| link.kind != LinkKind::ShortLink) {
+        return link.url;
+    }
+
+    let Ok(parsed) = url::Url::parse(trimmed) else {
+        return trimmed.to_lowercase();
+    };
+
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(&host)
+        .to_string();
+
+    let query: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !TRACKING_PARAMS.contains(&key.as_ref()) && !key.starts_with("utm_"))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    let path = parsed.path().trim_end_matches('/');
+    let mut normalized = format!("https://{}{}", host, path);
+    if !query.is_empty() {
+        let query: Vec<String> = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        normalized.push('?');
+        normalized.push_str(&query.join("&"));
+    }
+    normalized
+}
+
+/// Extract the Spotify track ID from a track URL or URI
+pub fn spotify_track_id(url: &str) -> Option<String> {
+    crate::download::services::SpotifyDownloader::extract_track_id(url)
+}
+
+/// Every identity a job can be matched by
+pub fn dedup_keys(job: &DownloadJob) -> Vec<String> {
+    let mut keys = vec![format!("url:{}", normalize_url(&job.url))];
+    let isrc = job.isrc.as_deref().or(job.metadata.isrc.as_deref());
+    if let Some(isrc) = isrc.filter(|isrc| !isrc.is_empty()) {
+        keys.push(format!("isrc:{}", isrc.to_uppercase()));
+    }
+    if let Some(track_id) = spotify_track_id(&job.url) {
+        keys.push(format!("spotify:{}", track_id));
+    }
+    keys
+}
+
+/// Whether a job in this state is still going to download the track
+/// Finished jobs don't count - a track that's already on disk is handled by the duplicate policy
+fn is_live(status: &DownloadStatus) -> bool {
+    matches!(
+        status,
+        DownloadStatus::Queued
+            | DownloadStatus::Downloading
+            | DownloadStatus::Converting
+            | DownloadStatus::Paused
+            | DownloadStatus::NeedsReview
+    )
+}
+
+/// Mark jobs that duplicate an existing queue entry (or an earlier job in the same batch) as Skipped
+/// Jobs with `bypass_dedup` (re-downloads) are never skipped
+/// Returns the number of skipped jobs
+pub fn skip_queue_duplicates(existing: &[DownloadJob], jobs: &mut [DownloadJob]) -> usize {
+    let mut seen: HashSet<String> = existing
+        .iter()
+        .filter(|j| is_live(&j.status))
+        .flat_map(dedup_keys)
+        .collect();
+
+    let mut skipped = 0;
+    for job in jobs.iter_mut() {
+        let keys = dedup_keys(job);
+        if !job.bypass_dedup && keys.iter().any(|key| seen.contains(key)) {
+            job.status = DownloadStatus::Skipped;
+            job.message = "Already in queue".to_string();
+            skipped += 1;
+        } else {
+            seen.extend(keys);
+        }
+    }
+    skipped
+}
+
+/// Find a previous download of this job's track that still exists on disk
+pub fn find_downloaded(job: &DownloadJob) -> Option<PathBuf> {
+    let normalized = normalize_url(&job.url);
+    let entries = DownloadHistory::find_matches(job.isrc.as_deref(), &[job.url.as_str(), normalized.as_str()])
+        .unwrap_or_default();
+
+    entries
+        .into_iter()
+        .map(|entry| PathBuf::from(entry.output_path))
+        .find(|path| path.exists())
+}
+
+/// Decide where a job should be written, applying the duplicate policy if the file exists
+/// Returns a Duplicate error (after recording the existing path on the job) when it should be skipped
+/// A copy in another audio format (e.g. .mp3 when the format is now Opus) counts as existing
+/// Re-downloads (`bypass_dedup`) always overwrite the existing file
+pub fn resolve_output_path(job_id: &str, path: PathBuf) -> AppResult<PathBuf> {
+    let bypass_dedup = QueueManager::get_job(job_id).is_ok_and(|job| job.bypass_dedup);
+    let policy = if bypass_dedup {
+        DuplicatePolicy::Overwrite
+    } else {
+        crate::utils::get_duplicate_policy()
+    };
+    apply_duplicate_policy(job_id, path, policy)
+}
+
+fn apply_duplicate_policy(job_id: &str, path: PathBuf, policy: DuplicatePolicy) -> AppResult<PathBuf> {
+    let Some(existing) = find_audio_file(&path) else {
+        return Ok(path);
+    };
+
+    match policy {
+        DuplicatePolicy::Skip => Err(skip_as_duplicate(job_id, &existing)),
+        DuplicatePolicy::Overwrite => {
+            println!("[Dedup] Overwriting existing file: {:?}", existing);
+            std::fs::remove_file(&existing).or_error(ErrorKind::Io, "Failed to remove existing file")?;
+            Ok(path)
+        }
+        DuplicatePolicy::KeepBoth => {
+            let free = next_free_path(&path);
+            println!("[Dedup] File exists, keeping both: {:?}", free);
+            Ok(free)
+        }
+    }
+}
+
+/// Record the existing file on the job and build the error that stops the download
+pub fn skip_as_duplicate(job_id: &str, existing: &Path) -> AppError {
+    let existing = existing.to_string_lossy().to_string();
+    println!("[Dedup] Skipping job {}, already downloaded: {}", job_id, existing);
+    let _ = QueueManager::update_job_metadata(job_id, |job| {
+        job.output_path = Some(existing.clone());
+    });
+    AppError::new(ErrorKind::Duplicate, format!("Already downloaded: {}", existing))
+}
+
+/// "Artist - Title.mp3" -> "Artist - Title (1).mp3", "(2)", ... until the name is free
+pub fn next_free_path(path: &Path) -> PathBuf {
+    let (stem, extension) = split_audio_extension(path);
+    let stem = stem.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+
+    (1..)
+        .map(|n| {
+            let name = match &extension {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| find_audio_file(candidate).is_none())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("https://open.spotify.com/intl-he/track/4uLU6hMCjMI75M1A2tKUQC?si=abc123"),
+            "https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC"
+        );
+        assert_eq!(
+            normalize_url("spotify:track:4uLU6hMCjMI75M1A2tKUQC"),
+            "https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC"
+        );
+        assert_eq!(normalize_url("https://youtu.be/dQw4w9WgXcQ?si=x"), "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        assert_eq!(
+            normalize_url("https://www.youtube.com/watch?v=dQw4w9WgXcQ&feature=share&utm_source=x"),
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+        );
+        assert_eq!(
+            normalize_url("https://example.com/page/?utm_source=x&id=3#top"),
+            "https://example.com/page?id=3"
+        );
+        assert_eq!(
+            normalize_url("https://m.soundcloud.com/artist/track/#comments"),
+            "https://soundcloud.com/artist/track"
+        );
+    }
+
+    #[test]
+    fn test_skip_queue_duplicates() {
+        let existing = vec![DownloadJob::new("https://open.spotify.com/track/AAA".to_string())];
+
+        let mut by_isrc_a = DownloadJob::new("https://open.spotify.com/track/BBB".to_string());
+        by_isrc_a.isrc = Some("ILA000000001".to_string());
+        let mut by_isrc_b = DownloadJob::new("https://music.apple.com/il/album/x/1?i=2".to_string());
+        by_isrc_b.isrc = Some("ila000000001".to_string());
+
+        let mut jobs = vec![
+            DownloadJob::new("spotify:track:AAA".to_string()),
+            by_isrc_a,
+            by_isrc_b,
+            DownloadJob::new("https://youtu.be/dQw4w9WgXcQ".to_string()),
+            DownloadJob::new("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string()),
+        ];
+
+        assert_eq!(skip_queue_duplicates(&existing, &mut jobs), 3);
+        let statuses: Vec<_> = jobs.iter().map(|j| j.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                DownloadStatus::Skipped,
+                DownloadStatus::Queued,
+                DownloadStatus::Skipped,
+                DownloadStatus::Queued,
+                DownloadStatus::Skipped,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_finished_jobs_are_not_queue_duplicates() {
+        let mut finished = DownloadJob::new("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string());
+        finished.status = DownloadStatus::Complete;
+        let mut paused = DownloadJob::new("https://open.spotify.com/track/AAA".to_string());
+        paused.status = DownloadStatus::Paused;
+
+        let mut jobs = vec![
+            DownloadJob::new("https://youtu.be/dQw4w9WgXcQ".to_string()),
+            DownloadJob::new("spotify:track:AAA".to_string()),
+        ];
+        // The finished track is left to the duplicate policy; the paused one is still coming
+        assert_eq!(skip_queue_duplicates(&[finished, paused], &mut jobs), 1);
+        assert_eq!(jobs[0].status, DownloadStatus::Queued);
+        assert_eq!(jobs[1].status, DownloadStatus::Skipped);
+    }
+
+    #[test]
+    fn test_redownload_bypasses_dedup() {
+        let queued = DownloadJob::new("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string());
+
+        let mut redownload = DownloadJob::new("https://youtu.be/dQw4w9WgXcQ".to_string());
+        redownload.bypass_dedup = true;
+        let mut jobs = vec![redownload, DownloadJob::new("https://youtu.be/dQw4w9WgXcQ".to_string())];
+
+        // The re-download is queued; a plain duplicate of it still isn't
+        assert_eq!(skip_queue_duplicates(&[queued], &mut jobs), 1);
+        assert_eq!(jobs[0].status, DownloadStatus::Queued);
+        assert_eq!(jobs[1].status, DownloadStatus::Skipped);
+
+        // The existing file is replaced instead of skipped, whatever the duplicate policy says
+        let dir = std::env::temp_dir().join(format!("hasod_dedup_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let existing = dir.join("Artist - Title.mp3");
+        std::fs::write(&existing, b"x").unwrap();
+
+        let redownload = jobs.remove(0);
+        let job_id = redownload.id.clone();
+        crate::download::queue::with_queue(vec![redownload], || {
+            let path = resolve_output_path(&job_id, existing.clone()).unwrap();
+            assert_eq!(path, existing);
+            assert!(!existing.exists());
+        });
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_next_free_path() {
+        let dir = std::env::temp_dir().join(format!("hasod_dedup_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let original = dir.join("Artist - Title.mp3");
+        std::fs::write(&original, b"x").unwrap();
+        std::fs::write(dir.join("Artist - Title (1).mp3"), b"x").unwrap();
+
+        assert_eq!(next_free_path(&original), dir.join("Artist - Title (2).mp3"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
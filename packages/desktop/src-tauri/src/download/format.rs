@@ -0,0 +1,122 @@
+// Output format and quality profiles
+// One setting drives the yt-dlp arguments, the Deezer quality request, the ffmpeg
+// transcode of Deezer files and the file extension used for the output path.
+
+use serde::{Deserialize, Serialize};
+
+use crate::api_types::DeezerQuality;
+
+/// Audio format and quality to produce
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioFormat {
+    #[default]
+    Mp3V0,     // MP3 VBR V0 (~245 kbps)
+    Mp3320,    // MP3 CBR 320 kbps
+    Aac,       // AAC in an .m4a container
+    Opus,      // Opus in an .opus container
+    Flac,      // FLAC when the source is lossless, otherwise the original stream
+    Alac,      // ALAC (.m4a) when the source is lossless, otherwise the original stream
+    Original,  // Keep the source audio without transcoding
+}
+
+impl AudioFormat {
+    /// Extension of the files this format produces
+    /// None when it depends on the source (Original, or a lossless format from a lossy source)
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            AudioFormat::Mp3V0 | AudioFormat::Mp3320 => Some("mp3"),
+            AudioFormat::Aac | AudioFormat::Alac => Some("m4a"),
+            AudioFormat::Opus => Some("opus"),
+            AudioFormat::Flac => Some("flac"),
+            AudioFormat::Original => None,
+        }
+    }
+
+    /// Human-readable name for status messages
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3V0 => "MP3 V0",
+            AudioFormat::Mp3320 => "MP3 320",
+            AudioFormat::Aac => "AAC",
+            AudioFormat::Opus => "Opus",
+            AudioFormat::Flac => "FLAC",
+            AudioFormat::Alac => "ALAC",
+            AudioFormat::Original => "original format",
+        }
+    }
+
+    pub fn is_lossless(&self) -> bool {
+        matches!(self, AudioFormat::Flac | AudioFormat::Alac)
+    }
+
+    /// yt-dlp audio extraction arguments
+    /// yt-dlp sources are always lossy, so lossless formats keep the original stream instead of
+    /// wrapping lossy audio in a lossless container
+    pub fn ytdlp_args(&self) -> Vec<&'static str> {
+        match self {
+            AudioFormat::Mp3V0 => vec!["--extract-audio", "--audio-format", "mp3", "--audio-quality", "0"],
+            AudioFormat::Mp3320 => vec!["--extract-audio", "--audio-format", "mp3", "--audio-quality", "320K"],
+            AudioFormat::Aac => vec!["--extract-audio", "--audio-format", "m4a", "--audio-quality", "0"],
+            AudioFormat::Opus => vec!["--extract-audio", "--audio-format", "opus", "--audio-quality", "0"],
+            AudioFormat::Flac | AudioFormat::Alac | AudioFormat::Original => vec!["--extract-audio"],
+        }
+    }
+
+    /// Quality to request from Deezer
+    pub fn deezer_quality(&self) -> DeezerQuality {
+        if self.is_lossless() {
+            DeezerQuality::Flac
+        } else {
+            DeezerQuality::Mp3320
+        }
+    }
+
+    /// Whether a file with `source_extension` has to be transcoded to match this format
+    /// Lossy audio is never transcoded to a lossless format
+    pub fn needs_transcode(&self, source_extension: &str) -> bool {
+        let Some(target) = self.extension() else {
+            return false;
+        };
+        let source_lossless = matches!(source_extension, "flac" | "wav");
+        if self.is_lossless() && !source_lossless {
+            return false;
+        }
+        // MP3 V0 and 320 share an extension - re-encoding one into the other only loses quality
+        target != source_extension
+    }
+
+    /// ffmpeg codec arguments that produce this format
+    pub fn ffmpeg_args(&self) -> Vec<&'static str> {
+        match self {
+            AudioFormat::Mp3V0 => vec!["-c:a", "libmp3lame", "-q:a", "0"],
+            AudioFormat::Mp3320 => vec!["-c:a", "libmp3lame", "-b:a", "320k"],
+            AudioFormat::Aac => vec!["-c:a", "aac", "-b:a", "256k"],
+            AudioFormat::Opus => vec!["-c:a", "libopus", "-b:a", "160k"],
+            AudioFormat::Flac => vec!["-c:a", "flac"],
+            AudioFormat::Alac => vec!["-c:a", "alac"],
+            AudioFormat::Original => vec!["-c:a", "copy"],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_transcode() {
+        // Deezer MP3 320 delivered as-is for MP3 profiles
+        assert!(!AudioFormat::Mp3V0.needs_transcode("mp3"));
+        assert!(!AudioFormat::Mp3320.needs_transcode("mp3"));
+        // Lossy to another lossy codec
+        assert!(AudioFormat::Aac.needs_transcode("mp3"));
+        assert!(AudioFormat::Opus.needs_transcode("mp3"));
+        // Lossless only from a lossless source
+        assert!(!AudioFormat::Flac.needs_transcode("flac"));
+        assert!(AudioFormat::Alac.needs_transcode("flac"));
+        assert!(!AudioFormat::Flac.needs_transcode("mp3"));
+        assert!(!AudioFormat::Alac.needs_transcode("mp3"));
+        assert!(!AudioFormat::Original.needs_transcode("flac"));
+    }
+}
@@ -0,0 +1,439 @@
+// Download history - local SQLite record of every completed download
+// Jobs disappear from the queue once cleared; the history keeps what was downloaded,
+// where it came from and where it was saved (~/.hasod_downloads/history.db)
+
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::download::{DownloadContext, DownloadJob, DownloadSource, MusicService, TrackMetadata};
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::utils::get_config_dir;
+
+// Lazily opened history database
+static HISTORY_DB: std::sync::LazyLock<Mutex<Option<Connection>>> =
+    std::sync::LazyLock::new(|| Mutex::new(None));
+
+// Bump when the schema changes and add a migration step in init_schema
+const SCHEMA_VERSION: i32 = 1;
+
+// Default page size for history queries
+const DEFAULT_LIMIT: u32 = 200;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A completed download
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,  // ID of the job that produced the file
+    pub url: String,
+    pub service: MusicService,
+    pub isrc: Option<String>,
+    pub output_path: String,
+    pub metadata: TrackMetadata,
+    pub source: Option<DownloadSource>,
+    pub file_size: Option<u64>,  // bytes
+    pub download_context: Option<DownloadContext>,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub completed_at: i64,
+}
+
+impl HistoryEntry {
+    /// Build an entry from a finished job (None if the job has no output file)
+    pub fn from_job(job: &DownloadJob) -> Option<Self> {
+        let output_path = job.output_path.clone()?;
+        let file_size = std::fs::metadata(&output_path).ok().map(|m| m.len());
+
+        Some(HistoryEntry {
+            id: job.id.clone(),
+            url: job.url.clone(),
+            service: job.service.clone(),
+            isrc: job.isrc.clone(),
+            output_path,
+            metadata: job.metadata.clone(),
+            source: job.source.clone(),
+            file_size,
+            download_context: job.download_context.clone(),
+            created_at: job.created_at,
+            started_at: job.started_at,
+            completed_at: job.completed_at.unwrap_or_else(|| chrono::Utc::now().timestamp()),
+        })
+    }
+}
+
+/// Filter for history queries - all fields are optional and combined with AND
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryFilter {
+    pub search: Option<String>,  // Matches title, artist or album
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub from: Option<i64>,       // Completed at or after (unix seconds)
+    pub to: Option<i64>,         // Completed at or before (unix seconds)
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+// ============================================================================
+// Download History
+// ============================================================================
+
+pub struct DownloadHistory;
+
+impl DownloadHistory {
+    /// Record a completed job (replaces an earlier entry for the same job)
+    pub fn record(job: &DownloadJob) -> AppResult<()> {
+        let entry = HistoryEntry::from_job(job)
+            .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, "Job has no output file"))?;
+        Self::with_connection(|conn| insert_entry(conn, &entry))?;
+        println!("[History] Recorded: {} - {}", entry.metadata.artist, entry.metadata.title);
+        Ok(())
+    }
+
+    /// Query the history, newest first
+    pub fn query(filter: &HistoryFilter) -> AppResult<Vec<HistoryEntry>> {
+        Self::with_connection(|conn| query_entries(conn, filter))
+    }
+
+    /// Get a single history entry
+    pub fn get(id: &str) -> AppResult<HistoryEntry> {
+        Self::with_connection(|conn| get_entry(conn, id))?
+            .ok_or_else(|| AppError::new(ErrorKind::NotFound, "History entry not found"))
+    }
+
+    /// Find earlier downloads of the same track (by ISRC or any of the given URLs), newest first
+    pub fn find_matches(isrc: Option<&str>, urls: &[&str]) -> AppResult<Vec<HistoryEntry>> {
+        Self::with_connection(|conn| find_matching_entries(conn, isrc, urls))
+    }
+
+    /// Remove a single entry (the downloaded file is left alone)
+    pub fn remove(id: &str) -> AppResult<bool> {
+        let removed = Self::with_connection(|conn| Ok(conn.execute("DELETE FROM downloads WHERE id = ?1", [id])?))?;
+        Ok(removed > 0)
+    }
+
+    /// Remove every entry (downloaded files are left alone)
+    pub fn clear() -> AppResult<usize> {
+        Self::with_connection(|conn| Ok(conn.execute("DELETE FROM downloads", [])?))
+    }
+
+    /// Create a new queue job that downloads a history entry again
+    pub fn job_for_redownload(id: &str) -> AppResult<DownloadJob> {
+        let entry = Self::get(id)?;
+
+        let mut job = DownloadJob::new(entry.url);
+        job.metadata = entry.metadata;
+        job.isrc = entry.isrc;
+        job.download_context = entry.download_context.or(Some(DownloadContext::Single));
+        job.bypass_dedup = true;
+        Ok(job)
+    }
+
+    /// Run a closure against the history database, opening it on first use
+    fn with_connection<T>(f: impl FnOnce(&Connection) -> AppResult<T>) -> AppResult<T> {
+        let mut db = HISTORY_DB.lock()?;
+        if db.is_none() {
+            let conn = Connection::open(get_history_path())?;
+            init_schema(&conn)?;
+            *db = Some(conn);
+        }
+        match db.as_ref() {
+            Some(conn) => f(conn),
+            None => Err(AppError::internal("History database not open")),
+        }
+    }
+}
+
+/// Get the path to the history database (~/.hasod_downloads/history.db)
+fn get_history_path() -> PathBuf {
+    let config_dir = get_config_dir();
+    std::fs::create_dir_all(&config_dir).ok();
+    config_dir.join("history.db")
+}
+
+// ============================================================================
+// SQL
+// ============================================================================
+
+fn init_schema(conn: &Connection) -> AppResult<()> {
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version >= SCHEMA_VERSION {
+        return Ok(());
+    }
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS downloads (
+            id TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            service TEXT NOT NULL,
+            isrc TEXT,
+            output_path TEXT NOT NULL,
+            title TEXT NOT NULL,
+            artist TEXT NOT NULL,
+            album TEXT NOT NULL,
+            metadata TEXT NOT NULL,
+            source_service TEXT,
+            source_url TEXT,
+            file_size INTEGER,
+            download_context TEXT,
+            created_at INTEGER NOT NULL,
+            started_at INTEGER,
+            completed_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_downloads_artist ON downloads(artist COLLATE NOCASE);
+        CREATE INDEX IF NOT EXISTS idx_downloads_album ON downloads(album COLLATE NOCASE);
+        CREATE INDEX IF NOT EXISTS idx_downloads_completed_at ON downloads(completed_at);
+        CREATE INDEX IF NOT EXISTS idx_downloads_isrc ON downloads(isrc);",
+    )?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    Ok(())
+}
+
+fn insert_entry(conn: &Connection, entry: &HistoryEntry) -> AppResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO downloads (
+            id, url, service, isrc, output_path, title, artist, album, metadata,
+            source_service, source_url, file_size, download_context,
+            created_at, started_at, completed_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            entry.id,
+            entry.url,
+            enum_to_sql(&entry.service)?,
+            entry.isrc,
+            entry.output_path,
+            entry.metadata.title,
+            entry.metadata.artist,
+            entry.metadata.album,
+            serde_json::to_string(&entry.metadata)?,
+            entry.source.as_ref().map(|s| enum_to_sql(&s.service)).transpose()?,
+            entry.source.as_ref().and_then(|s| s.url.clone()),
+            entry.file_size.map(|size| size as i64),
+            entry.download_context.as_ref().map(serde_json::to_string).transpose()?,
+            entry.created_at,
+            entry.started_at,
+            entry.completed_at,
+        ],
+    )?;
+    Ok(())
+}
+
+fn get_entry(conn: &Connection, id: &str) -> AppResult<Option<HistoryEntry>> {
+    let entry = conn
+        .query_row(
+            &format!("SELECT {} FROM downloads WHERE id = ?1", ENTRY_COLUMNS),
+            [id],
+            row_to_entry,
+        )
+        .optional()?;
+    Ok(entry)
+}
+
+fn query_entries(conn: &Connection, filter: &HistoryFilter) -> AppResult<Vec<HistoryEntry>> {
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+    if let Some(search) = filter.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        conditions.push("(title LIKE ? ESCAPE '\\' OR artist LIKE ? ESCAPE '\\' OR album LIKE ? ESCAPE '\\')");
+        let pattern = format!("%{}%", escape_like(search));
+        for _ in 0..3 {
+            values.push(pattern.clone().into());
+        }
+    }
+    if let Some(artist) = filter.artist.as_deref().filter(|s| !s.is_empty()) {
+        conditions.push("artist = ? COLLATE NOCASE");
+        values.push(artist.to_string().into());
+    }
+    if let Some(album) = filter.album.as_deref().filter(|s| !s.is_empty()) {
+        conditions.push("album = ? COLLATE NOCASE");
+        values.push(album.to_string().into());
+    }
+    if let Some(from) = filter.from {
+        conditions.push("completed_at >= ?");
+        values.push(from.into());
+    }
+    if let Some(to) = filter.to {
+        conditions.push("completed_at <= ?");
+        values.push(to.into());
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    values.push(i64::from(filter.limit.unwrap_or(DEFAULT_LIMIT)).into());
+    values.push(i64::from(filter.offset.unwrap_or(0)).into());
+
+    let sql = format!(
+        "SELECT {} FROM downloads {} ORDER BY completed_at DESC LIMIT ? OFFSET ?",
+        ENTRY_COLUMNS, where_clause
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let entries = stmt
+        .query_map(params_from_iter(values), row_to_entry)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+fn find_matching_entries(conn: &Connection, isrc: Option<&str>, urls: &[&str]) -> AppResult<Vec<HistoryEntry>> {
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+    if let Some(isrc) = isrc.filter(|isrc| !isrc.is_empty()) {
+        conditions.push("isrc = ? COLLATE NOCASE");
+        values.push(isrc.to_string().into());
+    }
+    for url in urls {
+        conditions.push("url = ?");
+        values.push(url.to_string().into());
+    }
+    if conditions.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sql = format!(
+        "SELECT {} FROM downloads WHERE {} ORDER BY completed_at DESC",
+        ENTRY_COLUMNS,
+        conditions.join(" OR ")
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let entries = stmt
+        .query_map(params_from_iter(values), row_to_entry)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+const ENTRY_COLUMNS: &str = "id, url, service, isrc, output_path, metadata, source_service, source_url, \
+    file_size, download_context, created_at, started_at, completed_at";
+
+fn row_to_entry(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    let service: String = row.get("service")?;
+    let metadata: String = row.get("metadata")?;
+    let source_service: Option<String> = row.get("source_service")?;
+    let download_context: Option<String> = row.get("download_context")?;
+    let file_size: Option<i64> = row.get("file_size")?;
+
+    Ok(HistoryEntry {
+        id: row.get("id")?,
+        url: row.get("url")?,
+        service: enum_from_sql(&service).unwrap_or(MusicService::Unknown),
+        isrc: row.get("isrc")?,
+        output_path: row.get("output_path")?,
+        metadata: serde_json::from_str(&metadata).unwrap_or_default(),
+        source: source_service.map(|service| DownloadSource {
+            service: enum_from_sql(&service).unwrap_or(MusicService::Unknown),
+            url: row.get("source_url").ok().flatten(),
+        }),
+        file_size: file_size.map(|size| size as u64),
+        download_context: download_context.and_then(|c| serde_json::from_str(&c).ok()),
+        created_at: row.get("created_at")?,
+        started_at: row.get("started_at")?,
+        completed_at: row.get("completed_at")?,
+    })
+}
+
+/// Store a unit enum variant by its serde name (e.g. "AppleMusic")
+fn enum_to_sql<T: Serialize>(value: &T) -> AppResult<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(name) => Ok(name),
+        other => Ok(other.to_string()),
+    }
+}
+
+fn enum_from_sql<T: serde::de::DeserializeOwned>(name: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+/// Escape LIKE wildcards in user input
+fn escape_like(input: &str) -> String {
+    input.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, artist: &str, album: &str, title: &str, completed_at: i64) -> HistoryEntry {
+        HistoryEntry {
+            id: id.to_string(),
+            url: format!("https://open.spotify.com/track/{}", id),
+            service: MusicService::Spotify,
+            isrc: Some(format!("ISRC{}", id)),
+            output_path: format!("/music/{}/{}/{}.mp3", artist, album, title),
+            metadata: TrackMetadata {
+                title: title.to_string(),
+                artist: artist.to_string(),
+                album: album.to_string(),
+                duration: Some(200),
+                thumbnail: None,
+                ..TrackMetadata::default()
+            },
+            source: Some(DownloadSource {
+                service: MusicService::YouTube,
+                url: Some("https://www.youtube.com/watch?v=abc".to_string()),
+            }),
+            file_size: Some(4_000_000),
+            download_context: Some(DownloadContext::Album(album.to_string())),
+            created_at: completed_at - 60,
+            started_at: Some(completed_at - 30),
+            completed_at,
+        }
+    }
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        insert_entry(&conn, &entry("1", "Idan Raichel", "Within My Walls", "Mimaamakim", 1_000)).unwrap();
+        insert_entry(&conn, &entry("2", "Idan Raichel", "Quarter to Six", "Ale", 2_000)).unwrap();
+        insert_entry(&conn, &entry("3", "Shlomo Artzi", "Hatuna Levana", "Yareach", 3_000)).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let conn = test_db();
+        let loaded = get_entry(&conn, "3").unwrap().unwrap();
+
+        assert_eq!(loaded.service, MusicService::Spotify);
+        assert_eq!(loaded.metadata.title, "Yareach");
+        assert_eq!(loaded.source.unwrap().service, MusicService::YouTube);
+        assert_eq!(loaded.download_context, Some(DownloadContext::Album("Hatuna Levana".to_string())));
+        assert_eq!(loaded.file_size, Some(4_000_000));
+        assert!(get_entry(&conn, "missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let conn = test_db();
+        let ids = |filter: HistoryFilter| -> Vec<String> {
+            query_entries(&conn, &filter).unwrap().into_iter().map(|e| e.id).collect()
+        };
+
+        assert_eq!(ids(HistoryFilter::default()), vec!["3", "2", "1"]);
+        assert_eq!(ids(HistoryFilter { artist: Some("idan raichel".into()), ..Default::default() }), vec!["2", "1"]);
+        assert_eq!(ids(HistoryFilter { album: Some("Quarter to Six".into()), ..Default::default() }), vec!["2"]);
+        assert_eq!(ids(HistoryFilter { search: Some("yare".into()), ..Default::default() }), vec!["3"]);
+        assert_eq!(ids(HistoryFilter { from: Some(1_500), to: Some(2_500), ..Default::default() }), vec!["2"]);
+        assert_eq!(ids(HistoryFilter { limit: Some(1), offset: Some(1), ..Default::default() }), vec!["2"]);
+        assert!(ids(HistoryFilter { search: Some("%".into()), ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn test_find_matching_entries() {
+        let conn = test_db();
+        let ids = |isrc: Option<&str>, urls: &[&str]| -> Vec<String> {
+            find_matching_entries(&conn, isrc, urls).unwrap().into_iter().map(|e| e.id).collect()
+        };
+
+        assert_eq!(ids(Some("isrc2"), &[]), vec!["2"]);
+        assert_eq!(ids(None, &["https://open.spotify.com/track/3"]), vec!["3"]);
+        assert_eq!(ids(Some("ISRC1"), &["https://open.spotify.com/track/3"]), vec!["3", "1"]);
+        assert!(ids(None, &[]).is_empty());
+    }
+}
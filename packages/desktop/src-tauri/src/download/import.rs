@@ -0,0 +1,442 @@
+// Playlist import - M3U/M3U8, CSV and plain-text track lists exported from other players
+// Rows with a music link are queued like any pasted link. Rows with only artist/title
+// become search jobs that YouTubeDownloader resolves with find_best_source when they run.
+
+use std::path::Path;
+
+use crate::download::links::find_links;
+use crate::download::TrackMetadata;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Format of a playlist file, detected from its extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    M3u,   // .m3u / .m3u8, with or without #EXTINF lines
+    Csv,   // "Artist,Title,Album", optionally with a header row
+    Text,  // One "Artist - Title" (or link) per line
+}
+
+impl ImportFormat {
+    pub fn from_file_name(file_name: &str) -> Self {
+        let extension = Path::new(file_name)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("m3u") | Some("m3u8") => ImportFormat::M3u,
+            Some("csv") | Some("tsv") => ImportFormat::Csv,
+            _ => ImportFormat::Text,
+        }
+    }
+}
+
+/// A track known only by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportTrack {
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub duration_secs: Option<u32>,  // From #EXTINF, helps find_best_source reject wrong versions
+}
+
+impl ImportTrack {
+    pub fn to_metadata(&self) -> TrackMetadata {
+        TrackMetadata {
+            title: self.title.clone(),
+            artist: self.artist.clone(),
+            album: self.album.clone().unwrap_or_default(),
+            duration: self.duration_secs,
+            ..TrackMetadata::default()
+        }
+    }
+}
+
+/// One row of a playlist file
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportRow {
+    Link(String),  // Canonical URL of a supported link
+    Track(ImportTrack),
+}
+
+/// A parsed playlist file
+#[derive(Debug, Clone)]
+pub struct ImportedPlaylist {
+    pub name: String,  // File name without extension
+    pub rows: Vec<ImportRow>,
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse a playlist file's contents; the format comes from the file name
+pub fn parse_playlist(file_name: &str, contents: &str) -> ImportedPlaylist {
+    let contents = contents.trim_start_matches('\u{feff}');
+    let rows = match ImportFormat::from_file_name(file_name) {
+        ImportFormat::M3u => parse_m3u(contents),
+        ImportFormat::Csv => parse_csv(contents),
+        ImportFormat::Text => parse_text(contents),
+    };
+
+    let name = Path::new(file_name.trim())
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().trim().to_string())
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| "Imported Playlist".to_string());
+
+    ImportedPlaylist { name, rows }
+}
+
+/// Links in a line, as rows
+fn link_rows(line: &str) -> Vec<ImportRow> {
+    find_links(line).into_iter().map(|link| ImportRow::Link(link.url)).collect()
+}
+
+/// M3U: every non-comment line is a location - a link, or a local file whose track is
+/// named by the preceding #EXTINF line (or by the file name when there is none)
+fn parse_m3u(contents: &str) -> Vec<ImportRow> {
+    let mut rows = Vec::new();
+    let mut extinf: Option<ImportTrack> = None;
+
+    for line in contents.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            extinf = Some(parse_extinf(info));
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let links = link_rows(line);
+        let info = extinf.take();
+        if !links.is_empty() {
+            rows.extend(links);
+            continue;
+        }
+
+        let track = info.filter(|track| !track.title.is_empty()).unwrap_or_else(|| {
+            // "C:\Music\Artist - Title.mp3" or "../Music/Artist - Title.flac"
+            let file_name = line.rsplit(['/', '\\']).next().unwrap_or(line);
+            let stem = Path::new(file_name).file_stem().map(|s| s.to_string_lossy().to_string());
+            split_artist_title(stem.as_deref().unwrap_or(file_name))
+        });
+        if !track.title.is_empty() {
+            rows.push(ImportRow::Track(track));
+        }
+    }
+
+    rows
+}
+
+/// "#EXTINF:215 tvg-id="...",Artist - Title" (after the prefix)
+fn parse_extinf(info: &str) -> ImportTrack {
+    let (attributes, name) = info.split_once(',').unwrap_or((info, ""));
+    let duration_secs = attributes
+        .split_whitespace()
+        .next()
+        .and_then(|secs| secs.parse::<i64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(|secs| secs as u32);
+
+    ImportTrack {
+        duration_secs,
+        ..split_artist_title(name)
+    }
+}
+
+/// CSV: columns come from the header row when there is one ("Artist Name(s)", "Track Name",
+/// "Album Name"...), otherwise they are Artist, Title, Album. A row with a link in any
+/// column is queued by its link.
+fn parse_csv(contents: &str) -> Vec<ImportRow> {
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty()).peekable();
+    let Some(first) = lines.peek() else {
+        return Vec::new();
+    };
+
+    // Spreadsheets in some locales export with ';', and .tsv files use tabs
+    let delimiter = [';', '\t']
+        .into_iter()
+        .find(|d| first.matches(*d).count() > first.matches(',').count())
+        .unwrap_or(',');
+
+    let mut columns = CsvColumns { artist: Some(0), title: 1, album: Some(2) };
+    let header = split_csv_line(first, delimiter);
+    if let Some(from_header) = CsvColumns::from_header(&header) {
+        columns = from_header;
+        lines.next();
+    }
+
+    let mut rows = Vec::new();
+    for line in lines {
+        let cells = split_csv_line(line, delimiter);
+        let links: Vec<ImportRow> = cells.iter().flat_map(|cell| link_rows(cell)).collect();
+        if !links.is_empty() {
+            rows.extend(links);
+            continue;
+        }
+
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|i| cells.get(i))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let Some(title) = cell(Some(columns.title)) else {
+            continue;
+        };
+        rows.push(ImportRow::Track(ImportTrack {
+            artist: cell(columns.artist).unwrap_or_default(),
+            title,
+            album: cell(columns.album),
+            duration_secs: None,
+        }));
+    }
+
+    rows
+}
+
+/// Column indexes of a CSV file
+struct CsvColumns {
+    artist: Option<usize>,
+    title: usize,
+    album: Option<usize>,
+}
+
+impl CsvColumns {
+    /// Columns named by a header row, or None if the first row isn't a header
+    fn from_header(header: &[String]) -> Option<Self> {
+        let names: Vec<String> = header.iter().map(|cell| cell.trim().to_lowercase()).collect();
+        let find = |matches: &dyn Fn(&str) -> bool| names.iter().position(|name| matches(name.as_str()));
+
+        let title = find(&|name| name.contains("title") || name == "name" || name == "track" || name == "track name")?;
+        let artist = find(&|name| name.contains("artist") && !name.contains("album"));
+        let album = find(&|name| name.contains("album") && !name.contains("artist"));
+        // A title column alone could be data ("Title Fight,Shed,...") - a header names more than one
+        if artist.is_none() && album.is_none() {
+            return None;
+        }
+        Some(CsvColumns { artist, title, album })
+    }
+}
+
+/// Split one CSV line, honouring double quotes ("a, b" and "" escapes)
+/// Quoted fields spanning several lines aren't supported
+fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    cells
+}
+
+/// Plain text: one "Artist - Title" per line; lines with links are queued by their links
+fn parse_text(contents: &str) -> Vec<ImportRow> {
+    let mut rows = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+        let links = link_rows(line);
+        if !links.is_empty() {
+            rows.extend(links);
+            continue;
+        }
+        let track = split_artist_title(line);
+        if !track.title.is_empty() {
+            rows.push(ImportRow::Track(track));
+        }
+    }
+    rows
+}
+
+/// Between artist and title in "Artist - Title" lines (hyphen, en dash, em dash)
+const ARTIST_TITLE_SEPARATORS: &[&str] = &[" - ", " – ", " — "];
+
+/// "01. Artist - Title" -> artist "Artist", title "Title"
+/// Without a separator the whole text is the title
+fn split_artist_title(text: &str) -> ImportTrack {
+    let text = strip_track_number(text.trim());
+    let split = ARTIST_TITLE_SEPARATORS
+        .iter()
+        .find_map(|separator| text.split_once(separator));
+
+    match split {
+        Some((artist, title)) if !artist.trim().is_empty() && !title.trim().is_empty() => ImportTrack {
+            artist: artist.trim().to_string(),
+            title: title.trim().to_string(),
+            ..ImportTrack::default()
+        },
+        _ => ImportTrack {
+            title: text.trim_matches(|c: char| c == '-' || c.is_whitespace()).to_string(),
+            ..ImportTrack::default()
+        },
+    }
+}
+
+/// Drop a leading track number ("1. ", "01) ", "03 - Artist - Title", "07 ")
+/// A plain space only counts after a zero-padded number, so "99 Luftballons" is kept, and
+/// " - " only when another separator follows, so "311 - Amber" keeps its artist
+fn strip_track_number(text: &str) -> &str {
+    let rest = text.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == text.len() {
+        return text;
+    }
+    let padded = text.starts_with('0');
+    for prefix in [". ", ") ", " - ", " "] {
+        if prefix == " " && !padded {
+            continue;
+        }
+        if let Some(title) = rest.strip_prefix(prefix) {
+            if prefix == " - " && !ARTIST_TITLE_SEPARATORS.iter().any(|separator| title.contains(separator)) {
+                continue;
+            }
+            if !title.trim().is_empty() {
+                return title;
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(artist: &str, title: &str) -> ImportRow {
+        ImportRow::Track(ImportTrack {
+            artist: artist.to_string(),
+            title: title.to_string(),
+            ..ImportTrack::default()
+        })
+    }
+
+    #[test]
+    fn test_format_and_name_from_file_name() {
+        assert_eq!(ImportFormat::from_file_name("Road Trip.M3U8"), ImportFormat::M3u);
+        assert_eq!(ImportFormat::from_file_name("export.csv"), ImportFormat::Csv);
+        assert_eq!(ImportFormat::from_file_name("list.txt"), ImportFormat::Text);
+        assert_eq!(ImportFormat::from_file_name("list"), ImportFormat::Text);
+
+        assert_eq!(parse_playlist("Road Trip.m3u8", "").name, "Road Trip");
+        assert_eq!(parse_playlist(".csv", "").name, ".csv");
+    }
+
+    #[test]
+    fn test_parse_m3u() {
+        let contents = "\u{feff}#EXTM3U\n\
+            #EXTINF:354,Queen - Bohemian Rhapsody\n\
+            C:\\Music\\Queen\\01 Bohemian Rhapsody.mp3\n\
+            \n\
+            /home/me/Music/Daft Punk - One More Time.flac\n\
+            #EXTINF:-1,Stream\n\
+            https://www.youtube.com/watch?v=dQw4w9WgXcQ\n";
+        let playlist = parse_playlist("mix.m3u", contents);
+
+        assert_eq!(playlist.rows, vec![
+            ImportRow::Track(ImportTrack {
+                artist: "Queen".to_string(),
+                title: "Bohemian Rhapsody".to_string(),
+                album: None,
+                duration_secs: Some(354),
+            }),
+            track("Daft Punk", "One More Time"),
+            ImportRow::Link("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        // Without a header: Artist, Title, Album - quoted commas stay in the cell
+        let playlist = parse_playlist("a.csv", "Queen,\"Bohemian Rhapsody\",\"A Night at the Opera\"\n\"Crosby, Stills & Nash\",Helplessly Hoping,\n");
+        assert_eq!(playlist.rows, vec![
+            ImportRow::Track(ImportTrack {
+                artist: "Queen".to_string(),
+                title: "Bohemian Rhapsody".to_string(),
+                album: Some("A Night at the Opera".to_string()),
+                duration_secs: None,
+            }),
+            track("Crosby, Stills & Nash", "Helplessly Hoping"),
+        ]);
+
+        // Header row (Exportify-style), ';' delimiter, and a row queued by its link
+        let contents = "Track URI;Track Name;Album Name;Artist Name(s);Album Artist Name(s)\n\
+            ;Around the World;Homework;Daft Punk;Daft Punk\n\
+            spotify:track:4cOdK2wGLETKBW3PvgPWqT;One More Time;Discovery;Daft Punk;Daft Punk\n";
+        let rows = parse_playlist("b.csv", contents).rows;
+        assert_eq!(rows[0], ImportRow::Track(ImportTrack {
+            artist: "Daft Punk".to_string(),
+            title: "Around the World".to_string(),
+            album: Some("Homework".to_string()),
+            duration_secs: None,
+        }));
+        assert!(matches!(&rows[1], ImportRow::Link(url) if url.contains("4cOdK2wGLETKBW3PvgPWqT")));
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let contents = "# Saturday\n\
+            1. Queen - Bohemian Rhapsody\n\
+            02) Daft Punk – One More Time\n\
+            Yesterday\n\
+            check this out https://youtu.be/dQw4w9WgXcQ\n\
+            \n";
+        assert_eq!(parse_playlist("list.txt", contents).rows, vec![
+            track("Queen", "Bohemian Rhapsody"),
+            track("Daft Punk", "One More Time"),
+            track("", "Yesterday"),
+            ImportRow::Link("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_strip_track_number() {
+        assert_eq!(strip_track_number("01 Bohemian Rhapsody"), "Bohemian Rhapsody");
+        assert_eq!(strip_track_number("3 - Queen - Bohemian Rhapsody"), "Queen - Bohemian Rhapsody");
+        assert_eq!(strip_track_number("1999"), "1999");
+        assert_eq!(strip_track_number("99 Luftballons"), "99 Luftballons");
+
+        // Bands named by a number keep their name
+        for line in ["311 - Amber", "112 - Cupid", "702 - Steelo"] {
+            assert_eq!(strip_track_number(line), line);
+        }
+        assert_eq!(split_artist_title("311 - Amber"), ImportTrack {
+            artist: "311".to_string(),
+            title: "Amber".to_string(),
+            ..ImportTrack::default()
+        });
+    }
+
+    #[test]
+    fn test_csv_header_needs_two_columns() {
+        let cells = |row: &str| split_csv_line(row, ',');
+        assert!(CsvColumns::from_header(&cells("Title,Artist,Album")).is_some());
+        assert!(CsvColumns::from_header(&cells("Track Name,Album Name")).is_some());
+
+        // A band with "title" in its name on the first data row
+        assert!(CsvColumns::from_header(&cells("Title Fight,Shed,Shed")).is_none());
+        let rows = parse_playlist("a.csv", "Title Fight,Shed,Shed\nQueen,Bohemian Rhapsody,\n").rows;
+        assert_eq!(rows[0], ImportRow::Track(ImportTrack {
+            artist: "Title Fight".to_string(),
+            title: "Shed".to_string(),
+            album: Some("Shed".to_string()),
+            duration_secs: None,
+        }));
+        assert_eq!(rows.len(), 2);
+    }
+}
@@ -0,0 +1,134 @@
+// Per-resource concurrency limits for download workers
+// Workers run several jobs at once, but some backends (YouTube search) need a tighter cap
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+use crate::download::control::JobControl;
+use crate::error::AppResult;
+use crate::utils::ResourceLimits;
+
+// In-use permit counts per resource
+static IN_USE: std::sync::LazyLock<Mutex<HashMap<DownloadResource, usize>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Signalled whenever a permit is released
+static RELEASED: std::sync::LazyLock<Notify> = std::sync::LazyLock::new(Notify::new);
+
+/// External resources with their own concurrency limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DownloadResource {
+    YtDlpSearch,
+    YtDlpDownload,
+    Deezer,
+}
+
+impl DownloadResource {
+    /// Current limit for this resource (read from settings so changes apply to the next acquire)
+    fn limit(&self) -> usize {
+        self.limit_in(&crate::utils::get_resource_limits())
+    }
+
+    /// This resource's cap in the given limits (never below 1, so a bad setting can't stall downloads)
+    fn limit_in(&self, limits: &ResourceLimits) -> usize {
+        let limit = match self {
+            DownloadResource::YtDlpSearch => limits.ytdlp_search,
+            DownloadResource::YtDlpDownload => limits.ytdlp_download,
+            DownloadResource::Deezer => limits.deezer,
+        };
+        limit.max(1)
+    }
+
+    /// Wait until a slot for this resource is free and take it
+    /// The slot is released when the returned permit is dropped
+    pub async fn acquire(self) -> ResourcePermit {
+        loop {
+            // Register for wakeups before checking, so a release between check and await isn't missed
+            let released = RELEASED.notified();
+            if let Some(permit) = self.try_acquire(self.limit()) {
+                return permit;
+            }
+            println!("[Limits] Waiting for free {:?} slot", self);
+            released.await;
+        }
+    }
+
+    /// Take a slot if fewer than `limit` are in use
+    fn try_acquire(self, limit: usize) -> Option<ResourcePermit> {
+        let mut in_use = IN_USE.lock().unwrap_or_else(|e| e.into_inner());
+        let count = in_use.entry(self).or_insert(0);
+        if *count < limit {
+            *count += 1;
+            return Some(ResourcePermit { resource: self });
+        }
+        None
+    }
+
+    /// Like `acquire`, but gives up if the job is paused or cancelled while it waits
+    pub async fn acquire_for_job(self, job_id: &str) -> AppResult<ResourcePermit> {
+        let mut control = JobControl::subscribe(job_id);
+        tokio::select! {
+            permit = self.acquire() => {
+                // Both may be ready at once - a stopped job doesn't keep the slot
+                JobControl::check(job_id)?;
+                Ok(permit)
+            }
+            signal = control.stopped() => Err(signal.stop_error()),
+        }
+    }
+}
+
+/// Held while a resource slot is in use
+pub struct ResourcePermit {
+    resource: DownloadResource,
+}
+
+impl Drop for ResourcePermit {
+    fn drop(&mut self) {
+        {
+            let mut in_use = IN_USE.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(count) = in_use.get_mut(&self.resource) {
+                *count = count.saturating_sub(1);
+            }
+        }
+        RELEASED.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_per_resource() {
+        let limits = ResourceLimits {
+            ytdlp_search: 1,
+            ytdlp_download: 3,
+            deezer: 0,
+        };
+        assert_eq!(DownloadResource::YtDlpSearch.limit_in(&limits), 1);
+        assert_eq!(DownloadResource::YtDlpDownload.limit_in(&limits), 3);
+        // A zero limit would never hand out a slot
+        assert_eq!(DownloadResource::Deezer.limit_in(&limits), 1);
+    }
+
+    #[test]
+    fn test_permits_are_capped_per_resource() {
+        // Deezer slots are only taken here, so the count starts at zero
+        let first = DownloadResource::Deezer.try_acquire(2).unwrap();
+        let second = DownloadResource::Deezer.try_acquire(2).unwrap();
+        assert!(DownloadResource::Deezer.try_acquire(2).is_none());
+
+        // Another resource has its own count
+        let download = DownloadResource::YtDlpDownload.try_acquire(usize::MAX).unwrap();
+        assert!(DownloadResource::Deezer.try_acquire(2).is_none());
+
+        drop(first);
+        let third = DownloadResource::Deezer.try_acquire(2).unwrap();
+        assert!(DownloadResource::Deezer.try_acquire(2).is_none());
+
+        drop((second, third, download));
+        assert_eq!(IN_USE.lock().unwrap()[&DownloadResource::Deezer], 0);
+    }
+}
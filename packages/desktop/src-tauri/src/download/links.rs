@@ -0,0 +1,741 @@
+// Link parsing - the one place that knows what each service's links look like
+// A pasted, dropped or typed link is parsed into its service, what it points to (track,
+// album, playlist, artist) and the ID, plus a canonical URL without localization prefixes,
+// tracking parameters or embed wrappers. Short links are followed to the page they open.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+
+use crate::download::services::{collection_source_for_url, source_for_service, source_for_url};
+use crate::download::MusicService;
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+
+/// Hosts that only redirect to a real link (share buttons, mobile apps)
+const SHORT_LINK_HOSTS: &[&str] = &[
+    "spotify.link",
+    "spotify.app.link",
+    "on.soundcloud.com",
+    "deezer.page.link",
+    "link.deezer.com",
+];
+
+/// First path segments of soundcloud.com that aren't user pages
+const SOUNDCLOUD_RESERVED: &[&str] = &[
+    "discover", "search", "stream", "you", "charts", "upload", "pages", "tags", "terms-of-use", "player",
+];
+
+/// First path segments of bandcamp.com that aren't fan pages
+const BANDCAMP_RESERVED: &[&str] = &[
+    "download", "discover", "tag", "search", "about", "help", "login", "signup", "artists", "fans", "api",
+    "EmbeddedPlayer", "yum", "gift_cards", "terms_of_use", "privacy", "guide",
+];
+
+/// Custom domains found to serve a Bandcamp artist site (music.artist.com instead of artist.bandcamp.com)
+static BANDCAMP_DOMAINS: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Pages of a SoundCloud user that list several tracks
+const SOUNDCLOUD_USER_LISTS: &[&str] = &["tracks", "likes", "reposts", "albums", "popular-tracks", "sets"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Track,
+    Album,
+    Playlist,
+    Artist,     // Artist/user/channel page
+    ShortLink,  // Not parsed yet - resolve_link follows it
+}
+
+impl LinkKind {
+    /// Whether the link lists several tracks rather than being one
+    pub fn is_collection(&self) -> bool {
+        matches!(self, LinkKind::Album | LinkKind::Playlist | LinkKind::Artist)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedLink {
+    pub service: MusicService,
+    pub kind: LinkKind,
+    pub id: String,
+    pub url: String,  // Canonical URL, used for queueing and comparison
+}
+
+impl ParsedLink {
+    fn new(service: MusicService, kind: LinkKind, id: impl Into<String>, url: String) -> Self {
+        ParsedLink {
+            service,
+            kind,
+            id: id.into(),
+            url,
+        }
+    }
+
+    /// The ID, if this is a link of the given service and kind
+    pub fn id_of(&self, service: MusicService, kind: LinkKind) -> Option<&str> {
+        (self.service == service && self.kind == kind).then_some(self.id.as_str())
+    }
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse a link of any supported service (without network access)
+/// Returns None for links we don't recognize
+pub fn parse_link(input: &str) -> Option<ParsedLink> {
+    let input = input.trim();
+    if let Some(uri) = input.strip_prefix("spotify:") {
+        return parse_spotify_uri(uri);
+    }
+
+    let parsed = url::Url::parse(input)
+        .ok()
+        .filter(|u| u.has_host())
+        .or_else(|| url::Url::parse(&format!("https://{}", input)).ok())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return None;
+    }
+
+    let host = parsed.host_str()?.to_lowercase();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(&host);
+    let segments: Vec<&str> = parsed
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    if SHORT_LINK_HOSTS.contains(&host) {
+        let service = match host {
+            "spotify.link" | "spotify.app.link" => MusicService::Spotify,
+            "on.soundcloud.com" => MusicService::SoundCloud,
+            _ => MusicService::Deezer,
+        };
+        let url = format!("https://{}/{}", host, segments.join("/"));
+        return Some(ParsedLink::new(service, LinkKind::ShortLink, segments.join("/"), url));
+    }
+
+    match host {
+        "open.spotify.com" | "play.spotify.com" => parse_spotify_path(&segments),
+        "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => parse_youtube(&parsed, &segments),
+        "youtu.be" => youtube_video(segments.first()?),
+        "soundcloud.com" => parse_soundcloud(&segments),
+        "w.soundcloud.com" => parse_soundcloud_embed(&parsed),
+        "api.soundcloud.com" => parse_soundcloud_api(&segments),
+        "music.apple.com" | "geo.music.apple.com" | "itunes.apple.com" => parse_apple_music(&parsed, &segments),
+        "deezer.com" => parse_deezer(&segments),
+        "tidal.com" | "listen.tidal.com" => parse_tidal(&segments),
+        "bandcamp.com" => parse_bandcamp_fan(&segments),
+        _ if host.ends_with(".bandcamp.com") || is_bandcamp_domain(host) => parse_bandcamp(host, &segments),
+        _ => None,
+    }
+}
+
+fn spotify_link(kind: &str, id: &str) -> Option<ParsedLink> {
+    let kind = match kind {
+        "track" => LinkKind::Track,
+        "album" => LinkKind::Album,
+        "playlist" => LinkKind::Playlist,
+        "artist" => LinkKind::Artist,
+        _ => return None,
+    };
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let url = format!("https://open.spotify.com/{}/{}", kind_path(kind), id);
+    Some(ParsedLink::new(MusicService::Spotify, kind, id, url))
+}
+
+fn kind_path(kind: LinkKind) -> &'static str {
+    match kind {
+        LinkKind::Track => "track",
+        LinkKind::Album => "album",
+        LinkKind::Playlist => "playlist",
+        LinkKind::Artist => "artist",
+        LinkKind::ShortLink => "",
+    }
+}
+
+/// spotify:track:ID, spotify:album:ID, spotify:user:NAME:playlist:ID, ...
+fn parse_spotify_uri(uri: &str) -> Option<ParsedLink> {
+    let parts: Vec<&str> = uri.split(':').collect();
+    match parts.as_slice() {
+        ["user", _, "playlist", id] => spotify_link("playlist", id),
+        [kind, id] => spotify_link(kind, id),
+        _ => None,
+    }
+}
+
+/// /track/ID, /intl-he/track/ID, /embed/track/ID, /user/NAME/playlist/ID
+fn parse_spotify_path(segments: &[&str]) -> Option<ParsedLink> {
+    let mut segments = segments;
+    if segments.first().is_some_and(|s| s.starts_with("intl-")) {
+        segments = &segments[1..];
+    }
+    if segments.first() == Some(&"embed") {
+        segments = &segments[1..];
+    }
+    match segments {
+        ["user", _, "playlist", id, ..] => spotify_link("playlist", id),
+        [kind, id, ..] => spotify_link(kind, id),
+        _ => None,
+    }
+}
+
+fn youtube_video(id: &str) -> Option<ParsedLink> {
+    let valid = id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| {
+        let url = format!("https://www.youtube.com/watch?v={}", id);
+        ParsedLink::new(MusicService::YouTube, LinkKind::Track, id, url)
+    })
+}
+
+/// A video played from a playlist ("watch?v=...&list=...") is still a single track;
+/// only playlist pages and watch links without a video are playlists
+fn parse_youtube(parsed: &url::Url, segments: &[&str]) -> Option<ParsedLink> {
+    let param = |name: &str| {
+        parsed
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+            .filter(|value| !value.is_empty())
+    };
+
+    match segments {
+        ["watch"] => match (param("v"), param("list")) {
+            (Some(video_id), _) => youtube_video(&video_id),
+            (None, Some(list_id)) => youtube_playlist(&list_id),
+            (None, None) => None,
+        },
+        ["playlist"] => youtube_playlist(&param("list")?),
+        ["shorts" | "embed" | "live" | "v", id, ..] => youtube_video(id),
+        ["channel" | "c" | "user", name, ..] => youtube_channel(&format!("{}/{}", segments[0], name)),
+        [handle, ..] if handle.starts_with('@') => youtube_channel(handle),
+        _ => None,
+    }
+}
+
+fn youtube_playlist(id: &str) -> Option<ParsedLink> {
+    let url = format!("https://www.youtube.com/playlist?list={}", id);
+    Some(ParsedLink::new(MusicService::YouTube, LinkKind::Playlist, id, url))
+}
+
+fn youtube_channel(path: &str) -> Option<ParsedLink> {
+    let url = format!("https://www.youtube.com/{}", path);
+    Some(ParsedLink::new(MusicService::YouTube, LinkKind::Artist, path, url))
+}
+
+/// soundcloud.com/USER                      artist
+/// soundcloud.com/USER/sets/SET[/s-SECRET]  playlist (set / album)
+/// soundcloud.com/USER/tracks|likes|...     playlist (a user's track list)
+/// soundcloud.com/USER/TRACK[/s-SECRET]     track
+fn parse_soundcloud(segments: &[&str]) -> Option<ParsedLink> {
+    let user = *segments.first()?;
+    if SOUNDCLOUD_RESERVED.contains(&user) {
+        return None;
+    }
+    let kind = match segments {
+        [_] => LinkKind::Artist,
+        [_, "sets", _] | [_, "sets", _, _] => LinkKind::Playlist,
+        [_, page] if SOUNDCLOUD_USER_LISTS.contains(page) => LinkKind::Playlist,
+        [_, _] => LinkKind::Track,
+        [_, _, secret] if secret.starts_with("s-") => LinkKind::Track,
+        _ => return None,
+    };
+    let id = segments.join("/");
+    let url = format!("https://soundcloud.com/{}", id);
+    Some(ParsedLink::new(MusicService::SoundCloud, kind, id, url))
+}
+
+/// w.soundcloud.com/player/?url=<track or playlist link>
+fn parse_soundcloud_embed(parsed: &url::Url) -> Option<ParsedLink> {
+    let (_, inner) = parsed.query_pairs().find(|(key, _)| key == "url")?;
+    parse_link(&inner).filter(|link| link.service == MusicService::SoundCloud)
+}
+
+/// api.soundcloud.com/tracks/ID and /playlists/ID (what embeds point to)
+fn parse_soundcloud_api(segments: &[&str]) -> Option<ParsedLink> {
+    let kind = match segments {
+        ["tracks", _] => LinkKind::Track,
+        ["playlists", _] => LinkKind::Playlist,
+        _ => return None,
+    };
+    let id = segments[1];
+    let url = format!("https://api.soundcloud.com/{}/{}", segments[0], id);
+    Some(ParsedLink::new(MusicService::SoundCloud, kind, id, url))
+}
+
+/// music.apple.com/STOREFRONT/(song|album|playlist|artist)/[SLUG/]ID, with album?i=SONG for songs
+fn parse_apple_music(parsed: &url::Url, segments: &[&str]) -> Option<ParsedLink> {
+    let is_storefront = |s: &str| s.len() == 2 && s.chars().all(|c| c.is_ascii_alphabetic());
+    let (storefront, rest) = match segments {
+        [first, rest @ ..] if is_storefront(first) => (first.to_lowercase(), rest),
+        _ => ("us".to_string(), segments),
+    };
+    let (page, tail) = rest.split_first()?;
+    // iTunes links write IDs as "id1234567890"
+    let numeric_id = || {
+        tail.iter()
+            .rev()
+            .map(|s| s.strip_prefix("id").unwrap_or(s))
+            .find(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
+            .map(|s| s.to_string())
+    };
+    let song_param = parsed
+        .query_pairs()
+        .find(|(key, _)| key == "i")
+        .map(|(_, value)| value.into_owned())
+        .filter(|value| !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()));
+
+    let (kind, id) = match *page {
+        "song" => (LinkKind::Track, numeric_id()?),
+        "album" => match song_param {
+            Some(song_id) => (LinkKind::Track, song_id),
+            None => (LinkKind::Album, numeric_id()?),
+        },
+        "artist" => (LinkKind::Artist, numeric_id()?),
+        "playlist" => (LinkKind::Playlist, tail.iter().rev().find(|s| s.starts_with("pl."))?.to_string()),
+        _ => return None,
+    };
+    let page = if kind == LinkKind::Track { "song" } else { *page };
+    let url = format!("https://music.apple.com/{}/{}/{}", storefront, page, id);
+    Some(ParsedLink::new(MusicService::AppleMusic, kind, id, url))
+}
+
+/// ARTIST.bandcamp.com/track/SLUG, /album/SLUG, or the artist's front page
+/// Custom domains work the same way, with the domain standing in for the artist
+fn parse_bandcamp(host: &str, segments: &[&str]) -> Option<ParsedLink> {
+    let artist = host.strip_suffix(".bandcamp.com").unwrap_or(host);
+    let (kind, id, url) = match segments {
+        ["track", slug, ..] => (LinkKind::Track, format!("{}/{}", artist, slug), format!("https://{}/track/{}", host, slug)),
+        ["album", slug, ..] => (LinkKind::Album, format!("{}/{}", artist, slug), format!("https://{}/album/{}", host, slug)),
+        [] | ["music"] => (LinkKind::Artist, artist.to_string(), format!("https://{}/music", host)),
+        _ => return None,
+    };
+    Some(ParsedLink::new(MusicService::Bandcamp, kind, id, url))
+}
+
+/// bandcamp.com/FAN - a fan's collection of purchases, expanded like a playlist
+fn parse_bandcamp_fan(segments: &[&str]) -> Option<ParsedLink> {
+    let [fan] = segments else {
+        return None;
+    };
+    if BANDCAMP_RESERVED.contains(fan) {
+        return None;
+    }
+    let url = format!("https://bandcamp.com/{}", fan);
+    Some(ParsedLink::new(MusicService::Bandcamp, LinkKind::Playlist, *fan, url))
+}
+
+fn is_bandcamp_domain(host: &str) -> bool {
+    BANDCAMP_DOMAINS.lock().unwrap_or_else(|e| e.into_inner()).contains(host)
+}
+
+/// Kind names shared by Deezer and Tidal paths
+fn numbered_kind(kind: &str) -> Option<LinkKind> {
+    match kind {
+        "track" => Some(LinkKind::Track),
+        "album" => Some(LinkKind::Album),
+        "playlist" => Some(LinkKind::Playlist),
+        "artist" => Some(LinkKind::Artist),
+        _ => None,
+    }
+}
+
+/// deezer.com/[LANG/](track|album|playlist|artist)/ID
+fn parse_deezer(segments: &[&str]) -> Option<ParsedLink> {
+    let segments = match segments {
+        [lang, rest @ ..] if lang.len() == 2 => rest,
+        _ => segments,
+    };
+    let [kind_name, id, ..] = segments else {
+        return None;
+    };
+    let kind = numbered_kind(kind_name)?;
+    if !id.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let url = format!("https://www.deezer.com/{}/{}", kind_name, id);
+    Some(ParsedLink::new(MusicService::Deezer, kind, *id, url))
+}
+
+/// tidal.com/[browse/](track|album|playlist|artist)/ID, and album/ID/track/ID
+fn parse_tidal(segments: &[&str]) -> Option<ParsedLink> {
+    let segments = segments.strip_prefix(&["browse"]).unwrap_or(segments);
+    let (kind_name, id) = match segments {
+        ["album", _, "track", id, ..] => ("track", *id),
+        [kind_name, id, ..] => (*kind_name, *id),
+        _ => return None,
+    };
+    let kind = numbered_kind(kind_name)?;
+    let url = format!("https://tidal.com/browse/{}/{}", kind_name, id);
+    Some(ParsedLink::new(MusicService::Tidal, kind, id, url))
+}
+
+// ============================================================================
+// Short links
+// ============================================================================
+
+/// Parse a link, following short links to the page they open
+/// Track and album links on unknown domains are checked for a Bandcamp artist site
+pub async fn resolve_link(input: &str) -> AppResult<ParsedLink> {
+    let link = match parse_link(input) {
+        Some(link) => link,
+        None => detect_bandcamp_domain(input)
+            .await
+            .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, format!("Unsupported link: {}", input.trim())))?,
+    };
+    if link.kind != LinkKind::ShortLink {
+        return Ok(link);
+    }
+
+    println!("[Links] Following short link: {}", link.url);
+    let target = follow_short_link(&link.url).await?;
+    println!("[Links] Short link opens: {}", target.url);
+    Ok(target)
+}
+
+/// Canonical form of a link entering the app (queue, drag & drop, clipboard)
+/// Links we don't recognize, or short links that can't be followed, are returned as given
+pub async fn normalize_link(input: &str) -> String {
+    match resolve_link(input).await {
+        Ok(link) => link.url,
+        Err(e) => {
+            if parse_link(input).is_some() {
+                println!("[Links] Could not resolve {}: {}", input.trim(), e);
+            }
+            input.trim().to_string()
+        }
+    }
+}
+
+/// Open a /track/ or /album/ link on an unknown domain and, if it's a Bandcamp page,
+/// remember the domain so its links are handled by the Bandcamp source from now on
+async fn detect_bandcamp_domain(input: &str) -> Option<ParsedLink> {
+    let parsed = url::Url::parse(input.trim()).ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    let first_segment = parsed.path_segments()?.next()?;
+    if !matches!(parsed.scheme(), "http" | "https") || !matches!(first_segment, "track" | "album") {
+        return None;
+    }
+
+    let html = reqwest::get(parsed.as_str()).await.ok()?.text().await.ok()?;
+    if !is_bandcamp_page(&html) {
+        return None;
+    }
+    println!("[Links] {} is a Bandcamp site", host);
+    BANDCAMP_DOMAINS.lock().unwrap_or_else(|e| e.into_inner()).insert(host);
+    parse_link(input)
+}
+
+/// Bandcamp pages load their assets from bcbits.com and name Bandcamp as the generator
+fn is_bandcamp_page(html: &str) -> bool {
+    html.contains("<meta name=\"generator\" content=\"Bandcamp\"") || html.contains("bcbits.com/")
+}
+
+async fn follow_short_link(url: &str) -> AppResult<ParsedLink> {
+    // Redirects are followed by default; a browser user agent gets HTTP redirects
+    // instead of an "open in app" page from most share-link services
+    let client = reqwest::Client::builder()
+        .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36")
+        .build()
+        .or_error(ErrorKind::Internal, "Failed to create HTTP client")?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .or_error(ErrorKind::Network, "Failed to open short link")?;
+
+    if let Some(link) = parse_link(response.url().as_str()).filter(|link| link.kind != LinkKind::ShortLink) {
+        return Ok(link);
+    }
+
+    // Some services redirect with JavaScript - take the first link on the page
+    let body = response.text().await.unwrap_or_default();
+    scan_links(&body)
+        .into_iter()
+        .find(|link| link.kind != LinkKind::ShortLink)
+        .ok_or_else(|| AppError::new(ErrorKind::NotFound, format!("Short link did not lead to a music link: {}", url)))
+}
+
+// ============================================================================
+// Links in text
+// ============================================================================
+
+/// Every downloadable link in a block of text (a chat message, a list, an HTML page), in order
+/// Links are deduplicated by canonical URL; short links are included unresolved
+pub fn find_links(text: &str) -> Vec<ParsedLink> {
+    scan_links(text).into_iter().filter(is_downloadable).collect()
+}
+
+/// Whether a source can queue the link - recognized Deezer/Tidal links and artist pages
+/// without an expander are left out rather than offered and then failing
+fn is_downloadable(link: &ParsedLink) -> bool {
+    match link.kind {
+        LinkKind::ShortLink => source_for_service(&link.service).is_some(),
+        kind if kind.is_collection() => collection_source_for_url(&link.url).is_ok(),
+        _ => source_for_url(&link.url).is_some(),
+    }
+}
+
+/// Every recognized link in text, whether or not it can be downloaded
+fn scan_links(text: &str) -> Vec<ParsedLink> {
+    let text = text.replace("\\/", "/").replace("&amp;", "&");
+    let mut links: Vec<ParsedLink> = Vec::new();
+
+    let tokens = text.split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '(' | ')' | '[' | ']' | '{' | '}' | '\\' | '|'));
+    for token in tokens {
+        // "Listen:https://..." - the link starts mid-token
+        let token = token
+            .find("http://")
+            .or_else(|| token.find("https://"))
+            .map_or(token, |start| &token[start..]);
+        // Sentence punctuation after a link isn't part of it
+        let token = token.trim_end_matches(['.', ',', ';', ':', '!', '?', '…']);
+        if token.is_empty() {
+            continue;
+        }
+        if let Some(link) = parse_link(token) {
+            if !links.iter().any(|known| known.url == link.url) {
+                links.push(link);
+            }
+        }
+    }
+    links
+}
+
+/// find_links, with short links followed (those that can't be followed are dropped)
+pub async fn resolve_links(text: &str) -> Vec<ParsedLink> {
+    let mut links: Vec<ParsedLink> = Vec::new();
+    for link in find_links(text) {
+        let link = if link.kind == LinkKind::ShortLink {
+            match resolve_link(&link.url).await {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    println!("[Links] Skipping {}: {}", link.url, e);
+                    continue;
+                }
+            }
+        } else {
+            link
+        };
+        if is_downloadable(&link) && !links.iter().any(|known| known.url == link.url) {
+            links.push(link);
+        }
+    }
+    links
+}
+
+// ============================================================================
+// Previews
+// ============================================================================
+
+/// Title and author of a link, shown before it's queued (e.g. in the smart paste picker)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkPreview {
+    pub title: String,
+    pub author: Option<String>,
+}
+
+/// oEmbed endpoint of a service - one small request instead of a yt-dlp run or an API login
+fn oembed_endpoint(service: &MusicService) -> Option<&'static str> {
+    match service {
+        MusicService::YouTube => Some("https://www.youtube.com/oembed"),
+        MusicService::SoundCloud => Some("https://soundcloud.com/oembed"),
+        MusicService::Spotify => Some("https://open.spotify.com/oembed"),
+        _ => None,
+    }
+}
+
+/// Preview of a link through its service's oEmbed endpoint
+pub async fn preview_link(input: &str) -> AppResult<LinkPreview> {
+    let link = resolve_link(input).await?;
+    let endpoint = oembed_endpoint(&link.service).ok_or_else(|| {
+        AppError::new(
+            ErrorKind::Unsupported,
+            format!("{} links can't be previewed", link.service.display_name()),
+        )
+    })?;
+    let request_url = url::Url::parse_with_params(endpoint, &[("format", "json"), ("url", link.url.as_str())])
+        .or_error(ErrorKind::Internal, "Failed to build preview URL")?;
+
+    let response = reqwest::Client::new()
+        .get(request_url)
+        .send()
+        .await
+        .or_error(ErrorKind::Network, "Preview request failed")?;
+    if !response.status().is_success() {
+        return Err(AppError::http(response.status(), format!("Preview failed: {}", response.status())));
+    }
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .or_error(ErrorKind::Parse, "Failed to parse preview response")?;
+
+    parse_oembed(&json).ok_or_else(|| AppError::new(ErrorKind::Parse, format!("No title in preview of {}", link.url)))
+}
+
+/// { "title": ..., "author_name": ... } - Spotify leaves out the author
+fn parse_oembed(json: &serde_json::Value) -> Option<LinkPreview> {
+    let text = |key: &str| {
+        json.get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    Some(LinkPreview {
+        title: text("title")?,
+        author: text("author_name"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_link() {
+        use LinkKind::*;
+        use MusicService::*;
+
+        let cases: &[(&str, MusicService, LinkKind, &str, &str)] = &[
+            // Spotify
+            ("https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC?si=abc", Spotify, Track, "4uLU6hMCjMI75M1A2tKUQC", "https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC"),
+            ("https://open.spotify.com/intl-he/track/4uLU6hMCjMI75M1A2tKUQC", Spotify, Track, "4uLU6hMCjMI75M1A2tKUQC", "https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC"),
+            ("open.spotify.com/album/4aawyAB9vmqN3uQ7FjRGTy", Spotify, Album, "4aawyAB9vmqN3uQ7FjRGTy", "https://open.spotify.com/album/4aawyAB9vmqN3uQ7FjRGTy"),
+            ("https://open.spotify.com/embed/playlist/37i9dQZF1DXcBWIGoYBM5M?utm_source=generator", Spotify, Playlist, "37i9dQZF1DXcBWIGoYBM5M", "https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M"),
+            ("https://open.spotify.com/user/spotify/playlist/37i9dQZF1DXcBWIGoYBM5M", Spotify, Playlist, "37i9dQZF1DXcBWIGoYBM5M", "https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M"),
+            ("https://open.spotify.com/intl-de/artist/0TnOYISbd1XYRBk9myaseg", Spotify, Artist, "0TnOYISbd1XYRBk9myaseg", "https://open.spotify.com/artist/0TnOYISbd1XYRBk9myaseg"),
+            ("spotify:track:4uLU6hMCjMI75M1A2tKUQC", Spotify, Track, "4uLU6hMCjMI75M1A2tKUQC", "https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC"),
+            ("spotify:album:4aawyAB9vmqN3uQ7FjRGTy", Spotify, Album, "4aawyAB9vmqN3uQ7FjRGTy", "https://open.spotify.com/album/4aawyAB9vmqN3uQ7FjRGTy"),
+            ("spotify:user:spotify:playlist:37i9dQZF1DXcBWIGoYBM5M", Spotify, Playlist, "37i9dQZF1DXcBWIGoYBM5M", "https://open.spotify.com/playlist/37i9dQZF1DXcBWIGoYBM5M"),
+            ("https://spotify.link/AbCdEf123", Spotify, ShortLink, "AbCdEf123", "https://spotify.link/AbCdEf123"),
+            // YouTube
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ&feature=share", YouTube, Track, "dQw4w9WgXcQ", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://youtu.be/dQw4w9WgXcQ?si=x", YouTube, Track, "dQw4w9WgXcQ", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://m.youtube.com/shorts/dQw4w9WgXcQ", YouTube, Track, "dQw4w9WgXcQ", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ", YouTube, Track, "dQw4w9WgXcQ", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://music.youtube.com/watch?v=dQw4w9WgXcQ&list=RDAMVM", YouTube, Track, "dQw4w9WgXcQ", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG", YouTube, Playlist, "PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG", "https://www.youtube.com/playlist?list=PLx0sYbCqOb8TBPRdmBHs5Iftvv9TPboYG"),
+            ("https://www.youtube.com/@artist", YouTube, Artist, "@artist", "https://www.youtube.com/@artist"),
+            // SoundCloud
+            ("https://soundcloud.com/artist/track-name?in=artist/sets/ep", SoundCloud, Track, "artist/track-name", "https://soundcloud.com/artist/track-name"),
+            ("https://m.soundcloud.com/artist/sets/summer-ep/", SoundCloud, Playlist, "artist/sets/summer-ep", "https://soundcloud.com/artist/sets/summer-ep"),
+            ("https://soundcloud.com/artist/likes", SoundCloud, Playlist, "artist/likes", "https://soundcloud.com/artist/likes"),
+            ("https://soundcloud.com/artist", SoundCloud, Artist, "artist", "https://soundcloud.com/artist"),
+            ("https://w.soundcloud.com/player/?url=https%3A%2F%2Fapi.soundcloud.com%2Ftracks%2F293&color=ff5500", SoundCloud, Track, "293", "https://api.soundcloud.com/tracks/293"),
+            ("https://on.soundcloud.com/AbC12", SoundCloud, ShortLink, "AbC12", "https://on.soundcloud.com/AbC12"),
+            // Apple Music
+            ("https://music.apple.com/il/album/album-name/1234567890?i=1234567891", AppleMusic, Track, "1234567891", "https://music.apple.com/il/song/1234567891"),
+            ("https://music.apple.com/us/song/song-name/1234567891", AppleMusic, Track, "1234567891", "https://music.apple.com/us/song/1234567891"),
+            ("https://music.apple.com/us/album/album-name/1234567890?l=he", AppleMusic, Album, "1234567890", "https://music.apple.com/us/album/1234567890"),
+            ("https://music.apple.com/us/playlist/road-trip/pl.u-abc123", AppleMusic, Playlist, "pl.u-abc123", "https://music.apple.com/us/playlist/pl.u-abc123"),
+            ("https://itunes.apple.com/gb/album/name/id1234567890", AppleMusic, Album, "1234567890", "https://music.apple.com/gb/album/1234567890"),
+            // Bandcamp
+            ("https://artist.bandcamp.com/track/song-name", Bandcamp, Track, "artist/song-name", "https://artist.bandcamp.com/track/song-name"),
+            ("https://artist.bandcamp.com/album/album-name?from=x", Bandcamp, Album, "artist/album-name", "https://artist.bandcamp.com/album/album-name"),
+            ("https://artist.bandcamp.com/", Bandcamp, Artist, "artist", "https://artist.bandcamp.com/music"),
+            ("https://bandcamp.com/fanname", Bandcamp, Playlist, "fanname", "https://bandcamp.com/fanname"),
+            // Deezer and Tidal
+            ("https://www.deezer.com/en/track/3135556", Deezer, Track, "3135556", "https://www.deezer.com/track/3135556"),
+            ("https://deezer.page.link/xYz", Deezer, ShortLink, "xYz", "https://deezer.page.link/xYz"),
+            ("https://tidal.com/browse/album/77640617/track/77640618", Tidal, Track, "77640618", "https://tidal.com/browse/track/77640618"),
+            ("https://listen.tidal.com/album/77640617", Tidal, Album, "77640617", "https://tidal.com/browse/album/77640617"),
+        ];
+        for (input, service, kind, id, url) in cases {
+            let link = parse_link(input).unwrap_or_else(|| panic!("not parsed: {}", input));
+            assert_eq!(link, ParsedLink::new(service.clone(), *kind, *id, url.to_string()), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_link_rejects() {
+        for input in [
+            "",
+            "not a link",
+            "https://example.com/track/4uLU6hMCjMI75M1A2tKUQC",
+            "https://notyoutube.com/watch?v=dQw4w9WgXcQ",
+            "https://open.spotify.com/show/4rOoJ6Egrf8K2IrywzwOMk",
+            "https://www.youtube.com/watch?v=short",
+            "https://soundcloud.com/discover",
+            "https://bandcamp.com/discover",
+            "https://music.example-artist.com/album/name",
+            "spotify:track:",
+            "ftp://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC",
+        ] {
+            assert_eq!(parse_link(input), None, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_bandcamp_custom_domain() {
+        let html = r#"<html><head><meta name="generator" content="Bandcamp"><link href="https://s4.bcbits.com/client-bundle/1/x.css"></head></html>"#;
+        assert!(is_bandcamp_page(html));
+        assert!(!is_bandcamp_page("<html><head><title>Shop</title></head></html>"));
+
+        // Once a domain is known to be a Bandcamp site, its links parse like artist.bandcamp.com ones
+        assert_eq!(parse_link("https://music.custom-artist.com/album/name"), None);
+        BANDCAMP_DOMAINS.lock().unwrap().insert("music.custom-artist.com".to_string());
+        let link = parse_link("https://music.custom-artist.com/album/name?from=x").unwrap();
+        assert_eq!(
+            link,
+            ParsedLink::new(
+                MusicService::Bandcamp,
+                LinkKind::Album,
+                "music.custom-artist.com/name",
+                "https://music.custom-artist.com/album/name".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_find_links() {
+        let cases: &[(&str, &[&str])] = &[
+            // A chat message with punctuation, a scheme-less link and a repeat
+            (
+                "check this!! https://open.spotify.com/intl-he/track/4uLU6hMCjMI75M1A2tKUQC?si=1, and youtu.be/dQw4w9WgXcQ.\n\
+                 again: https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC",
+                &["https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"],
+            ),
+            // A list with URIs, albums and playlists
+            (
+                "1) spotify:album:4aawyAB9vmqN3uQ7FjRGTy\n2) (https://soundcloud.com/artist/sets/summer-ep)\n3) https://example.com/x",
+                &["https://open.spotify.com/album/4aawyAB9vmqN3uQ7FjRGTy", "https://soundcloud.com/artist/sets/summer-ep"],
+            ),
+            // Links glued to text, short links kept for resolving
+            ("Listen:https://spotify.link/abc", &["https://spotify.link/abc"]),
+            // HTML with escaped slashes and entities
+            (
+                r#"<a href="https:\/\/open.spotify.com\/track\/4uLU6hMCjMI75M1A2tKUQC">x</a> go("https://www.youtube.com/watch?v=dQw4w9WgXcQ&amp;t=1")"#,
+                &["https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC", "https://www.youtube.com/watch?v=dQw4w9WgXcQ"],
+            ),
+            ("no links here, just words.", &[]),
+            // Services and pages we can't download from are left out
+            (
+                "https://www.deezer.com/track/3135556 https://tidal.com/browse/track/77640618 https://deezer.page.link/xYz \
+                 https://artist.bandcamp.com/ https://youtu.be/dQw4w9WgXcQ https://open.spotify.com/artist/0OdUWJ0sBjDrqHygGUXeCF",
+                &["https://www.youtube.com/watch?v=dQw4w9WgXcQ", "https://open.spotify.com/artist/0OdUWJ0sBjDrqHygGUXeCF"],
+            ),
+        ];
+        for (text, expected) in cases {
+            let urls: Vec<String> = find_links(text).into_iter().map(|link| link.url).collect();
+            assert_eq!(urls, *expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_oembed() {
+        let json = serde_json::json!({ "title": "Never Gonna Give You Up", "author_name": "Rick Astley", "type": "video" });
+        assert_eq!(
+            parse_oembed(&json),
+            Some(LinkPreview { title: "Never Gonna Give You Up".to_string(), author: Some("Rick Astley".to_string()) })
+        );
+        let json = serde_json::json!({ "title": "Summer Hits", "type": "rich" });
+        assert_eq!(parse_oembed(&json).and_then(|preview| preview.author), None);
+        assert_eq!(parse_oembed(&serde_json::json!({ "title": "" })), None);
+    }
+}
@@ -0,0 +1,484 @@
+// Lyrics lookup - plain and synced (LRC) lyrics for downloaded tracks
+// Providers are tried in order until one has lyrics. The result is embedded in the
+// file's tags by the tag writer and/or written as an .lrc file next to the audio file.
+
+use std::path::{Path, PathBuf};
+
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+
+use crate::download::TrackMetadata;
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+use crate::utils::{sanitize_filename, LyricsSettings};
+
+// ============================================================================
+// Lyrics
+// ============================================================================
+
+/// One timed line of synced lyrics
+#[derive(Debug, Clone, PartialEq)]
+pub struct LyricLine {
+    pub time_ms: u32,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lyrics {
+    pub plain: Option<String>,
+    pub synced: Option<Vec<LyricLine>>,
+}
+
+impl Lyrics {
+    /// Lyrics from LRC text; text without timestamps is treated as plain lyrics
+    pub fn from_lrc(text: &str) -> Self {
+        let lines = parse_lrc(text);
+        if lines.is_empty() {
+            return Lyrics {
+                plain: Some(text.trim().to_string()).filter(|t| !t.is_empty()),
+                synced: None,
+            };
+        }
+        Lyrics {
+            plain: Some(lines.iter().map(|l| l.text.as_str()).collect::<Vec<_>>().join("\n")),
+            synced: Some(lines),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plain.is_none() && self.synced.is_none()
+    }
+
+    /// Synced lyrics as LRC text ("[01:02.34]line")
+    pub fn to_lrc(&self) -> Option<String> {
+        let lines = self.synced.as_ref()?;
+        let lrc = lines
+            .iter()
+            .map(|line| {
+                let minutes = line.time_ms / 60_000;
+                let seconds = (line.time_ms % 60_000) / 1000;
+                let centis = (line.time_ms % 1000) / 10;
+                format!("[{:02}:{:02}.{:02}]{}", minutes, seconds, centis, line.text)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(lrc)
+    }
+}
+
+/// Parse LRC text into timed lines, sorted by time
+/// Handles several timestamps per line ("[00:12.00][01:30.00]Chorus") and skips
+/// metadata tags like "[ar:Artist]"
+pub fn parse_lrc(text: &str) -> Vec<LyricLine> {
+    let mut lines = Vec::new();
+    for raw in text.lines() {
+        let mut rest = raw.trim();
+        let mut times = Vec::new();
+        while let Some(tag) = rest.strip_prefix('[') {
+            let Some((stamp, after)) = tag.split_once(']') else {
+                break;
+            };
+            match parse_timestamp(stamp) {
+                Some(time_ms) => times.push(time_ms),
+                None => break,
+            }
+            rest = after;
+        }
+        for time_ms in times {
+            lines.push(LyricLine {
+                time_ms,
+                text: rest.trim().to_string(),
+            });
+        }
+    }
+    lines.sort_by_key(|line| line.time_ms);
+    lines
+}
+
+/// "mm:ss", "mm:ss.xx" or "mm:ss.xxx" -> milliseconds
+/// None for malformed stamps, including ones too large for u32 ("[99999999:00.00]")
+fn parse_timestamp(stamp: &str) -> Option<u32> {
+    let (minutes, seconds) = stamp.split_once(':')?;
+    let minutes: u32 = minutes.parse().ok()?;
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let seconds: u32 = seconds.parse().ok()?;
+    if seconds >= 60 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // ".5" is half a second, ".05" five hundredths
+    let millis = match fraction.len() {
+        0 => 0,
+        1..=3 => fraction.parse::<u32>().ok()? * 10u32.pow(3 - fraction.len() as u32),
+        _ => fraction[..3].parse().ok()?,
+    };
+    minutes.checked_mul(60_000)?.checked_add(seconds * 1000 + millis)
+}
+
+// ============================================================================
+// Providers
+// ============================================================================
+
+/// What providers search by
+#[derive(Debug, Clone, Default)]
+pub struct LyricsQuery {
+    pub isrc: Option<String>,
+    pub artist: String,
+    pub title: String,
+    pub album: Option<String>,
+    pub duration: Option<u32>,
+}
+
+impl LyricsQuery {
+    pub fn from_metadata(metadata: &TrackMetadata, isrc: Option<&str>) -> Self {
+        let album = match metadata.album.trim() {
+            "" | "Unknown Album" => None,
+            album => Some(album.to_string()),
+        };
+        LyricsQuery {
+            isrc: metadata.isrc.as_deref().or(isrc).map(|i| i.to_string()),
+            artist: metadata.artist.clone(),
+            title: metadata.title.clone(),
+            album,
+            duration: metadata.duration,
+        }
+    }
+}
+
+/// A source of lyrics
+/// Ok(None) means the provider has no lyrics for the track; errors are logged and the next provider is tried
+pub trait LyricsProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn fetch<'a>(&'a self, query: &'a LyricsQuery) -> BoxFuture<'a, AppResult<Option<Lyrics>>>;
+}
+
+/// Lyrics files in a local folder, named "<ISRC>.lrc" or "<Artist> - <Title>.lrc" (or .txt for plain lyrics)
+pub struct LocalFileProvider {
+    dir: PathBuf,
+}
+
+impl LocalFileProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn find(&self, query: &LyricsQuery) -> AppResult<Option<Lyrics>> {
+        let mut names = Vec::new();
+        if let Some(isrc) = &query.isrc {
+            names.push(sanitize_filename(isrc));
+        }
+        names.push(sanitize_filename(&format!("{} - {}", query.artist, query.title)));
+
+        for name in names {
+            for extension in ["lrc", "txt"] {
+                let path = self.dir.join(format!("{}.{}", name, extension));
+                if !path.is_file() {
+                    continue;
+                }
+                let text = std::fs::read_to_string(&path)
+                    .or_error(ErrorKind::Io, format!("Failed to read {}", path.display()))?;
+                let lyrics = Lyrics::from_lrc(&text);
+                if !lyrics.is_empty() {
+                    return Ok(Some(lyrics));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl LyricsProvider for LocalFileProvider {
+    fn name(&self) -> &'static str {
+        "Local files"
+    }
+
+    fn fetch<'a>(&'a self, query: &'a LyricsQuery) -> BoxFuture<'a, AppResult<Option<Lyrics>>> {
+        Box::pin(async move { self.find(query) })
+    }
+}
+
+/// LRCLIB (lrclib.net) - free lyrics database with synced lyrics, searched by artist/title/album/duration
+/// LRCLIB can't search by ISRC, so an ISRC is first resolved to the recording's canonical
+/// artist/title/album through Deezer's public API; the tagged names are the fallback.
+pub struct LrclibProvider {
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibResponse {
+    #[serde(default)]
+    instrumental: bool,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+impl LrclibProvider {
+    pub fn new() -> AppResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .user_agent(concat!("HasodDownloads/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .or_error(ErrorKind::Internal, "Failed to create HTTP client")?;
+        Ok(Self { client })
+    }
+
+    async fn get(&self, query: &LyricsQuery) -> AppResult<Option<Lyrics>> {
+        let by_isrc = match &query.isrc {
+            Some(isrc) => match self.resolve_isrc(isrc).await {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    println!("[Lyrics] ISRC lookup failed for {}: {}", isrc, e);
+                    None
+                }
+            },
+            None => None,
+        };
+        if let Some(resolved) = &by_isrc {
+            if let Some(lyrics) = self.get_by_name(resolved).await? {
+                return Ok(Some(lyrics));
+            }
+            let same_names = resolved.artist.eq_ignore_ascii_case(&query.artist)
+                && resolved.title.eq_ignore_ascii_case(&query.title);
+            if same_names {
+                return Ok(None);
+            }
+        }
+        self.get_by_name(query).await
+    }
+
+    /// Canonical names of the recording with this ISRC, or None when Deezer doesn't know it
+    async fn resolve_isrc(&self, isrc: &str) -> AppResult<Option<LyricsQuery>> {
+        let response = self
+            .client
+            .get(format!("https://api.deezer.com/track/isrc:{}", isrc.trim()))
+            .send()
+            .await
+            .or_error(ErrorKind::Network, "ISRC lookup failed")?;
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(AppError::http(status, format!("Deezer returned status {}", status)));
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .or_error(ErrorKind::Parse, "Failed to parse ISRC lookup")?;
+        Ok(query_from_isrc_lookup(isrc, &body))
+    }
+
+    async fn get_by_name(&self, query: &LyricsQuery) -> AppResult<Option<Lyrics>> {
+        let mut params = vec![
+            ("artist_name", query.artist.clone()),
+            ("track_name", query.title.clone()),
+        ];
+        if let Some(album) = &query.album {
+            params.push(("album_name", album.clone()));
+        }
+        if let Some(duration) = query.duration {
+            params.push(("duration", duration.to_string()));
+        }
+
+        let response = self
+            .client
+            .get("https://lrclib.net/api/get")
+            .query(&params)
+            .send()
+            .await
+            .or_error(ErrorKind::Network, "Lyrics request failed")?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            return Err(AppError::http(status, format!("LRCLIB returned status {}", status)));
+        }
+
+        let body: LrclibResponse = response
+            .json()
+            .await
+            .or_error(ErrorKind::Parse, "Failed to parse LRCLIB response")?;
+        if body.instrumental {
+            return Ok(None);
+        }
+
+        let mut lyrics = body
+            .synced_lyrics
+            .as_deref()
+            .map(Lyrics::from_lrc)
+            .unwrap_or_default();
+        // LRCLIB's own plain text keeps blank lines between verses, which the synced version loses
+        if let Some(plain) = body.plain_lyrics.filter(|p| !p.trim().is_empty()) {
+            lyrics.plain = Some(plain);
+        }
+        Ok((!lyrics.is_empty()).then_some(lyrics))
+    }
+}
+
+/// Lyrics query from a Deezer track lookup; unknown ISRCs come back as `{"error": ...}`
+fn query_from_isrc_lookup(isrc: &str, track: &serde_json::Value) -> Option<LyricsQuery> {
+    let text = |pointer: &str| {
+        track
+            .pointer(pointer)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    Some(LyricsQuery {
+        isrc: Some(isrc.to_string()),
+        artist: text("/artist/name")?,
+        // title_short leaves out version suffixes like "(Remastered)"
+        title: text("/title_short").or_else(|| text("/title"))?,
+        album: text("/album/title"),
+        duration: track.get("duration").and_then(|v| v.as_u64()).map(|d| d as u32),
+    })
+}
+
+impl LyricsProvider for LrclibProvider {
+    fn name(&self) -> &'static str {
+        "LRCLIB"
+    }
+
+    fn fetch<'a>(&'a self, query: &'a LyricsQuery) -> BoxFuture<'a, AppResult<Option<Lyrics>>> {
+        Box::pin(self.get(query))
+    }
+}
+
+// ============================================================================
+// Lookup
+// ============================================================================
+
+pub struct LyricsFetcher {
+    providers: Vec<Box<dyn LyricsProvider>>,
+}
+
+impl LyricsFetcher {
+    pub fn new(providers: Vec<Box<dyn LyricsProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// Providers for the current settings: the local folder first, then LRCLIB
+    pub fn from_settings(settings: &LyricsSettings) -> Self {
+        let mut providers: Vec<Box<dyn LyricsProvider>> = Vec::new();
+        if let Some(dir) = settings.local_dir.as_deref().filter(|d| !d.trim().is_empty()) {
+            providers.push(Box::new(LocalFileProvider::new(dir)));
+        }
+        match LrclibProvider::new() {
+            Ok(provider) => providers.push(Box::new(provider)),
+            Err(e) => eprintln!("[Lyrics] LRCLIB unavailable: {}", e),
+        }
+        Self::new(providers)
+    }
+
+    /// First lyrics any provider has for the track
+    pub async fn fetch(&self, query: &LyricsQuery) -> Option<Lyrics> {
+        for provider in &self.providers {
+            match provider.fetch(query).await {
+                Ok(Some(lyrics)) => {
+                    println!("[Lyrics] ✅ Found lyrics via {}: {} - {}", provider.name(), query.artist, query.title);
+                    return Some(lyrics);
+                }
+                Ok(None) => {}
+                Err(e) => println!("[Lyrics] ⚠️ {} lookup failed: {}", provider.name(), e),
+            }
+        }
+        println!("[Lyrics] No lyrics found for: {} - {}", query.artist, query.title);
+        None
+    }
+}
+
+/// Path of the .lrc file that goes next to an audio file ("Artist - Title.mp3" -> "Artist - Title.lrc")
+pub fn lrc_path(audio_path: &Path) -> PathBuf {
+    let (stem, _) = crate::utils::filesystem::split_audio_extension(audio_path);
+    let mut path = stem.into_os_string();
+    path.push(".lrc");
+    PathBuf::from(path)
+}
+
+/// Write lyrics next to an audio file - synced lyrics when available, otherwise plain text
+pub fn write_lrc(audio_path: &Path, lyrics: &Lyrics) -> AppResult<PathBuf> {
+    let contents = lyrics
+        .to_lrc()
+        .or_else(|| lyrics.plain.clone())
+        .ok_or_else(|| AppError::new(ErrorKind::NotFound, "No lyrics to write"))?;
+    let path = lrc_path(audio_path);
+    crate::utils::write_atomic(&path, contents.as_bytes())?;
+    println!("[Lyrics] Wrote {}", path.display());
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lrc() {
+        let lines = parse_lrc("[ar:Artist]\n[00:12.5][01:02.34]Chorus\n[00:05.000]First line\nno timestamp");
+        assert_eq!(
+            lines,
+            vec![
+                LyricLine { time_ms: 5000, text: "First line".to_string() },
+                LyricLine { time_ms: 12500, text: "Chorus".to_string() },
+                LyricLine { time_ms: 62340, text: "Chorus".to_string() },
+            ]
+        );
+        assert_eq!(Lyrics::from_lrc("[00:05.00]First line").to_lrc().as_deref(), Some("[00:05.00]First line"));
+        assert_eq!(Lyrics::from_lrc("Just words").synced, None);
+    }
+
+    #[test]
+    fn test_parse_timestamp_overflow() {
+        assert_eq!(parse_timestamp("71582:47.295"), Some(u32::MAX));
+        assert_eq!(parse_timestamp("71582:47.296"), None);
+        assert_eq!(parse_timestamp("99999:00.00"), None);
+        assert_eq!(parse_timestamp("4294967295:00"), None);
+        assert_eq!(parse_lrc("[99999:00.00]Too late\n[00:01.00]Fine").len(), 1);
+    }
+
+    #[test]
+    fn test_local_file_provider() {
+        let dir = std::env::temp_dir().join(format!("hasod_lyrics_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("ILA000000001.lrc"), "[00:01.00]By ISRC").unwrap();
+        std::fs::write(dir.join("AC_DC - Title.txt"), "By name").unwrap();
+
+        let provider = LocalFileProvider::new(&dir);
+        let mut query = LyricsQuery {
+            isrc: Some("ILA000000001".to_string()),
+            artist: "AC/DC".to_string(),
+            title: "Title".to_string(),
+            ..LyricsQuery::default()
+        };
+        let lyrics = provider.find(&query).unwrap().unwrap();
+        assert_eq!(lyrics.plain.as_deref(), Some("By ISRC"));
+        assert!(lyrics.synced.is_some());
+
+        query.isrc = None;
+        let lyrics = provider.find(&query).unwrap().unwrap();
+        assert_eq!(lyrics.plain.as_deref(), Some("By name"));
+        assert_eq!(lyrics.synced, None);
+
+        query.title = "Other".to_string();
+        assert!(provider.find(&query).unwrap().is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_query_from_isrc_lookup() {
+        let track = serde_json::json!({
+            "title": "Song (Remastered)",
+            "title_short": "Song",
+            "duration": 215,
+            "artist": { "name": "Artist" },
+            "album": { "title": "Album" },
+        });
+        let query = query_from_isrc_lookup("ILA000000001", &track).unwrap();
+        assert_eq!(query.isrc.as_deref(), Some("ILA000000001"));
+        assert_eq!((query.artist.as_str(), query.title.as_str()), ("Artist", "Song"));
+        assert_eq!(query.album.as_deref(), Some("Album"));
+        assert_eq!(query.duration, Some(215));
+
+        let unknown = serde_json::json!({ "error": { "type": "DataException", "code": 800 } });
+        assert!(query_from_isrc_lookup("ILA000000002", &unknown).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+// Match scoring for YouTube fallback searches
+// A candidate video is compared with the track we're looking for: title and artist
+// similarity, duration, and keywords that indicate a different version of the song.
+
+/// Candidates scoring below this are never downloaded automatically
+pub const MIN_MATCH_CONFIDENCE: f32 = 0.6;
+
+/// Durations within this many seconds count as identical (intros/outros differ between uploads)
+const DURATION_TOLERANCE_SECS: u64 = 5;
+
+/// Beyond this difference the duration score is zero
+const DURATION_MAX_DIFF_SECS: u64 = 45;
+
+/// Words that mark a different version of a song, with the penalty applied when the
+/// candidate has them but the track we want doesn't
+const PENALTY_KEYWORDS: &[(&str, f32)] = &[
+    ("live", 0.35),
+    ("cover", 0.4),
+    ("remix", 0.35),
+    ("sped up", 0.45),
+    ("slowed", 0.45),
+    ("reverb", 0.3),
+    ("nightcore", 0.45),
+    ("karaoke", 0.5),
+    ("instrumental", 0.4),
+    ("acoustic", 0.25),
+    ("8d", 0.4),
+    ("loop", 0.4),
+    ("hour", 0.5),
+    ("hours", 0.5),
+    ("reaction", 0.5),
+    ("tutorial", 0.5),
+];
+
+/// The track a search is for
+#[derive(Debug, Clone, Default)]
+pub struct MatchTarget {
+    pub artist: String,
+    pub title: String,
+    pub duration_secs: Option<u64>,
+}
+
+/// How well a candidate matches, each part from 0.0 to 1.0
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchScore {
+    pub title: f32,
+    pub artist: f32,
+    pub duration: f32,
+    pub penalty: f32,
+    pub confidence: f32,
+}
+
+impl MatchScore {
+    pub fn is_confident(&self) -> bool {
+        self.confidence >= MIN_MATCH_CONFIDENCE
+    }
+}
+
+/// Score a candidate video against the target track
+pub fn score_match(
+    target: &MatchTarget,
+    candidate_title: &str,
+    candidate_uploader: &str,
+    candidate_duration_secs: Option<u64>,
+) -> MatchScore {
+    let candidate_words = words(candidate_title);
+    // Artist names often only appear in the channel name ("Artist - Topic", "ArtistVEVO")
+    let mut artist_haystack = candidate_words.clone();
+    artist_haystack.extend(words(candidate_uploader));
+    artist_haystack.extend(words(&candidate_uploader.replace("VEVO", "")));
+
+    let title = coverage(&words(&target.title), &candidate_words);
+    let artist = coverage(&words(&target.artist), &artist_haystack);
+    let duration = duration_score(target.duration_secs, candidate_duration_secs);
+    let penalty = keyword_penalty(&target.title, candidate_title);
+
+    // A 10-hour loop or a 30-second preview is never the track, however well the title matches
+    let confidence = if duration == 0.0 && target.duration_secs.is_some() && candidate_duration_secs.is_some() {
+        0.0
+    } else {
+        (0.45 * title + 0.25 * artist + 0.3 * duration - penalty).clamp(0.0, 1.0)
+    };
+
+    MatchScore {
+        title,
+        artist,
+        duration,
+        penalty,
+        confidence,
+    }
+}
+
+/// Lowercase words, with punctuation dropped ("Don't Stop (Remastered)" -> ["dont", "stop", "remastered"])
+/// Works for Hebrew and other scripts since it only strips non-alphanumeric characters
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '/' || c == '|')
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric()).collect::<String>())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Fraction of the wanted words found in the candidate
+/// Close spellings count too, so "Beyonce" matches "Beyoncé" and small typos don't sink a match
+fn coverage(wanted: &[String], candidate: &[String]) -> f32 {
+    if wanted.is_empty() {
+        return 1.0;
+    }
+    let found = wanted
+        .iter()
+        .filter(|word| candidate.iter().any(|c| similar_words(word, c)))
+        .count();
+    found as f32 / wanted.len() as f32
+}
+
+fn similar_words(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    // Short words must match exactly - "i" vs "a" are different words, not typos
+    let len = a.chars().count().max(b.chars().count());
+    len >= 5 && levenshtein(a, b) <= len / 5
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// 1.0 within the tolerance, falling linearly to 0.0 at DURATION_MAX_DIFF_SECS
+/// Unknown durations are neutral (0.5) since many search results lack them
+fn duration_score(target: Option<u64>, candidate: Option<u64>) -> f32 {
+    let (Some(target), Some(candidate)) = (target, candidate) else {
+        return 0.5;
+    };
+    let diff = target.abs_diff(candidate);
+    if diff <= DURATION_TOLERANCE_SECS {
+        1.0
+    } else if diff >= DURATION_MAX_DIFF_SECS {
+        0.0
+    } else {
+        1.0 - (diff - DURATION_TOLERANCE_SECS) as f32 / (DURATION_MAX_DIFF_SECS - DURATION_TOLERANCE_SECS) as f32
+    }
+}
+
+/// Total penalty for version keywords in the candidate that the target title doesn't have
+/// (a track that *is* a remix shouldn't be penalized for matching remixes)
+fn keyword_penalty(target_title: &str, candidate_title: &str) -> f32 {
+    let target = format!(" {} ", words(target_title).join(" "));
+    let candidate = format!(" {} ", words(candidate_title).join(" "));
+    PENALTY_KEYWORDS
+        .iter()
+        .filter(|(keyword, _)| {
+            let keyword = format!(" {} ", keyword);
+            candidate.contains(&keyword) && !target.contains(&keyword)
+        })
+        .map(|(_, penalty)| penalty)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> MatchTarget {
+        MatchTarget {
+            artist: "Queen".to_string(),
+            title: "Don't Stop Me Now".to_string(),
+            duration_secs: Some(209),
+        }
+    }
+
+    #[test]
+    fn test_score_match() {
+        let good = score_match(&target(), "Don't Stop Me Now (Remastered 2011)", "Queen - Topic", Some(211));
+        assert!(good.is_confident(), "{:?}", good);
+
+        let live = score_match(&target(), "Queen - Don't Stop Me Now (Live at Wembley)", "Queen", Some(230));
+        let cover = score_match(&target(), "Don't Stop Me Now - Cover", "Some Singer", Some(209));
+        let loop_video = score_match(&target(), "Queen - Don't Stop Me Now 10 hours", "Loops", Some(36000));
+        let other_song = score_match(&target(), "Bohemian Rhapsody", "Queen Official", Some(209));
+        for score in [&live, &cover, &loop_video, &other_song] {
+            assert!(!score.is_confident(), "{:?}", score);
+        }
+        assert!(good.confidence > live.confidence);
+        assert_eq!(loop_video.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_keyword_penalty() {
+        // The wanted track is itself a remix, so remixes aren't penalized
+        assert_eq!(keyword_penalty("Title (Remix)", "Artist - Title Remix"), 0.0);
+        assert!(keyword_penalty("Title", "Artist - Title (Sped Up)") > 0.0);
+        // Whole words only: "Olive" is not "live"
+        assert_eq!(keyword_penalty("Olive", "Artist - Olive"), 0.0);
+    }
+
+    #[test]
+    fn test_coverage() {
+        assert_eq!(coverage(&words("Beyoncé"), &words("Beyonce - Halo")), 1.0);
+        assert_eq!(coverage(&words("Omer Adam"), &words("עומר אדם")), 0.0);
+        assert_eq!(coverage(&words("עומר אדם"), &words("עומר אדם - שיר")), 1.0);
+    }
+}
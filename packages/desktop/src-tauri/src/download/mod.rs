@@ -5,18 +5,39 @@ pub mod services;
 pub mod queue;
 pub mod processor;
 pub mod transliteration;
+pub mod persistence;
+pub mod limits;
+pub mod control;
+pub mod retry;
+pub mod history;
+pub mod dedup;
+pub mod format;
+pub mod tagging;
+pub mod lyrics;
+pub mod matching;
+pub mod ytdlp;
+pub mod links;
+pub mod import;
 
 // Re-export common types
 pub use models::{
     MusicService,
     DownloadStatus,
     TrackMetadata,
+    SourceIds,
+    split_artists,
     DownloadJob,
     QueueStatus,
     DownloadContext,
+    DownloadSource,
     DownloadProgress,
+    SourceCandidate,
+    SourceTier,
 };
 
 // Re-export managers
 pub use queue::QueueManager;
 pub use processor::JobProcessor;
+pub use history::{DownloadHistory, HistoryEntry, HistoryFilter};
+pub use format::AudioFormat;
+pub use links::{LinkKind, ParsedLink};
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::error::ErrorKind;
+
 // ============================================================================
 // Music Service Detection
 // ============================================================================
@@ -20,25 +22,9 @@ pub enum MusicService {
 }
 
 impl MusicService {
+    /// Detect the service from a link using the registered music sources
     pub fn from_url(url: &str) -> Self {
-        let url_lower = url.to_lowercase();
-        if url_lower.contains("youtube.com") || url_lower.contains("youtu.be") || url_lower.contains("music.youtube.com") {
-            MusicService::YouTube
-        } else if url_lower.contains("spotify.com") || url_lower.starts_with("spotify:") {
-            MusicService::Spotify
-        } else if url_lower.contains("soundcloud.com") {
-            MusicService::SoundCloud
-        } else if url_lower.contains("deezer.com") {
-            MusicService::Deezer
-        } else if url_lower.contains("tidal.com") {
-            MusicService::Tidal
-        } else if url_lower.contains("music.apple.com") {
-            MusicService::AppleMusic
-        } else if url_lower.contains("bandcamp.com") {
-            MusicService::Bandcamp
-        } else {
-            MusicService::Unknown
-        }
+        crate::download::services::registry::service_for_url(url)
     }
 
     pub fn display_name(&self) -> &str {
@@ -79,15 +65,42 @@ pub enum DownloadStatus {
     Converting,
     Complete,
     Error,
+    Paused,     // Stopped by the user, can be resumed
+    Cancelled,  // Stopped by the user, partial files removed
+    Skipped,    // Duplicate of a queued or already downloaded track
+    NeedsReview,  // Automatic source matching was ambiguous, waiting for the user to pick one
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackMetadata {
     pub title: String,
-    pub artist: String,
+    pub artist: String,  // Display credit, e.g. "Artist A, Artist B"
     pub album: String,
     pub duration: Option<u32>,  // seconds
     pub thumbnail: Option<String>,
+    // Everything below is optional and defaults when loading older queue/history data
+    #[serde(default)]
+    pub artists: Vec<String>,  // Individual artists in credit order
+    #[serde(default)]
+    pub album_artist: Option<String>,
+    #[serde(default)]
+    pub track_number: Option<u32>,
+    #[serde(default)]
+    pub track_total: Option<u32>,
+    #[serde(default)]
+    pub disc_number: Option<u32>,
+    #[serde(default)]
+    pub disc_total: Option<u32>,
+    #[serde(default)]
+    pub release_date: Option<String>,  // "YYYY", "YYYY-MM" or "YYYY-MM-DD"
+    #[serde(default)]
+    pub isrc: Option<String>,
+    #[serde(default)]
+    pub genre: Option<String>,
+    #[serde(default)]
+    pub explicit: Option<bool>,
+    #[serde(default)]
+    pub source_ids: SourceIds,
 }
 
 impl Default for TrackMetadata {
@@ -98,6 +111,89 @@ impl Default for TrackMetadata {
             album: "Unknown Album".to_string(),
             duration: None,
             thumbnail: None,
+            artists: Vec::new(),
+            album_artist: None,
+            track_number: None,
+            track_total: None,
+            disc_number: None,
+            disc_total: None,
+            release_date: None,
+            isrc: None,
+            genre: None,
+            explicit: None,
+            source_ids: SourceIds::default(),
+        }
+    }
+}
+
+impl TrackMetadata {
+    /// Release year from release_date ("2019-05-17" -> 2019)
+    pub fn year(&self) -> Option<u32> {
+        let date = self.release_date.as_deref()?;
+        date.get(..4)?.parse().ok()
+    }
+
+    /// Fill optional fields this metadata lacks from another copy of the same track
+    /// e.g. the track number known when an album was queued, after a service re-fetched the track
+    pub fn fill_missing(&mut self, other: &TrackMetadata) {
+        fn fill<T: Clone>(field: &mut Option<T>, other: &Option<T>) {
+            if field.is_none() {
+                field.clone_from(other);
+            }
+        }
+
+        if self.artists.is_empty() {
+            self.artists.clone_from(&other.artists);
+        }
+        fill(&mut self.album_artist, &other.album_artist);
+        fill(&mut self.track_number, &other.track_number);
+        fill(&mut self.track_total, &other.track_total);
+        fill(&mut self.disc_number, &other.disc_number);
+        fill(&mut self.disc_total, &other.disc_total);
+        fill(&mut self.release_date, &other.release_date);
+        fill(&mut self.isrc, &other.isrc);
+        fill(&mut self.genre, &other.genre);
+        fill(&mut self.explicit, &other.explicit);
+        fill(&mut self.thumbnail, &other.thumbnail);
+        self.source_ids.fill_missing(&other.source_ids);
+    }
+}
+
+/// Split a comma-joined credit into individual artists ("A, B" -> ["A", "B"])
+/// "&" is left alone since it is usually part of a name ("Simon & Garfunkel")
+pub fn split_artists(credit: &str) -> Vec<String> {
+    credit
+        .split(',')
+        .map(|artist| artist.trim().to_string())
+        .filter(|artist| !artist.is_empty())
+        .collect()
+}
+
+/// IDs of the track on each service it is known from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceIds {
+    pub spotify: Option<String>,
+    pub deezer: Option<String>,
+    pub apple_music: Option<String>,
+    pub youtube: Option<String>,
+    pub soundcloud: Option<String>,
+    pub bandcamp: Option<String>,
+}
+
+impl SourceIds {
+    pub fn fill_missing(&mut self, other: &SourceIds) {
+        for (field, other) in [
+            (&mut self.spotify, &other.spotify),
+            (&mut self.deezer, &other.deezer),
+            (&mut self.apple_music, &other.apple_music),
+            (&mut self.youtube, &other.youtube),
+            (&mut self.soundcloud, &other.soundcloud),
+            (&mut self.bandcamp, &other.bandcamp),
+        ] {
+            if field.is_none() {
+                field.clone_from(other);
+            }
         }
     }
 }
@@ -114,6 +210,40 @@ pub enum DownloadContext {
     Playlist(String),    // Playlist download with playlist name
 }
 
+// ============================================================================
+// Download Source
+// ============================================================================
+
+/// Where the audio was actually downloaded from
+/// (may differ from the job's service, e.g. a Spotify link downloaded via Deezer or YouTube)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadSource {
+    pub service: MusicService,
+    pub url: Option<String>,  // e.g. the YouTube video chosen by find_best_source
+}
+
+/// A search result offered to the user when a job needs review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceCandidate {
+    pub url: String,
+    pub title: String,
+    pub uploader: String,
+    pub tier: SourceTier,
+    pub duration_secs: Option<u64>,
+    pub audio_bitrate: Option<u32>,
+    pub confidence: f32,
+}
+
+/// Quality tier for YouTube sources (higher = better)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceTier {
+    Regular = 0,       // Any result
+    OfficialAudio = 1, // "official audio" in title
+    Vevo = 2,          // VEVO channel
+    Topic = 3,         // Artist - Topic channel (Art Tracks, best quality)
+}
+
 // ============================================================================
 // Download Job
 // ============================================================================
@@ -132,8 +262,32 @@ pub struct DownloadJob {
     pub started_at: Option<i64>,
     pub completed_at: Option<i64>,
     pub error: Option<String>,
-    #[serde(skip)]  // Don't serialize to frontend
+    #[serde(default)]
+    pub error_code: Option<ErrorKind>,  // Machine-readable category of `error`
+    #[serde(default)]
+    pub isrc: Option<String>,
+    #[serde(default)]
+    pub source: Option<DownloadSource>,
+    #[serde(default)]
+    pub attempts: u32,  // Number of times a worker has started this job
+    #[serde(default)]
+    pub next_retry_at: Option<i64>,  // Earliest time (unix seconds) an automatic retry may start
+    #[serde(default)]  // Persisted with the queue so album/playlist routing survives restarts
     pub download_context: Option<DownloadContext>,
+    #[serde(default)]
+    pub output_dir: Option<String>,  // Download directory override (None = directory from settings)
+    #[serde(default)]
+    pub match_confidence: Option<f32>,  // 0.0-1.0 score of the YouTube video matched by search
+    #[serde(default)]
+    pub review_candidates: Vec<SourceCandidate>,  // Top search results while the job needs review
+    #[serde(default)]
+    pub manual_source: Option<String>,  // YouTube video picked by the user, used instead of searching
+    #[serde(default)]
+    pub download_speed: Option<f64>,  // Bytes per second while yt-dlp is downloading
+    #[serde(default)]
+    pub eta_secs: Option<u64>,  // Seconds left in the current download, as estimated by yt-dlp
+    #[serde(default)]
+    pub bypass_dedup: bool,  // Re-download asked for by the user: no duplicate checks, existing file is overwritten
 }
 
 impl DownloadJob {
@@ -152,40 +306,72 @@ impl DownloadJob {
                 title: initial_title,
                 artist: String::new(), // Empty instead of "Unknown Artist"
                 album: String::new(),  // Empty instead of "Unknown Album"
-                duration: None,
-                thumbnail: None,
+                ..TrackMetadata::default()
             },
             output_path: None,
             created_at: chrono::Utc::now().timestamp(),
             started_at: None,
             completed_at: None,
             error: None,
+            error_code: None,
+            isrc: None,
+            source: None,
+            attempts: 0,
+            next_retry_at: None,
             download_context: Some(DownloadContext::Single), // Default to single track
+            output_dir: None,
+            match_confidence: None,
+            review_candidates: Vec::new(),
+            manual_source: None,
+            download_speed: None,
+            eta_secs: None,
+            bypass_dedup: false,
+        }
+    }
+
+    /// Job for a track known only by artist/title (e.g. an imported playlist row)
+    /// YouTubeDownloader picks the video with find_best_source when the job runs
+    pub fn for_search(metadata: TrackMetadata) -> Self {
+        let url = crate::download::services::youtube::search_url(&metadata.artist, &metadata.title);
+        DownloadJob {
+            service: MusicService::YouTube,
+            metadata,
+            ..Self::new(url)
         }
     }
 
+    /// "1.2 MB/s · 0:42" while downloading, if yt-dlp reported speed or ETA
+    pub fn transfer_summary(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            self.download_speed.map(crate::download::ytdlp::format_speed),
+            self.eta_secs.map(crate::download::ytdlp::format_eta),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+
     /// Extract a readable title from URL for initial display
     fn extract_title_from_url(url: &str, service: &MusicService) -> String {
+        let track_id = || {
+            crate::download::links::parse_link(url)
+                .filter(|link| link.kind == crate::download::LinkKind::Track)
+                .map(|link| link.id)
+        };
         // Try to extract meaningful info from the URL
         match service {
             MusicService::YouTube => {
-                // YouTube: try to get video title from URL path
-                if let Some(v_param) = url.find("v=") {
-                    let video_id = &url[v_param + 2..].split('&').next().unwrap_or("");
-                    if !video_id.is_empty() {
-                        return format!("YouTube: {}", &video_id[..video_id.len().min(11)]);
-                    }
-                }
-                "YouTube video".to_string()
+                // YouTube: show the video ID until the title is known
+                track_id()
+                    .map(|video_id| format!("YouTube: {}", video_id))
+                    .unwrap_or_else(|| "YouTube video".to_string())
             }
             MusicService::Spotify => {
-                // Spotify: extract track name from URL if possible
-                if let Some(track_pos) = url.find("/track/") {
-                    let after_track = &url[track_pos + 7..];
-                    let track_id = after_track.split('?').next().unwrap_or(after_track);
-                    return format!("Spotify: {}", &track_id[..track_id.len().min(22)]);
-                }
-                "Spotify track".to_string()
+                // Spotify: show the track ID until the title is known
+                track_id()
+                    .map(|track_id| format!("Spotify: {}", track_id))
+                    .unwrap_or_else(|| "Spotify track".to_string())
             }
             MusicService::AppleMusic => {
                 // Apple Music: try to extract song name from URL path
@@ -239,7 +425,10 @@ pub struct QueueStatus {
     pub queued_count: usize,
     pub completed_count: usize,
     pub error_count: usize,
+    pub skipped_count: usize,
+    pub needs_review_count: usize,
     pub is_processing: bool,
+    pub max_concurrent: usize,  // Configured worker count
 }
 
 // ============================================================================
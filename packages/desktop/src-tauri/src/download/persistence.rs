@@ -0,0 +1,101 @@
+// Download queue persistence - keeps jobs across app restarts and crashes
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::download::{DownloadJob, DownloadStatus};
+use crate::error::AppResult;
+use crate::utils::{get_config_dir, write_atomic};
+
+/// Get the path to the persisted queue file (~/.hasod_downloads/queue.json)
+/// Tests write to a throwaway file so they never touch the user's queue
+fn get_queue_path() -> PathBuf {
+    if cfg!(test) {
+        return std::env::temp_dir().join(format!("hasod_test_queue_{}.json", std::process::id()));
+    }
+    let config_dir = get_config_dir();
+    fs::create_dir_all(&config_dir).ok();
+    config_dir.join("queue.json")
+}
+
+/// Save a snapshot of the queue to disk using a crash-safe write
+pub fn save_queue(jobs: &[DownloadJob]) -> AppResult<()> {
+    let json = serde_json::to_string_pretty(jobs)?;
+
+    write_atomic(&get_queue_path(), json.as_bytes())
+}
+
+/// Load the persisted queue from disk
+/// Jobs that were in flight when the app stopped are reset to Queued
+pub fn load_queue() -> Vec<DownloadJob> {
+    let path = get_queue_path();
+
+    if !path.exists() {
+        return Vec::new();
+    }
+
+    let json = match fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("[Queue] Failed to read persisted queue: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut jobs: Vec<DownloadJob> = match serde_json::from_str(&json) {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            eprintln!("[Queue] Failed to parse persisted queue, starting empty: {}", e);
+            return Vec::new();
+        }
+    };
+
+    reset_interrupted_jobs(&mut jobs);
+    jobs
+}
+
+/// Reset jobs that were Downloading/Converting back to Queued so they run again
+fn reset_interrupted_jobs(jobs: &mut [DownloadJob]) {
+    for job in jobs.iter_mut() {
+        if job.status == DownloadStatus::Downloading || job.status == DownloadStatus::Converting {
+            job.status = DownloadStatus::Queued;
+            job.progress = 0.0;
+            job.message = "Waiting in queue...".to_string();
+            job.started_at = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::DownloadContext;
+
+    #[test]
+    fn test_reset_interrupted_jobs() {
+        let mut downloading = DownloadJob::new("https://youtu.be/abc".to_string());
+        downloading.status = DownloadStatus::Downloading;
+        downloading.progress = 42.0;
+        let mut complete = DownloadJob::new("https://youtu.be/def".to_string());
+        complete.status = DownloadStatus::Complete;
+        complete.progress = 100.0;
+
+        let mut jobs = vec![downloading, complete];
+        reset_interrupted_jobs(&mut jobs);
+
+        assert_eq!(jobs[0].status, DownloadStatus::Queued);
+        assert_eq!(jobs[0].progress, 0.0);
+        assert_eq!(jobs[1].status, DownloadStatus::Complete);
+    }
+
+    #[test]
+    fn test_download_context_round_trip() {
+        let mut job = DownloadJob::new("https://open.spotify.com/track/abc".to_string());
+        job.download_context = Some(DownloadContext::Album("Shirim".to_string()));
+
+        let json = serde_json::to_string(&vec![job]).unwrap();
+        let restored: Vec<DownloadJob> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored[0].download_context, Some(DownloadContext::Album("Shirim".to_string())));
+    }
+}
@@ -1,22 +1,27 @@
 // Download job processor - orchestrates the download flow
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{AppHandle, Emitter};
 use tauri_plugin_shell::ShellExt;
 
 use crate::api_types::{HasodApiClient, SpotifyTrackMetadata};
 use crate::auth::get_auth_from_keychain;
 use crate::download::{
-    MusicService, DownloadStatus, TrackMetadata, DownloadContext,
-    QueueManager,
+    DownloadStatus, TrackMetadata, DownloadContext, DownloadSource,
+    QueueManager, DownloadHistory,
 };
 use crate::download::services::{
-    YouTubeDownloader, SpotifyDownloader, SoundCloudDownloader, DeezerDownloader,
-    AppleMusicDownloader, AppleMusicTrackInfo,
+    source_for_service, DownloadRequest, DeezerDownloader, AppleMusicTrackInfo,
 };
 use crate::download::queue::{DOWNLOAD_QUEUE};
+use crate::download::control::{JobControl, JobSignal};
+use crate::download::dedup;
+use crate::download::tagging::TagWriter;
+use crate::download::lyrics::{self, Lyrics, LyricsFetcher, LyricsQuery};
+use crate::error::{AppError, AppResult, ErrorKind};
 
 use crate::platform::FloatingPanelManager;
+use crate::utils::DuplicatePolicy;
 
 // ============================================================================
 // Job Processor
@@ -30,11 +35,16 @@ impl JobProcessor {
         app: &AppHandle,
         job_id: String,
         base_output_dir: String,
-    ) -> Result<String, String> {
+    ) -> AppResult<String> {
         // Get job details
         let (url, service, initial_title, download_context) =
             QueueManager::get_job_info(&job_id)?;
 
+        // Jobs queued with a directory override (e.g. an album sent to an external drive) go there
+        let base_output_dir = QueueManager::get_job(&job_id)?
+            .output_dir
+            .unwrap_or(base_output_dir);
+
         // Update job to downloading
         QueueManager::update_job_status(&job_id, DownloadStatus::Downloading, 0.0, "Starting download...");
         QueueManager::update_job_metadata(&job_id, |job| {
@@ -67,15 +77,23 @@ impl JobProcessor {
             // Update job status (this moves status)
             QueueManager::update_job_status(id, status, progress, message);
 
-            // Get current track title from queue
-            let title = DOWNLOAD_QUEUE
+            // Get current track title (and speed/ETA while downloading) from queue
+            let (title, transfer) = DOWNLOAD_QUEUE
                 .lock()
                 .ok()
-                .and_then(|q| q.iter().find(|j| j.id == id).map(|j| j.metadata.title.clone()))
-                .unwrap_or_else(|| message.to_string());
+                .and_then(|q| {
+                    q.iter()
+                        .find(|j| j.id == id)
+                        .map(|j| (j.metadata.title.clone(), j.transfer_summary()))
+                })
+                .unwrap_or_else(|| (message.to_string(), None));
 
             // Update floating panel with real-time progress
-            FloatingPanelManager::update_status(app, panel_state, progress, &title, get_queued_count());
+            if panel_state == "downloading" {
+                FloatingPanelManager::update_download(app, progress, &title, transfer.as_deref(), get_queued_count());
+            } else {
+                FloatingPanelManager::update_status(app, panel_state, progress, &title, get_queued_count());
+            }
         };
 
         let emit_queue_fn = || {
@@ -95,83 +113,124 @@ impl JobProcessor {
         // Update floating panel - works on all platforms now
         FloatingPanelManager::update_status(app, "fetching", 1.0, &initial_title, get_queued_count());
 
+        // With the skip policy, a track already in the download history isn't fetched again
+        // (unless the user asked to download it again)
+        let already_downloaded = if crate::utils::get_duplicate_policy() == DuplicatePolicy::Skip {
+            QueueManager::get_job(&job_id)
+                .ok()
+                .filter(|job| !job.bypass_dedup)
+                .and_then(|job| dedup::find_downloaded(&job))
+        } else {
+            None
+        };
+
         // Delegate to service-specific download methods
-        let result = match service {
-            MusicService::Spotify => {
-                SpotifyDownloader::download_track(
-                    app,
-                    &url,
-                    &base_output_dir,
-                    download_context.as_ref().unwrap_or(&DownloadContext::Single),
-                    &job_id,
-                    update_status_fn,
-                    emit_queue_fn,
-                    update_metadata_fn,
-                )
-                .await
-            }
-            MusicService::AppleMusic => {
-                AppleMusicDownloader::download_track(
-                    app,
-                    &url,
-                    &base_output_dir,
-                    download_context.as_ref().unwrap_or(&DownloadContext::Single),
-                    &job_id,
-                    update_status_fn,
-                    emit_queue_fn,
-                    update_metadata_fn,
-                )
-                .await
-            }
-            MusicService::YouTube => {
-                YouTubeDownloader::download_track(
-                    app,
-                    &url,
-                    &base_output_dir,
-                    download_context.as_ref().unwrap_or(&DownloadContext::Single),
-                    &job_id,
-                    update_status_fn,
-                    emit_queue_fn,
-                    update_metadata_fn,
-                )
-                .await
-            }
-            MusicService::SoundCloud => {
-                SoundCloudDownloader::download_track(
-                    app,
-                    &url,
-                    &base_output_dir,
-                    download_context.as_ref().unwrap_or(&DownloadContext::Single),
-                    &job_id,
-                    update_status_fn,
-                    emit_queue_fn,
-                    update_metadata_fn,
-                )
-                .await
+        let result = if let Some(existing) = already_downloaded {
+            Err(dedup::skip_as_duplicate(&job_id, &existing))
+        } else if let Err(stopped) = JobControl::check(&job_id) {
+            // Paused or cancelled between being claimed and starting
+            Err(stopped)
+        } else {
+            match source_for_service(&service) {
+                Some(source) => {
+                    source
+                        .download(DownloadRequest {
+                            app,
+                            url: &url,
+                            base_output_dir: &base_output_dir,
+                            download_context: download_context.as_ref().unwrap_or(&DownloadContext::Single),
+                            job_id: &job_id,
+                            update_status: &update_status_fn,
+                            emit_queue: &emit_queue_fn,
+                            update_metadata: &update_metadata_fn,
+                        })
+                        .await
+                }
+                None => Err(AppError::new(
+                    ErrorKind::Unsupported,
+                    format!("Unsupported service: {}", service.display_name()),
+                )),
             }
-            _ => Err(format!("Unsupported service: {}", service.display_name())),
+        };
+
+        // Tagging and lyrics still belong to the download - a pause or cancel stops them too
+        let result = match result {
+            Ok(output_path) => Self::finish_download(app, &job_id, output_path).await,
+            Err(e) => Err(e),
         };
 
         // Handle result
         match result {
-            Ok(output_path) => {
-                // Update job with output path and completion time
-                QueueManager::update_job_metadata(&job_id, |job| {
-                    job.output_path = Some(output_path.clone());
-                    job.completed_at = Some(chrono::Utc::now().timestamp());
-                })?;
+            Ok((output_path, message)) => {
+                JobControl::forget_partial_files(&job_id);
+                QueueManager::update_job_status(&job_id, DownloadStatus::Complete, 100.0, &message);
                 QueueManager::emit_update(app);
 
+                // Keep a permanent record - a history failure must not fail the download
+                if let Err(e) = QueueManager::get_job(&job_id).and_then(|job| DownloadHistory::record(&job)) {
+                    eprintln!("[History] Failed to record job {}: {}", job_id, e);
+                }
+
                 // Update floating panel - cross-platform
                 FloatingPanelManager::update_status(app, "complete", 100.0, "Done!", get_queued_count());
 
                 Ok(output_path)
             }
+            Err(e) if e.kind() == ErrorKind::Duplicate => {
+                // Already on disk and the duplicate policy says skip
+                QueueManager::update_job_status(&job_id, DownloadStatus::Skipped, 100.0, "Already downloaded");
+                QueueManager::emit_update(app);
+                FloatingPanelManager::update_status(app, "idle", 0.0, "Already downloaded", get_queued_count());
+
+                Err(e)
+            }
+            Err(e) if e.kind() == ErrorKind::NeedsReview => {
+                // Only this job waits - workers move on to the rest of the queue
+                QueueManager::update_job_status(&job_id, DownloadStatus::NeedsReview, 0.0, "Choose a source");
+                QueueManager::emit_update(app);
+                if let Ok(job) = QueueManager::get_job(&job_id) {
+                    app.emit("job-needs-review", &job).ok();
+                }
+                FloatingPanelManager::update_status(app, "idle", 0.0, "Needs review", get_queued_count());
+
+                Err(e)
+            }
+            Err(e) if JobControl::requested(&job_id) == JobSignal::Cancel => {
+                // Stopped by the user - remove whatever was written so far
+                JobControl::cleanup_partial_files(&job_id);
+                let _ = QueueManager::update_job_metadata(&job_id, |job| {
+                    // Cancelled while tagging - the finished file was removed too
+                    job.output_path = None;
+                    job.completed_at = None;
+                });
+                QueueManager::update_job_status(&job_id, DownloadStatus::Cancelled, 0.0, "Cancelled");
+                QueueManager::emit_update(app);
+                FloatingPanelManager::update_status(app, "idle", 0.0, "Cancelled", get_queued_count());
+
+                Err(e)
+            }
+            Err(e) if JobControl::requested(&job_id) == JobSignal::Pause => {
+                // Keep partial files so the download can continue on resume
+                QueueManager::update_job_status(&job_id, DownloadStatus::Paused, 0.0, "Paused");
+                QueueManager::emit_update(app);
+                FloatingPanelManager::update_status(app, "idle", 0.0, "Paused", get_queued_count());
+
+                Err(e)
+            }
             Err(e) => {
+                // Transient failures go back into the queue with a backoff delay
+                if let Some(delay) = QueueManager::schedule_retry(&job_id, &e)? {
+                    println!("[Download] Job {} failed with transient error, retrying in {:?}: {}", job_id, delay, e);
+                    QueueManager::emit_update(app);
+                    FloatingPanelManager::update_status(app, "processing", 0.0, "Retrying...", get_queued_count());
+                    return Err(e);
+                }
+
                 // Update job with error
-                QueueManager::update_job_status(&job_id, DownloadStatus::Error, 0.0, &e);
+                QueueManager::update_job_status(&job_id, DownloadStatus::Error, 0.0, &e.to_string());
                 QueueManager::update_job_metadata(&job_id, |job| {
-                    job.error = Some(e.clone());
+                    job.error = Some(e.to_string());
+                    job.error_code = Some(e.kind());
                 })?;
                 QueueManager::emit_update(app);
 
@@ -182,4 +241,85 @@ impl JobProcessor {
             }
         }
     }
+
+    /// Record the downloaded file on the job, then add lyrics and tags
+    /// Returns the output path and the completion message; stops with the stop error
+    /// if the job is paused or cancelled between steps
+    async fn finish_download(app: &AppHandle, job_id: &str, output_path: String) -> AppResult<(String, String)> {
+        // Until the job is Complete, cancelling it removes the downloaded file as well
+        JobControl::track_partial_file(job_id, &output_path);
+        JobControl::check(job_id)?;
+
+        // Update job with output path and completion time
+        QueueManager::update_job_metadata(job_id, |job| {
+            job.output_path = Some(output_path.clone());
+            job.completed_at = Some(chrono::Utc::now().timestamp());
+            // Services that download straight from the link don't report a separate source
+            if job.source.is_none() {
+                job.source = Some(DownloadSource {
+                    service: job.service.clone(),
+                    url: Some(job.url.clone()),
+                });
+            }
+        })?;
+
+        // The job only shows Complete once lyrics and tags are in the file
+        QueueManager::update_job_status(job_id, DownloadStatus::Converting, 95.0, "Adding tags and lyrics...");
+        QueueManager::emit_update(app);
+
+        // Lyrics are optional - no lyrics or a failed lookup never fails the download
+        let lyrics = Self::fetch_lyrics(app, job_id, &output_path).await;
+        JobControl::check(job_id)?;
+
+        // Write title/artist/album/ISRC/cover tags natively for every format
+        // A tagging failure leaves an untagged file but doesn't fail the download
+        let message = match Self::write_tags(job_id, &output_path, lyrics).await {
+            Ok(()) => "Download complete!".to_string(),
+            Err(e) => {
+                eprintln!("[Tags] Failed to tag job {}: {}", job_id, e);
+                format!("Download complete (tags not written: {})", e)
+            }
+        };
+        JobControl::check(job_id)?;
+
+        Ok((output_path, message))
+    }
+
+    /// Tag the downloaded file from the job's metadata
+    async fn write_tags(job_id: &str, output_path: &str, lyrics: Option<Lyrics>) -> AppResult<()> {
+        let job = QueueManager::get_job(job_id)?;
+        TagWriter::write_for_job(&job, Path::new(output_path), lyrics).await
+    }
+
+    /// Look up lyrics when enabled in settings and write the .lrc file if configured
+    /// Returns the lyrics to embed in the file's tags
+    async fn fetch_lyrics(app: &AppHandle, job_id: &str, output_path: &str) -> Option<Lyrics> {
+        let settings = crate::utils::get_lyrics_settings();
+        if !settings.enabled {
+            return None;
+        }
+        let job = QueueManager::get_job(job_id).ok()?;
+
+        let previous_message = job.message.clone();
+        let _ = QueueManager::update_job_metadata(job_id, |job| {
+            job.message = "Fetching lyrics...".to_string();
+        });
+        QueueManager::emit_update(app);
+
+        let query = LyricsQuery::from_metadata(&job.metadata, job.isrc.as_deref());
+        let found = LyricsFetcher::from_settings(&settings).fetch(&query).await;
+
+        if let (Some(found), true) = (&found, settings.write_lrc) {
+            if let Err(e) = lyrics::write_lrc(Path::new(output_path), found) {
+                eprintln!("[Lyrics] Failed to write .lrc for job {}: {}", job_id, e);
+            }
+        }
+
+        let _ = QueueManager::update_job_metadata(job_id, |job| {
+            job.message = previous_message;
+        });
+        QueueManager::emit_update(app);
+
+        found.filter(|_| settings.embed)
+    }
 }
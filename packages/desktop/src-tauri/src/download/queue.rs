@@ -1,9 +1,16 @@
 // Download queue management
 
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 
-use crate::download::{DownloadJob, QueueStatus, DownloadStatus};
+use crate::download::{DownloadJob, DownloadSource, DownloadStatus, MusicService, QueueStatus, SourceCandidate};
+use crate::download::persistence;
+use crate::download::dedup;
+use crate::download::control::{JobControl, JobSignal};
+use crate::download::retry;
+use crate::error::{AppError, AppResult, ErrorKind};
 
 // Global download queue (will be migrated to managed state later)
 pub(crate) static DOWNLOAD_QUEUE: std::sync::LazyLock<Arc<Mutex<Vec<DownloadJob>>>> =
@@ -13,6 +20,13 @@ pub(crate) static DOWNLOAD_QUEUE: std::sync::LazyLock<Arc<Mutex<Vec<DownloadJob>
 pub(crate) static QUEUE_PROCESSING: std::sync::LazyLock<Arc<Mutex<bool>>> =
     std::sync::LazyLock::new(|| Arc::new(Mutex::new(false)));
 
+// IDs of jobs currently claimed by a worker
+pub(crate) static ACTIVE_JOBS: std::sync::LazyLock<Mutex<HashSet<String>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashSet::new()));
+
+// Serializes queue writes to disk (always acquired before DOWNLOAD_QUEUE, never while holding it)
+static QUEUE_PERSIST_LOCK: Mutex<()> = Mutex::new(());
+
 // ============================================================================
 // Queue Manager
 // ============================================================================
@@ -21,30 +35,74 @@ pub struct QueueManager;
 
 impl QueueManager {
     /// Add a job to the queue
-    pub fn add_job(job: DownloadJob) -> Result<DownloadJob, String> {
-        let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        queue.push(job.clone());
-        Ok(job)
+    /// A duplicate of a job already in the queue is added as Skipped
+    pub fn add_job(job: DownloadJob) -> AppResult<DownloadJob> {
+        let mut jobs = Self::add_jobs(vec![job])?;
+        jobs.pop().ok_or_else(|| AppError::internal("Job was not added"))
     }
 
     /// Add multiple jobs to the queue
-    pub fn add_jobs(jobs: Vec<DownloadJob>) -> Result<Vec<DownloadJob>, String> {
-        let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        for job in &jobs {
-            queue.push(job.clone());
+    /// Duplicates (of queued jobs or of each other) are added as Skipped so the caller can report them
+    pub fn add_jobs(mut jobs: Vec<DownloadJob>) -> AppResult<Vec<DownloadJob>> {
+        {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let skipped = dedup::skip_queue_duplicates(&queue, &mut jobs);
+            if skipped > 0 {
+                println!("[Queue] Skipped {} duplicate jobs", skipped);
+            }
+            queue.extend(jobs.iter().cloned());
         }
+        Self::persist();
         Ok(jobs)
     }
 
+    /// Write the current queue to disk
+    /// Must be called without holding the DOWNLOAD_QUEUE lock
+    pub fn persist() {
+        // Serialize writers so an older snapshot never overwrites a newer one
+        let _guard = QUEUE_PERSIST_LOCK.lock();
+
+        let snapshot = match DOWNLOAD_QUEUE.lock() {
+            Ok(queue) => queue.clone(),
+            Err(e) => {
+                eprintln!("[Queue] Lock error while persisting: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = persistence::save_queue(&snapshot) {
+            eprintln!("[Queue] Failed to persist queue: {}", e);
+        }
+    }
+
+    /// Load the persisted queue from disk into memory (called once at startup)
+    /// Returns the number of restored jobs
+    pub fn restore() -> AppResult<usize> {
+        let restored = persistence::load_queue();
+        let count = restored.len();
+
+        {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            queue.extend(restored);
+        }
+
+        // Write back so reset statuses are reflected on disk immediately
+        Self::persist();
+        Ok(count)
+    }
+
     /// Get current queue status
-    pub fn get_status() -> Result<QueueStatus, String> {
-        let queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        let is_processing = *QUEUE_PROCESSING.lock().map_err(|e| format!("Lock error: {}", e))?;
+    pub fn get_status() -> AppResult<QueueStatus> {
+        let queue = DOWNLOAD_QUEUE.lock()?;
+        let is_processing = *QUEUE_PROCESSING.lock()?;
 
-        let active_count = queue.iter().filter(|j| j.status == DownloadStatus::Downloading || j.status == DownloadStatus::Converting).count();
+        // Count jobs actually held by workers rather than inferring from status
+        let active_count = ACTIVE_JOBS.lock()?.len();
         let queued_count = queue.iter().filter(|j| j.status == DownloadStatus::Queued).count();
         let completed_count = queue.iter().filter(|j| j.status == DownloadStatus::Complete).count();
         let error_count = queue.iter().filter(|j| j.status == DownloadStatus::Error).count();
+        let skipped_count = queue.iter().filter(|j| j.status == DownloadStatus::Skipped).count();
+        let needs_review_count = queue.iter().filter(|j| j.status == DownloadStatus::NeedsReview).count();
 
         Ok(QueueStatus {
             jobs: queue.clone(),
@@ -52,60 +110,189 @@ impl QueueManager {
             queued_count,
             completed_count,
             error_count,
+            skipped_count,
+            needs_review_count,
             is_processing,
+            max_concurrent: crate::utils::get_max_concurrent_downloads(),
         })
     }
 
     /// Update job status in queue
     pub fn update_job_status(job_id: &str, status: DownloadStatus, progress: f32, message: &str) {
+        let mut status_changed = false;
         if let Ok(mut queue) = DOWNLOAD_QUEUE.lock() {
             if let Some(job) = queue.iter_mut().find(|j| j.id == job_id) {
+                status_changed = job.status != status;
                 job.status = status;
                 job.progress = progress;
                 job.message = message.to_string();
+                // Speed and ETA only mean something while bytes are arriving
+                if job.status != DownloadStatus::Downloading {
+                    job.download_speed = None;
+                    job.eta_secs = None;
+                }
+            }
+        }
+
+        // Only persist on status transitions, not on every progress tick
+        if status_changed {
+            Self::persist();
+        }
+    }
+
+    /// Record a job's current download speed and ETA (not persisted - it changes every tick)
+    pub fn set_job_transfer(job_id: &str, speed: Option<f64>, eta_secs: Option<u64>) {
+        if let Ok(mut queue) = DOWNLOAD_QUEUE.lock() {
+            if let Some(job) = queue.iter_mut().find(|j| j.id == job_id) {
+                job.download_speed = speed;
+                job.eta_secs = eta_secs;
             }
         }
     }
 
     /// Update job metadata
-    pub fn update_job_metadata(job_id: &str, update_fn: impl FnOnce(&mut DownloadJob)) -> Result<(), String> {
-        let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        if let Some(job) = queue.iter_mut().find(|j| j.id == job_id) {
-            update_fn(job);
+    pub fn update_job_metadata(job_id: &str, update_fn: impl FnOnce(&mut DownloadJob)) -> AppResult<()> {
+        {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            if let Some(job) = queue.iter_mut().find(|j| j.id == job_id) {
+                update_fn(job);
+            }
+        }
+        Self::persist();
+        Ok(())
+    }
+
+    /// Get a snapshot of a job
+    pub fn get_job(job_id: &str) -> AppResult<DownloadJob> {
+        let queue = DOWNLOAD_QUEUE.lock()?;
+        queue
+            .iter()
+            .find(|j| j.id == job_id)
+            .cloned()
+            .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Job not found"))
+    }
+
+    /// Record where a job's audio is being downloaded from
+    pub fn set_job_source(job_id: &str, service: MusicService, url: Option<String>) {
+        let _ = Self::update_job_metadata(job_id, |job| {
+            job.source = Some(DownloadSource { service, url });
+        });
+    }
+
+    /// Record the YouTube video matched by search and the confidence of the match
+    pub fn set_job_match(job_id: &str, url: &str, confidence: f32) {
+        let _ = Self::update_job_metadata(job_id, |job| {
+            job.source = Some(DownloadSource {
+                service: MusicService::YouTube,
+                url: Some(url.to_string()),
+            });
+            job.match_confidence = Some(confidence);
+        });
+    }
+
+    /// Store the search results the user can choose from when a job needs review
+    pub fn set_job_review(job_id: &str, candidates: Vec<SourceCandidate>) {
+        let _ = Self::update_job_metadata(job_id, |job| {
+            job.review_candidates = candidates;
+        });
+    }
+
+    /// Use the video the user picked for a job waiting for review and queue it again
+    /// The URL must be one of the offered candidates or another YouTube link
+    pub fn resolve_job_source(job_id: &str, candidate_url: &str) -> AppResult<()> {
+        let candidate_url = candidate_url.trim();
+        {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let job = queue
+                .iter_mut()
+                .find(|j| j.id == job_id)
+                .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Job not found"))?;
+            if job.status != DownloadStatus::NeedsReview {
+                return Err(AppError::new(
+                    ErrorKind::InvalidInput,
+                    format!("Job does not need review (status: {:?})", job.status),
+                ));
+            }
+            let offered = job.review_candidates.iter().any(|c| c.url == candidate_url);
+            if !offered && MusicService::from_url(candidate_url) != MusicService::YouTube {
+                return Err(AppError::new(
+                    ErrorKind::InvalidInput,
+                    format!("Not a YouTube link: {}", candidate_url),
+                ));
+            }
+
+            job.manual_source = Some(candidate_url.to_string());
+            job.review_candidates.clear();
+            job.status = DownloadStatus::Queued;
+            job.progress = 0.0;
+            job.message = "Waiting in queue...".to_string();
+            job.error = None;
+            job.error_code = None;
         }
+        Self::persist();
         Ok(())
     }
 
     /// Get job details (returns cloned data to avoid holding lock)
-    pub fn get_job_info(job_id: &str) -> Result<(String, crate::download::MusicService, String, Option<crate::download::DownloadContext>), String> {
-        let queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        let job = queue.iter().find(|j| j.id == job_id).ok_or("Job not found")?;
+    pub fn get_job_info(job_id: &str) -> AppResult<(String, crate::download::MusicService, String, Option<crate::download::DownloadContext>)> {
+        let queue = DOWNLOAD_QUEUE.lock()?;
+        let job = queue
+            .iter()
+            .find(|j| j.id == job_id)
+            .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Job not found"))?;
         Ok((job.url.clone(), job.service.clone(), job.metadata.title.clone(), job.download_context.clone()))
     }
 
-    /// Clear completed and error jobs from queue
-    pub fn clear_completed() -> Result<usize, String> {
-        let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        let initial_len = queue.len();
-        queue.retain(|j| j.status != DownloadStatus::Complete && j.status != DownloadStatus::Error);
-        let removed = initial_len - queue.len();
+    /// Clear completed, error and cancelled jobs from queue
+    pub fn clear_completed() -> AppResult<usize> {
+        let removed = {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let initial_len = queue.len();
+            queue.retain(|j| {
+                j.status != DownloadStatus::Complete
+                    && j.status != DownloadStatus::Error
+                    && j.status != DownloadStatus::Cancelled
+                    && j.status != DownloadStatus::Skipped
+            });
+            initial_len - queue.len()
+        };
+        Self::persist();
         Ok(removed)
     }
 
     /// Clear all jobs from queue (including queued, not just completed)
-    pub fn clear_all() -> Result<usize, String> {
-        let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        let removed = queue.len();
-        queue.clear();
+    pub fn clear_all() -> AppResult<usize> {
+        // Stop any in-flight downloads first
+        let active: Vec<String> = ACTIVE_JOBS
+            .lock()
+            .map(|a| a.iter().cloned().collect())
+            .unwrap_or_default();
+        for job_id in &active {
+            JobControl::signal(job_id, JobSignal::Cancel);
+        }
+
+        let removed = {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let removed = queue.len();
+            queue.clear();
+            removed
+        };
+        Self::persist();
         Ok(removed)
     }
 
     /// Remove a specific job from queue
-    pub fn remove_job(job_id: &str) -> Result<bool, String> {
-        let mut queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        let initial_len = queue.len();
-        queue.retain(|j| j.id != job_id);
-        let removed = initial_len > queue.len();
+    pub fn remove_job(job_id: &str) -> AppResult<bool> {
+        // Stop the download if a worker is processing it
+        JobControl::signal(job_id, JobSignal::Cancel);
+
+        let removed = {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let initial_len = queue.len();
+            queue.retain(|j| j.id != job_id);
+            initial_len > queue.len()
+        };
+        Self::persist();
         Ok(removed)
     }
 
@@ -114,23 +301,222 @@ impl QueueManager {
         DOWNLOAD_QUEUE.lock().map(|q| q.iter().filter(|j| j.status == DownloadStatus::Queued).count()).unwrap_or(0)
     }
 
-    /// Get next queued job ID
-    pub fn get_next_queued_job() -> Result<Option<String>, String> {
-        let queue = DOWNLOAD_QUEUE.lock().map_err(|e| format!("Lock error: {}", e))?;
-        Ok(queue.iter()
-            .find(|j| j.status == DownloadStatus::Queued)
-            .map(|j| j.id.clone()))
+    /// Claim the next queued job for a worker
+    /// The job is marked Downloading while the queue lock is held, so no two workers can claim it
+    /// Jobs waiting for a scheduled retry are skipped until their retry time
+    pub fn claim_next_job() -> AppResult<Option<String>> {
+        let now = chrono::Utc::now().timestamp();
+        let claimed = {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let next = queue.iter_mut().find(|j| {
+                j.status == DownloadStatus::Queued && !matches!(j.next_retry_at, Some(at) if at > now)
+            });
+            match next {
+                Some(job) => {
+                    job.status = DownloadStatus::Downloading;
+                    job.progress = 0.0;
+                    job.message = "Starting download...".to_string();
+                    job.attempts += 1;
+                    job.next_retry_at = None;
+                    ACTIVE_JOBS
+                        .lock()
+                        ?
+                        .insert(job.id.clone());
+                    JobControl::register(&job.id);
+                    Some(job.id.clone())
+                }
+                None => None,
+            }
+        };
+
+        if claimed.is_some() {
+            Self::persist();
+        }
+        Ok(claimed)
+    }
+
+    /// Time until the earliest scheduled retry, if any queued job is waiting for one
+    pub fn next_retry_delay() -> Option<Duration> {
+        let now = chrono::Utc::now().timestamp();
+        let queue = DOWNLOAD_QUEUE.lock().ok()?;
+        queue
+            .iter()
+            .filter(|j| j.status == DownloadStatus::Queued)
+            .filter_map(|j| j.next_retry_at)
+            .min()
+            .map(|at| Duration::from_secs((at - now).max(0) as u64))
+    }
+
+    /// Schedule an automatic retry for a failed job if the error is transient
+    /// and the job has attempts left. Returns the retry delay if one was scheduled.
+    pub fn schedule_retry(job_id: &str, error: &AppError) -> AppResult<Option<Duration>> {
+        let delay = {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let job = match queue.iter_mut().find(|j| j.id == job_id) {
+                Some(job) => job,
+                None => return Ok(None),
+            };
+            if !retry::should_retry(error, job.attempts) {
+                return Ok(None);
+            }
+
+            let delay = retry::backoff_delay(job.attempts);
+            job.status = DownloadStatus::Queued;
+            job.progress = 0.0;
+            job.message = format!(
+                "Retrying in {}s (attempt {} of {})...",
+                delay.as_secs(),
+                job.attempts + 1,
+                retry::MAX_ATTEMPTS
+            );
+            job.error = Some(error.to_string());
+            job.error_code = Some(error.kind());
+            job.next_retry_at = Some(chrono::Utc::now().timestamp() + delay.as_secs() as i64);
+            delay
+        };
+
+        Self::persist();
+        Ok(Some(delay))
+    }
+
+    /// Requeue every job that ended in Error, resetting its attempt counter
+    pub fn retry_failed() -> AppResult<usize> {
+        let count = {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let mut count = 0;
+            for job in queue.iter_mut().filter(|j| j.status == DownloadStatus::Error) {
+                job.status = DownloadStatus::Queued;
+                job.progress = 0.0;
+                job.message = "Waiting in queue...".to_string();
+                job.error = None;
+                job.error_code = None;
+                job.attempts = 0;
+                job.next_retry_at = None;
+                count += 1;
+            }
+            count
+        };
+
+        Self::persist();
+        Ok(count)
+    }
+
+    /// Release a job claimed by a worker
+    fn release_job(job_id: &str) {
+        JobControl::unregister(job_id);
+        if let Ok(mut active) = ACTIVE_JOBS.lock() {
+            active.remove(job_id);
+        }
+    }
+
+    /// Cancel a job: queued/paused/review jobs are cancelled immediately,
+    /// in-flight jobs are signalled and marked Cancelled by their worker
+    pub fn cancel_job(job_id: &str) -> AppResult<()> {
+        let in_flight = {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let job = queue
+                .iter_mut()
+                .find(|j| j.id == job_id)
+                .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Job not found"))?;
+            match job.status {
+                DownloadStatus::Queued | DownloadStatus::Paused | DownloadStatus::NeedsReview => {
+                    job.status = DownloadStatus::Cancelled;
+                    job.review_candidates.clear();
+                    job.progress = 0.0;
+                    job.message = "Cancelled".to_string();
+                    false
+                }
+                DownloadStatus::Downloading | DownloadStatus::Converting => {
+                    job.message = "Cancelling...".to_string();
+                    true
+                }
+                _ => {
+                    return Err(AppError::new(
+                        ErrorKind::InvalidInput,
+                        format!("Job cannot be cancelled while {:?}", job.status),
+                    ))
+                }
+            }
+        };
+
+        if in_flight {
+            JobControl::signal(job_id, JobSignal::Cancel);
+        } else {
+            // A paused job may have left partial files behind
+            JobControl::cleanup_partial_files(job_id);
+        }
+        Self::persist();
+        Ok(())
+    }
+
+    /// Pause a job: queued jobs are paused immediately,
+    /// in-flight jobs are signalled and marked Paused by their worker
+    pub fn pause_job(job_id: &str) -> AppResult<()> {
+        let in_flight = {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let job = queue
+                .iter_mut()
+                .find(|j| j.id == job_id)
+                .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Job not found"))?;
+            match job.status {
+                DownloadStatus::Queued => {
+                    job.status = DownloadStatus::Paused;
+                    job.message = "Paused".to_string();
+                    false
+                }
+                DownloadStatus::Downloading | DownloadStatus::Converting => {
+                    job.message = "Pausing...".to_string();
+                    true
+                }
+                _ => {
+                    return Err(AppError::new(
+                        ErrorKind::InvalidInput,
+                        format!("Job cannot be paused while {:?}", job.status),
+                    ))
+                }
+            }
+        };
+
+        if in_flight {
+            JobControl::signal(job_id, JobSignal::Pause);
+        }
+        Self::persist();
+        Ok(())
+    }
+
+    /// Resume a paused job by putting it back in the queue
+    /// yt-dlp picks up its .part files, so the download continues where it stopped
+    pub fn resume_job(job_id: &str) -> AppResult<()> {
+        {
+            let mut queue = DOWNLOAD_QUEUE.lock()?;
+            let job = queue
+                .iter_mut()
+                .find(|j| j.id == job_id)
+                .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Job not found"))?;
+            if job.status != DownloadStatus::Paused {
+                return Err(AppError::new(
+                    ErrorKind::InvalidInput,
+                    format!("Job cannot be resumed while {:?}", job.status),
+                ));
+            }
+            job.status = DownloadStatus::Queued;
+            job.progress = 0.0;
+            job.message = "Waiting in queue...".to_string();
+            job.next_retry_at = None;
+        }
+        Self::persist();
+        Ok(())
     }
 
     /// Check if queue is currently processing
-    pub fn is_processing() -> Result<bool, String> {
-        let processing = QUEUE_PROCESSING.lock().map_err(|e| format!("Lock error: {}", e))?;
+    pub fn is_processing() -> AppResult<bool> {
+        let processing = QUEUE_PROCESSING.lock()?;
         Ok(*processing)
     }
 
     /// Set processing flag
-    pub fn set_processing(value: bool) -> Result<(), String> {
-        let mut processing = QUEUE_PROCESSING.lock().map_err(|e| format!("Lock error: {}", e))?;
+    pub fn set_processing(value: bool) -> AppResult<()> {
+        let mut processing = QUEUE_PROCESSING.lock()?;
         *processing = value;
         Ok(())
     }
@@ -141,13 +527,11 @@ impl QueueManager {
     }
 
     /// Start processing the download queue
-    /// Processes all queued jobs sequentially
-    pub async fn start_processing(app: AppHandle) -> Result<(), String> {
-        use crate::download::JobProcessor;
-
+    /// Runs up to `max_concurrent_downloads` jobs at once until no queued jobs remain
+    pub async fn start_processing(app: AppHandle) -> AppResult<()> {
         // Check if already processing
         {
-            let mut processing = QUEUE_PROCESSING.lock().map_err(|e| format!("Lock error: {}", e))?;
+            let mut processing = QUEUE_PROCESSING.lock()?;
             if *processing {
                 println!("[Queue] Already processing");
                 return Ok(());
@@ -158,33 +542,214 @@ impl QueueManager {
         let base_output_dir = crate::utils::get_download_dir();
         std::fs::create_dir_all(&base_output_dir).ok();
 
-        println!("[Queue] Starting queue processing");
+        let worker_count = crate::utils::get_max_concurrent_downloads();
+        println!("[Queue] Starting queue processing with {} workers", worker_count);
         Self::emit_update(&app);
 
-        // Process queue
+        // Workers share the queue and claim jobs one at a time until it runs dry
+        let workers = (0..worker_count).map(|worker_id| Self::run_worker(&app, worker_id, &base_output_dir));
+        futures_util::future::join_all(workers).await;
+
+        // Mark processing as complete
+        Self::set_processing(false)?;
+        Self::emit_update(&app);
+        println!("[Queue] Queue processing complete");
+
+        Ok(())
+    }
+
+    /// Worker loop: claim and process jobs until none are queued
+    async fn run_worker(app: &AppHandle, worker_id: usize, base_output_dir: &str) {
+        use crate::download::JobProcessor;
+
         loop {
-            let next_job_id = Self::get_next_queued_job()?;
-
-            match next_job_id {
-                Some(job_id) => {
-                    println!("[Queue] Processing job: {}", job_id);
-                    match JobProcessor::process_job(&app, job_id.clone(), base_output_dir.clone()).await {
-                        Ok(_) => println!("[Queue] Job {} completed successfully", job_id),
-                        Err(e) => println!("[Queue] Job {} failed: {}", job_id, e),
+            let job_id = match Self::claim_next_job() {
+                Ok(Some(job_id)) => job_id,
+                Ok(None) => match Self::next_retry_delay() {
+                    Some(delay) => {
+                        // Wake up periodically so newly added jobs aren't stuck behind a long backoff
+                        tokio::time::sleep(delay.clamp(Duration::from_millis(500), Duration::from_secs(5))).await;
+                        continue;
                     }
-                }
-                None => {
-                    println!("[Queue] No more jobs to process");
+                    None => {
+                        println!("[Queue] Worker {}: no more jobs to process", worker_id);
+                        break;
+                    }
+                },
+                Err(e) => {
+                    eprintln!("[Queue] Worker {}: failed to claim job: {}", worker_id, e);
                     break;
                 }
+            };
+
+            println!("[Queue] Worker {}: processing job {}", worker_id, job_id);
+            Self::emit_update(app);
+
+            match JobProcessor::process_job(app, job_id.clone(), base_output_dir.to_string()).await {
+                Ok(_) => println!("[Queue] Job {} completed successfully", job_id),
+                Err(e) => println!("[Queue] Job {} failed: {}", job_id, e),
             }
+
+            Self::release_job(&job_id);
+            Self::emit_update(app);
         }
+    }
+}
 
-        // Mark processing as complete
-        Self::set_processing(false)?;
-        Self::emit_update(&app);
-        println!("[Queue] Queue processing complete");
+// The queue is global - tests that use it run one at a time on a queue holding only their jobs
+#[cfg(test)]
+static QUEUE_TEST_LOCK: Mutex<()> = Mutex::new(());
 
-        Ok(())
+#[cfg(test)]
+pub(crate) fn with_queue(jobs: Vec<DownloadJob>, test: impl FnOnce()) {
+    let _guard = QUEUE_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    *DOWNLOAD_QUEUE.lock().unwrap_or_else(|e| e.into_inner()) = jobs;
+    test();
+    DOWNLOAD_QUEUE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::SourceTier;
+
+    fn job(url: &str, status: DownloadStatus) -> DownloadJob {
+        let mut job = DownloadJob::new(url.to_string());
+        job.status = status;
+        job
+    }
+
+    fn candidate(url: &str) -> SourceCandidate {
+        SourceCandidate {
+            url: url.to_string(),
+            title: "Artist - Title".to_string(),
+            uploader: "Artist".to_string(),
+            tier: SourceTier::Regular,
+            duration_secs: Some(200),
+            audio_bitrate: None,
+            confidence: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_resolve_job_source_picks_candidate() {
+        let mut review = job("https://open.spotify.com/track/abc", DownloadStatus::NeedsReview);
+        review.review_candidates = vec![candidate("https://www.youtube.com/watch?v=aaa")];
+        let id = review.id.clone();
+
+        with_queue(vec![review], || {
+            QueueManager::resolve_job_source(&id, " https://www.youtube.com/watch?v=aaa ").unwrap();
+            let job = QueueManager::get_job(&id).unwrap();
+            assert_eq!(job.status, DownloadStatus::Queued);
+            assert_eq!(job.manual_source.as_deref(), Some("https://www.youtube.com/watch?v=aaa"));
+            assert!(job.review_candidates.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_resolve_job_source_errors() {
+        let queued = job("https://open.spotify.com/track/abc", DownloadStatus::Queued);
+        let review = job("https://open.spotify.com/track/def", DownloadStatus::NeedsReview);
+        let (queued_id, review_id) = (queued.id.clone(), review.id.clone());
+
+        with_queue(vec![queued, review], || {
+            let url = "https://www.youtube.com/watch?v=aaa";
+            let error = QueueManager::resolve_job_source(&queued_id, url).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput);
+            let error = QueueManager::resolve_job_source("no-such-job", url).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::NotFound);
+            // Not offered and not a YouTube link
+            let error = QueueManager::resolve_job_source(&review_id, "https://example.com/video").unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput);
+            assert_eq!(QueueManager::get_job(&review_id).unwrap().status, DownloadStatus::NeedsReview);
+        });
+    }
+
+    #[test]
+    fn test_cancel_job() {
+        let queued = job("https://youtu.be/aaa", DownloadStatus::Queued);
+        let downloading = job("https://youtu.be/bbb", DownloadStatus::Downloading);
+        let complete = job("https://youtu.be/ccc", DownloadStatus::Complete);
+        let (queued_id, downloading_id, complete_id) = (queued.id.clone(), downloading.id.clone(), complete.id.clone());
+
+        with_queue(vec![queued, downloading, complete], || {
+            JobControl::register(&downloading_id);
+
+            // Not started yet - cancelled right away
+            QueueManager::cancel_job(&queued_id).unwrap();
+            assert_eq!(QueueManager::get_job(&queued_id).unwrap().status, DownloadStatus::Cancelled);
+
+            // In flight - the worker is signalled and marks it Cancelled when it stops
+            QueueManager::cancel_job(&downloading_id).unwrap();
+            assert_eq!(QueueManager::get_job(&downloading_id).unwrap().status, DownloadStatus::Downloading);
+            assert_eq!(JobControl::requested(&downloading_id), JobSignal::Cancel);
+            assert!(JobControl::check(&downloading_id).is_err());
+
+            let error = QueueManager::cancel_job(&complete_id).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+            JobControl::unregister(&downloading_id);
+        });
+    }
+
+    #[test]
+    fn test_pause_and_resume_job() {
+        let queued = job("https://youtu.be/aaa", DownloadStatus::Queued);
+        let downloading = job("https://youtu.be/bbb", DownloadStatus::Downloading);
+        let (queued_id, downloading_id) = (queued.id.clone(), downloading.id.clone());
+
+        with_queue(vec![queued, downloading], || {
+            JobControl::register(&downloading_id);
+
+            QueueManager::pause_job(&queued_id).unwrap();
+            assert_eq!(QueueManager::get_job(&queued_id).unwrap().status, DownloadStatus::Paused);
+            // Paused jobs aren't claimed
+            assert_eq!(QueueManager::claim_next_job().unwrap(), None);
+
+            QueueManager::resume_job(&queued_id).unwrap();
+            assert_eq!(QueueManager::get_job(&queued_id).unwrap().status, DownloadStatus::Queued);
+            let error = QueueManager::resume_job(&queued_id).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+            QueueManager::pause_job(&downloading_id).unwrap();
+            assert_eq!(JobControl::requested(&downloading_id), JobSignal::Pause);
+
+            JobControl::unregister(&downloading_id);
+        });
+    }
+
+    #[test]
+    fn test_claim_next_job() {
+        let complete = job("https://youtu.be/aaa", DownloadStatus::Complete);
+        let mut waiting = job("https://youtu.be/bbb", DownloadStatus::Queued);
+        waiting.next_retry_at = Some(chrono::Utc::now().timestamp() + 3600);
+        let first = job("https://youtu.be/ccc", DownloadStatus::Queued);
+        let second = job("https://youtu.be/ddd", DownloadStatus::Queued);
+        let (waiting_id, first_id, second_id) = (waiting.id.clone(), first.id.clone(), second.id.clone());
+
+        with_queue(vec![complete, waiting, first, second], || {
+            // Queue order, skipping finished jobs and jobs waiting for their retry time
+            assert_eq!(QueueManager::claim_next_job().unwrap().as_deref(), Some(first_id.as_str()));
+            let claimed = QueueManager::get_job(&first_id).unwrap();
+            assert_eq!(claimed.status, DownloadStatus::Downloading);
+            assert_eq!(claimed.attempts, 1);
+            assert!(ACTIVE_JOBS.lock().unwrap().contains(&first_id));
+
+            assert_eq!(QueueManager::claim_next_job().unwrap().as_deref(), Some(second_id.as_str()));
+            assert_eq!(QueueManager::claim_next_job().unwrap(), None);
+
+            // Due retries are claimed again, with the retry time cleared
+            QueueManager::update_job_metadata(&waiting_id, |job| {
+                job.next_retry_at = Some(chrono::Utc::now().timestamp() - 1);
+            })
+            .unwrap();
+            assert_eq!(QueueManager::claim_next_job().unwrap().as_deref(), Some(waiting_id.as_str()));
+            assert_eq!(QueueManager::get_job(&waiting_id).unwrap().next_retry_at, None);
+
+            for id in [&first_id, &second_id, &waiting_id] {
+                ACTIVE_JOBS.lock().unwrap().remove(id);
+                JobControl::unregister(id);
+            }
+        });
     }
 }
@@ -0,0 +1,52 @@
+// Automatic retry policy for failed downloads
+
+use rand::Rng;
+use std::time::Duration;
+
+use crate::error::AppError;
+
+/// Maximum number of attempts per job (first try + retries)
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled on every further attempt
+const BASE_DELAY_SECS: u64 = 5;
+
+/// Upper bound for the retry delay
+const MAX_DELAY_SECS: u64 = 300;
+
+/// Whether a failed job should be retried automatically
+/// Only transient errors (network, HTTP 5xx, rate limiting) are worth another attempt
+pub fn should_retry(error: &AppError, attempts: u32) -> bool {
+    error.kind().is_transient() && attempts < MAX_ATTEMPTS
+}
+
+/// Delay before the given retry attempt (1-based), with up to 20% jitter
+pub fn backoff_delay(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    let secs = (BASE_DELAY_SECS << exponent).min(MAX_DELAY_SECS);
+    let jitter_ms = rand::thread_rng().gen_range(0..=secs * 200);
+    Duration::from_millis(secs * 1000 + jitter_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_retry() {
+        use crate::error::ErrorKind;
+
+        assert!(should_retry(&AppError::new(ErrorKind::ServerError, "status 503"), 1));
+        assert!(should_retry(&AppError::new(ErrorKind::RateLimited, "Too Many Requests"), 2));
+        assert!(!should_retry(&AppError::new(ErrorKind::Network, "timed out"), MAX_ATTEMPTS));
+        assert!(!should_retry(&AppError::new(ErrorKind::NotFound, "Song not found"), 1));
+        assert!(!should_retry(&AppError::new(ErrorKind::LicenseRequired, "No license"), 1));
+    }
+
+    #[test]
+    fn test_backoff_delay_grows_and_caps() {
+        assert!(backoff_delay(1) >= Duration::from_secs(5));
+        assert!(backoff_delay(2) >= Duration::from_secs(10));
+        assert!(backoff_delay(30) <= Duration::from_secs(MAX_DELAY_SECS * 6 / 5));
+    }
+}
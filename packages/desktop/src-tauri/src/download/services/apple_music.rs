@@ -1,6 +1,14 @@
 // Apple Music download service (via iTunes API + YouTube fallback)
 
+use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::download::links::{parse_link, LinkKind};
+use crate::download::services::registry::{host_matches, Collection, DownloadRequest, MusicSource};
+use crate::download::ytdlp::YtDlpRunner;
+use crate::download::{DownloadContext, DownloadJob, MusicService, SourceIds, TrackMetadata};
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
 
 // ============================================================================
 // Types
@@ -13,6 +21,41 @@ pub struct AppleMusicTrackInfo {
     pub artist: String,
     pub album: String,
     pub artwork_url: Option<String>,
+    pub track_id: Option<String>,
+    pub album_artist: Option<String>,
+    pub track_number: Option<u32>,
+    pub track_count: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub disc_count: Option<u32>,
+    pub release_date: Option<String>,
+    pub genre: Option<String>,
+    pub explicit: Option<bool>,
+    pub duration: Option<u32>,  // seconds
+}
+
+impl AppleMusicTrackInfo {
+    pub fn to_metadata(&self) -> TrackMetadata {
+        TrackMetadata {
+            title: self.title.clone(),
+            artist: self.artist.clone(),
+            album: self.album.clone(),
+            duration: self.duration,
+            thumbnail: self.artwork_url.clone(),
+            album_artist: self.album_artist.clone(),
+            track_number: self.track_number,
+            track_total: self.track_count,
+            disc_number: self.disc_number,
+            disc_total: self.disc_count,
+            release_date: self.release_date.clone(),
+            genre: self.genre.clone(),
+            explicit: self.explicit,
+            source_ids: SourceIds {
+                apple_music: self.track_id.clone(),
+                ..SourceIds::default()
+            },
+            ..TrackMetadata::default()
+        }
+    }
 }
 
 // ============================================================================
@@ -27,51 +70,65 @@ impl AppleMusicDownloader {
     /// - https://music.apple.com/us/album/song-name/1234567890?i=1234567891
     /// - https://music.apple.com/us/song/song-name/1234567891
     pub fn extract_track_id(url: &str) -> Option<String> {
-        // Check for ?i= parameter (song within album)
-        if let Some(pos) = url.find("?i=") {
-            let id_start = pos + 3;
-            let id_end = url[id_start..].find('&').map(|p| id_start + p).unwrap_or(url.len());
-            let id = &url[id_start..id_end];
-            if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
-                return Some(id.to_string());
-            }
-        }
-
-        // Check for /song/ URL format
-        if url.contains("/song/") {
-            let parts: Vec<&str> = url.split('/').collect();
-            if let Some(last) = parts.last() {
-                // Remove query string if present
-                let id = last.split('?').next().unwrap_or(last);
-                if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
-                    return Some(id.to_string());
-                }
-            }
-        }
+        Self::extract_id(url, LinkKind::Track)
+    }
 
-        None
+    fn extract_id(url: &str, kind: LinkKind) -> Option<String> {
+        parse_link(url)?.id_of(MusicService::AppleMusic, kind).map(|id| id.to_string())
     }
 
     /// Get Apple Music track info using iTunes Lookup API (no authentication required)
     /// Returns (search_query, artist, track_info)
-    pub async fn get_track_info(url: &str) -> Result<(String, String, Option<AppleMusicTrackInfo>), String> {
+    pub async fn get_track_info(url: &str) -> AppResult<(String, String, Option<AppleMusicTrackInfo>)> {
         // Validate URL type
-        let url_lower = url.to_lowercase();
-        if url_lower.contains("/artist/") && !url_lower.contains("?i=") {
-            return Err("Artist pages cannot be downloaded. Please use a specific song URL.".to_string());
+        let kind = parse_link(url).map(|link| link.kind);
+        if kind == Some(LinkKind::Artist) {
+            return Err(AppError::new(
+                ErrorKind::Unsupported,
+                "Artist pages cannot be downloaded. Please use a specific song URL.",
+            ));
         }
-        if url_lower.contains("/playlist/") {
-            return Err("Playlist pages are not yet supported. Please use individual song URLs.".to_string());
+        if kind == Some(LinkKind::Playlist) {
+            return Err(AppError::new(
+                ErrorKind::Unsupported,
+                "Playlist pages must be added as a playlist, not as a single song.",
+            ));
         }
 
         // Extract track ID
         let track_id = Self::extract_track_id(url)
-            .ok_or_else(|| "Could not extract track ID from Apple Music URL. Please use a direct song link.".to_string())?;
+            .ok_or_else(|| AppError::new(
+                ErrorKind::InvalidInput,
+                "Could not extract track ID from Apple Music URL. Please use a direct song link.",
+            ))?;
 
         println!("[AppleMusic] Extracted track ID: {}", track_id);
 
         // Use iTunes Lookup API (no authentication required!)
-        let lookup_url = format!("https://itunes.apple.com/lookup?id={}&entity=song", track_id);
+        let results = Self::itunes_lookup(&[track_id], Self::storefront(url).as_deref()).await?;
+
+        // First result is usually the track
+        let track = results
+            .iter()
+            .find(|result| result.get("wrapperType").and_then(|v| v.as_str()) == Some("track"))
+            .or(results.first())
+            .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Song not found in iTunes database"))?;
+
+        let info = Self::parse_itunes_track(track);
+        println!("[AppleMusic] Found: '{}' by '{}' from '{}'", info.title, info.artist, info.album);
+
+        let search_query = format!("{} - {}", info.artist, info.title);
+        let artist = info.artist.clone();
+        Ok((search_query, artist, Some(info)))
+    }
+
+    /// Look up songs (or an album with its songs) by ID with the iTunes Lookup API
+    /// `country` is the storefront from the link ("us", "il"); releases missing from the US store need it
+    async fn itunes_lookup(ids: &[String], country: Option<&str>) -> AppResult<Vec<serde_json::Value>> {
+        let mut lookup_url = format!("https://itunes.apple.com/lookup?id={}&entity=song&limit=200", ids.join(","));
+        if let Some(country) = country {
+            lookup_url.push_str(&format!("&country={}", country));
+        }
 
         let client = reqwest::Client::new();
         let response = client
@@ -79,81 +136,227 @@ impl AppleMusicDownloader {
             .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7)")
             .send()
             .await
-            .map_err(|e| format!("iTunes API request failed: {}", e))?;
+            .or_error(ErrorKind::Network, "iTunes API request failed")?;
 
         if !response.status().is_success() {
-            return Err(format!("iTunes API error: {}", response.status()));
+            return Err(AppError::http(response.status(), format!("iTunes API error: {}", response.status())));
         }
 
         let json: serde_json::Value = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse iTunes response: {}", e))?;
+            .or_error(ErrorKind::Parse, "Failed to parse iTunes response")?;
 
         // iTunes API returns { resultCount: N, results: [...] }
         let results = json.get("results")
             .and_then(|v| v.as_array())
-            .ok_or("No results in iTunes response")?;
+            .ok_or_else(|| AppError::new(ErrorKind::Parse, "No results in iTunes response"))?;
 
-        if results.is_empty() {
-            return Err("Song not found in iTunes database".to_string());
+        // Empty when none of the IDs are in the storefront - callers decide whether that's an error
+        Ok(results.clone())
+    }
+
+    /// Track info from one iTunes Lookup result
+    fn parse_itunes_track(track: &serde_json::Value) -> AppleMusicTrackInfo {
+        let text = |key: &str| track.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let number = |key: &str| track.get(key).and_then(|v| v.as_u64()).map(|n| n as u32);
+
+        AppleMusicTrackInfo {
+            title: text("trackName").unwrap_or_else(|| "Unknown".to_string()),
+            artist: text("artistName").unwrap_or_else(|| "Unknown Artist".to_string()),
+            album: text("collectionName").unwrap_or_else(|| "Unknown Album".to_string()),
+            // Get artwork URL (replace size for higher quality)
+            artwork_url: text("artworkUrl100").map(|url| url.replace("100x100", "600x600")),
+            track_id: track.get("trackId").and_then(|v| v.as_u64()).map(|id| id.to_string()),
+            album_artist: text("collectionArtistName"),
+            track_number: number("trackNumber"),
+            track_count: number("trackCount"),
+            disc_number: number("discNumber"),
+            disc_count: number("discCount"),
+            // "2019-05-17T07:00:00Z" -> "2019-05-17"
+            release_date: text("releaseDate").map(|date| date.chars().take(10).collect()),
+            genre: text("primaryGenreName"),
+            explicit: text("trackExplicitness").map(|e| e == "explicit"),
+            duration: number("trackTimeMillis").map(|ms| ms / 1000),
         }
+    }
 
-        // First result is usually the track
-        let track = &results[0];
+    /// Storefront country code from a link ("https://music.apple.com/il/album/..." -> "il")
+    fn storefront(url: &str) -> Option<String> {
+        let parsed = url::Url::parse(url).ok()?;
+        let country = parsed.path_segments()?.next()?;
+        (country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic())).then(|| country.to_lowercase())
+    }
 
-        let title = track.get("trackName")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown")
-            .to_string();
+    /// Extract the album ID from an album link
+    /// - https://music.apple.com/us/album/album-name/1234567890
+    /// - https://music.apple.com/us/album/1234567890
+    pub fn extract_album_id(url: &str) -> Option<String> {
+        Self::extract_id(url, LinkKind::Album)
+    }
 
-        let artist = track.get("artistName")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown Artist")
-            .to_string();
+    /// Song IDs linked from a playlist page, in page order
+    /// Apple Music lists songs as "music:song" meta tags and /song/ links
+    fn extract_song_ids(html: &str) -> Vec<String> {
+        let mut found: Vec<(usize, String)> = Vec::new();
+        for marker in ["/song/", "?i="] {
+            for (pos, _) in html.match_indices(marker) {
+                let rest = &html[pos + marker.len()..];
+                // "/song/name/123" - skip the slug; "?i=123" - the ID follows directly
+                let rest = if marker == "/song/" {
+                    match rest.find('/') {
+                        Some(slash) if !rest[..slash].contains(['"', '<', ' ']) => &rest[slash + 1..],
+                        _ => continue,
+                    }
+                } else {
+                    rest
+                };
+                let id: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+                if !id.is_empty() {
+                    found.push((pos, id));
+                }
+            }
+        }
 
-        let album = track.get("collectionName")
+        found.sort_by_key(|(pos, _)| *pos);
+        let mut ids: Vec<String> = Vec::new();
+        for (_, id) in found {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    /// Content of a <meta property="..." content="..."> tag
+    fn meta_content(html: &str, property: &str) -> Option<String> {
+        let tag_start = html.find(&format!("property=\"{}\"", property))?;
+        let tag_start = html[..tag_start].rfind('<')?;
+        let tag = &html[tag_start..tag_start + html[tag_start..].find('>')?];
+        let content = tag.split("content=\"").nth(1)?.split('"').next()?;
+        Some(content.replace("&amp;", "&").replace("&quot;", "\"").replace("&#39;", "'"))
+    }
+
+    /// One job per track, in album order
+    fn jobs_for_tracks(tracks: &[&serde_json::Value], context: &DownloadContext) -> Vec<DownloadJob> {
+        tracks
+            .iter()
+            .filter_map(|track| {
+                let info = Self::parse_itunes_track(track);
+                let track_url = track.get("trackViewUrl").and_then(|v| v.as_str())?;
+                let mut job = DownloadJob::new(track_url.to_string());
+                job.metadata = info.to_metadata();
+                job.download_context = Some(context.clone());
+                Some(job)
+            })
+            .collect()
+    }
+
+    /// Expand an album into one job per track (album context, with full track metadata)
+    pub async fn expand_album(album_url: &str) -> AppResult<Collection> {
+        println!("[AppleMusic] Processing album: {}", album_url);
+
+        let album_id = Self::extract_album_id(album_url)
+            .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, "Could not extract album ID from Apple Music URL"))?;
+        let results = Self::itunes_lookup(&[album_id], Self::storefront(album_url).as_deref()).await?;
+
+        let album = results
+            .iter()
+            .find(|result| result.get("wrapperType").and_then(|v| v.as_str()) == Some("collection"));
+        let album_name = album
+            .and_then(|album| album.get("collectionName"))
             .and_then(|v| v.as_str())
             .unwrap_or("Unknown Album")
             .to_string();
 
-        // Get artwork URL (replace size for higher quality)
-        let artwork_url = track.get("artworkUrl100")
-            .and_then(|v| v.as_str())
-            .map(|url| url.replace("100x100", "600x600"));
+        let mut tracks: Vec<&serde_json::Value> = results
+            .iter()
+            .filter(|result| result.get("wrapperType").and_then(|v| v.as_str()) == Some("track"))
+            .collect();
+        let position = |track: &serde_json::Value, key: &str| track.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        tracks.sort_by_key(|track| (position(track, "discNumber"), position(track, "trackNumber")));
 
-        println!("[AppleMusic] Found: '{}' by '{}' from '{}'", title, artist, album);
+        if tracks.is_empty() {
+            return Err(AppError::new(ErrorKind::NotFound, "No tracks found on Apple Music album"));
+        }
+        println!("[AppleMusic] Album: '{}' ({} tracks)", album_name, tracks.len());
 
-        let search_query = format!("{} - {}", artist, title);
-        let info = AppleMusicTrackInfo {
-            title,
-            artist: artist.clone(),
-            album,
-            artwork_url,
-        };
+        let jobs = Self::jobs_for_tracks(&tracks, &DownloadContext::Album(album_name.clone()));
+        Ok(Collection {
+            name: album_name,
+            jobs,
+        })
+    }
 
-        Ok((search_query, artist, Some(info)))
+    /// Expand a public playlist into one job per track (playlist context)
+    /// The iTunes API has no playlist lookup, so song IDs are read from the playlist page
+    pub async fn expand_playlist(playlist_url: &str) -> AppResult<Collection> {
+        println!("[AppleMusic] Processing playlist: {}", playlist_url);
+
+        let client = reqwest::Client::new();
+        let response = client
+            .get(playlist_url)
+            .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7)")
+            .send()
+            .await
+            .or_error(ErrorKind::Network, "Failed to load Apple Music playlist")?;
+
+        if !response.status().is_success() {
+            return Err(AppError::http(response.status(), format!("Apple Music error: {}", response.status())));
+        }
+        let html = response
+            .text()
+            .await
+            .or_error(ErrorKind::Network, "Failed to read Apple Music playlist")?;
+
+        let playlist_name = Self::meta_content(&html, "og:title")
+            .map(|title| title.trim_end_matches(" on Apple Music").to_string())
+            .unwrap_or_else(|| "Apple Music Playlist".to_string());
+        let song_ids = Self::extract_song_ids(&html);
+        if song_ids.is_empty() {
+            return Err(AppError::new(
+                ErrorKind::NotFound,
+                "Could not read the songs of this playlist. Only public playlists are supported.",
+            ));
+        }
+
+        let country = Self::storefront(playlist_url);
+        let mut results = Vec::new();
+        // Keep lookup URLs a reasonable length; IDs missing from the store are skipped below
+        for chunk in song_ids.chunks(100) {
+            results.extend(Self::itunes_lookup(chunk, country.as_deref()).await?);
+        }
+
+        // Lookup results come back in any order - restore the playlist order
+        let track_id = |track: &serde_json::Value| track.get("trackId").and_then(|v| v.as_u64()).map(|id| id.to_string());
+        let tracks: Vec<&serde_json::Value> = song_ids
+            .iter()
+            .filter_map(|id| results.iter().find(|track| track_id(track).as_ref() == Some(id)))
+            .collect();
+        if tracks.is_empty() {
+            return Err(AppError::new(ErrorKind::NotFound, "None of the playlist's songs are in the iTunes database"));
+        }
+
+        println!("[AppleMusic] Playlist: '{}' ({} of {} tracks resolved)", playlist_name, tracks.len(), song_ids.len());
+
+        let jobs = Self::jobs_for_tracks(&tracks, &DownloadContext::Playlist(playlist_name.clone()));
+        Ok(Collection {
+            name: playlist_name,
+            jobs,
+        })
     }
 
     /// Download an Apple Music track (via YouTube search)
     /// Returns the path to the downloaded file
-    pub async fn download_track(
-        app: &tauri::AppHandle,
-        url: &str,
-        base_output_dir: &str,
-        download_context: &crate::download::DownloadContext,
-        job_id: &str,
-        update_status_fn: impl Fn(&str, crate::download::DownloadStatus, f32, &str),
-        emit_queue_fn: impl Fn(),
-        update_metadata_fn: impl Fn(crate::download::TrackMetadata),
-    ) -> Result<String, String> {
+    pub async fn download_track(request: DownloadRequest<'_>) -> AppResult<String> {
         use crate::download::services::YouTubeDownloader;
-        use crate::download::{DownloadStatus, TrackMetadata};
-        use tauri_plugin_shell::ShellExt;
+        use crate::download::DownloadStatus;
+
+        let DownloadRequest { app, url, base_output_dir, download_context, job_id, update_status, emit_queue, update_metadata } = request;
 
         // Step 1: Get track info from iTunes API
-        update_status_fn(job_id, DownloadStatus::Downloading, 2.0, "Fetching Apple Music track info...");
-        emit_queue_fn();
+        update_status(job_id, DownloadStatus::Downloading, 2.0, "Fetching Apple Music track info...");
+        emit_queue();
 
         let (_search_query_base, _artist, apple_info) = Self::get_track_info(url).await?;
 
@@ -169,52 +372,53 @@ impl AppleMusicDownloader {
         };
 
         // Step 2: Update job metadata
-        let track_metadata = if let Some(ref info) = apple_info {
-            TrackMetadata {
-                title: info.title.clone(),
-                artist: info.artist.clone(),
-                album: info.album.clone(),
-                duration: None,
-                thumbnail: info.artwork_url.clone(),
-            }
+        let mut track_metadata = if let Some(ref info) = apple_info {
+            info.to_metadata()
         } else {
             TrackMetadata {
                 title: title.clone(),
                 artist: artist.clone(),
                 album: String::new(),
-                duration: None,
-                thumbnail: None,
+                ..TrackMetadata::default()
             }
         };
 
+        // Merge details known at queue time - skipped if the job was removed meanwhile
+        if let Ok(job) = crate::download::QueueManager::get_job(job_id) {
+            track_metadata.fill_missing(&job.metadata);
+        }
+
         // Transliterate if English Only mode is enabled (BEFORE calculating path)
         let track_metadata = crate::download::transliteration::transliterate_if_needed(&track_metadata)
             .await
             .unwrap_or(track_metadata);
 
-        update_metadata_fn(track_metadata.clone());
+        update_metadata(track_metadata.clone());
 
         // Step 3: Find best YouTube source
         println!("[AppleMusic] Finding best YouTube source for: {} - {}", artist, title);
-        update_status_fn(
+        update_status(
             job_id,
             DownloadStatus::Downloading,
             3.0,
             &format!("Finding best quality: {} - {}", artist, title),
         );
-        emit_queue_fn();
+        emit_queue();
 
-        let youtube_url = YouTubeDownloader::find_best_source(
+        let youtube_match = YouTubeDownloader::find_best_source(
             app,
             &artist,
             &title,
+            track_metadata.duration.map(u64::from),
             job_id,
-            &update_status_fn,
-            &emit_queue_fn,
+            &update_status,
+            &emit_queue,
         )
         .await?;
+        let youtube_url = youtube_match.url;
 
-        println!("[AppleMusic] Best source found: {}", youtube_url);
+        println!("[AppleMusic] Best source found: {} ({:.2})", youtube_url, youtube_match.confidence);
+        crate::download::QueueManager::set_job_match(job_id, &youtube_url, youtube_match.confidence);
 
         // Step 4: Calculate output path
         let output_path = crate::utils::filesystem::get_organized_output_path(
@@ -222,91 +426,115 @@ impl AppleMusicDownloader {
             &track_metadata,
             download_context,
         );
+        // Apply the duplicate policy if this track is already on disk
+        let output_path = crate::download::dedup::resolve_output_path(job_id, output_path)?;
         let output_dir = output_path.parent().unwrap().to_string_lossy().to_string();
 
         std::fs::create_dir_all(&output_dir)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+            .or_error(ErrorKind::Io, "Failed to create directory")?;
 
-        let output_template = format!("{}/%(title)s.%(ext)s", output_dir);
+        let output_template = crate::utils::filesystem::ytdlp_output_template(&output_path);
 
         // Step 5: Download from YouTube using yt-dlp
-        let sidecar = app.shell().sidecar("yt-dlp")
-            .map_err(|e| format!("Failed to get yt-dlp sidecar: {}", e))?;
-
-        let args: Vec<&str> = vec![
-            &youtube_url,
-            "-f", "bestaudio",
-            "--extract-audio",
-            "--audio-format", "mp3",
-            "--audio-quality", "0",
+        let audio_format = crate::utils::get_audio_format();
+        let mut args: Vec<&str> = vec![&youtube_url, "-f", "bestaudio"];
+        args.extend(audio_format.ytdlp_args());
+        args.extend([
             "--prefer-free-formats",
-            "--embed-thumbnail",
-            "--add-metadata",
-            "--output", &output_template,
-            "--progress",
-            "--newline",
+            "--output", output_template.as_str(),
             "--no-warnings",
-        ];
-
-        let (mut rx, _child) = sidecar.args(args).spawn()
-            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
-
-        update_status_fn(job_id, DownloadStatus::Downloading, 10.0, "Downloading...");
-
-        let mut last_progress: f32 = 10.0;
-        let track_title = track_metadata.title.clone();
-
-        while let Some(event) = rx.recv().await {
-            match event {
-                tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
-                    let line_str = String::from_utf8_lossy(&line).to_string();
-                    println!("[yt-dlp] {}", line_str);
-
-                    if let Some(pct) = YouTubeDownloader::parse_ytdlp_progress(&line_str) {
-                        last_progress = 10.0 + (pct * 0.8); // 10-90%
-                        update_status_fn(
-                            job_id,
-                            DownloadStatus::Downloading,
-                            last_progress,
-                            &format!("Downloading... {:.1}%", pct),
-                        );
-                        // Real-time UI update
-                        emit_queue_fn();
-                    }
+        ]);
 
-                    if line_str.contains("[ExtractAudio]") || line_str.contains("[Merger]") {
-                        update_status_fn(job_id, DownloadStatus::Converting, 92.0, "Converting to MP3...");
-                        emit_queue_fn();
-                    }
-                }
-                tauri_plugin_shell::process::CommandEvent::Stderr(line) => {
-                    let line_str = String::from_utf8_lossy(&line).to_string();
-                    eprintln!("[yt-dlp stderr] {}", line_str);
-                }
-                tauri_plugin_shell::process::CommandEvent::Error(error) => {
-                    update_status_fn(
-                        job_id,
-                        DownloadStatus::Error,
-                        last_progress,
-                        &format!("Error: {}", error),
-                    );
-                    return Err(format!("yt-dlp error: {}", error));
-                }
-                tauri_plugin_shell::process::CommandEvent::Terminated(payload) => {
-                    if payload.code != Some(0) {
-                        let error_msg = format!("yt-dlp exited with code: {:?}", payload.code);
-                        update_status_fn(job_id, DownloadStatus::Error, last_progress, &error_msg);
-                        return Err(error_msg);
-                    }
-                    break;
-                }
-                _ => {}
+        YtDlpRunner::download_audio(app, job_id, &args, (10.0, 90.0), &update_status, &emit_queue).await?;
+
+        // The extension may differ from the planned one when the original format is kept
+        let output_path = crate::utils::filesystem::find_audio_file(&output_path).unwrap_or(output_path);
+        Ok(output_path.to_string_lossy().to_string())
+    }
+}
+
+impl MusicSource for AppleMusicDownloader {
+    fn service(&self) -> MusicService {
+        MusicService::AppleMusic
+    }
+
+    fn matches_url(&self, url: &str) -> bool {
+        host_matches(url, &["music.apple.com", "itunes.apple.com"])
+    }
+
+    /// Album links without a song (?i=) and playlist links
+    fn is_collection(&self, url: &str) -> bool {
+        parse_link(url).is_some_and(|link| matches!(link.kind, LinkKind::Album | LinkKind::Playlist))
+    }
+
+    fn expand_collection<'a>(&'a self, _app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<Collection>> {
+        Box::pin(async move {
+            if parse_link(url).is_some_and(|link| link.kind == LinkKind::Playlist) {
+                Self::expand_playlist(url).await
+            } else {
+                Self::expand_album(url).await
             }
-        }
+        })
+    }
 
-        update_status_fn(job_id, DownloadStatus::Complete, 100.0, "Download complete!");
-        emit_queue_fn();
+    fn resolve_metadata<'a>(&'a self, _app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<TrackMetadata>> {
+        Box::pin(async move {
+            let (_search_query, _artist, info) = Self::get_track_info(url).await?;
+            info.map(|info| info.to_metadata())
+                .ok_or_else(|| AppError::new(ErrorKind::NotFound, format!("No track info for {}", url)))
+        })
+    }
 
-        Ok(output_path.to_string_lossy().to_string())
+    fn download<'a>(&'a self, request: DownloadRequest<'a>) -> BoxFuture<'a, AppResult<String>> {
+        Box::pin(Self::download_track(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_ids() {
+        assert_eq!(
+            AppleMusicDownloader::extract_album_id("https://music.apple.com/il/album/album-name/1234567890").as_deref(),
+            Some("1234567890")
+        );
+        assert_eq!(
+            AppleMusicDownloader::extract_album_id("https://music.apple.com/us/album/1234567890?l=he").as_deref(),
+            Some("1234567890")
+        );
+        assert_eq!(AppleMusicDownloader::extract_album_id("https://music.apple.com/us/song/name/1234567891"), None);
+        assert_eq!(
+            AppleMusicDownloader::storefront("https://music.apple.com/il/album/name/1").as_deref(),
+            Some("il")
+        );
+    }
+
+    #[test]
+    fn test_extract_song_ids() {
+        let html = r#"<meta property="og:title" content="Road Trip &amp; Chill on Apple Music">
+            <meta property="music:song" content="https://music.apple.com/us/song/first/111">
+            <a href="https://music.apple.com/us/album/second/999?i=222">Second</a>
+            <a href="/us/song/first/111">First again</a>"#;
+        assert_eq!(AppleMusicDownloader::extract_song_ids(html), vec!["111", "222"]);
+        assert_eq!(
+            AppleMusicDownloader::meta_content(html, "og:title").as_deref(),
+            Some("Road Trip & Chill on Apple Music")
+        );
+    }
+
+    #[test]
+    fn test_extract_song_ids_from_playlist_page() {
+        let html = include_str!("testdata/apple_music_playlist.html");
+        // "music:song" meta tags and "?i=" track links name the same songs; the last is only linked
+        assert_eq!(
+            AppleMusicDownloader::extract_song_ids(html),
+            vec!["1440806421", "1443110104", "1440806429", "1545623397"]
+        );
+        assert_eq!(
+            AppleMusicDownloader::meta_content(html, "og:title").as_deref(),
+            Some("Late Night Drive on Apple Music")
+        );
     }
 }
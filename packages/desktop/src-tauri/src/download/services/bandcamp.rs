@@ -0,0 +1,445 @@
+// Bandcamp Download Service
+// Uses yt-dlp for track metadata, album listings and downloads
+// A fan's collection page (bandcamp.com/FAN) queues every album and track they bought.
+// Only the public stream (128 kbps MP3) is fetched. Purchased files in higher quality need the
+// buyer's login on the fan download page, which isn't supported - the UI says so on the Bandcamp icon.
+
+use crate::download::{DownloadContext, DownloadJob, DownloadStatus, MusicService, SourceIds, TrackMetadata};
+use crate::download::links::{parse_link, LinkKind};
+use crate::download::services::registry::{host_matches, Collection, DownloadRequest, MusicSource};
+use crate::download::services::YouTubeDownloader;
+use crate::download::ytdlp::YtDlpRunner;
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+use futures_util::future::BoxFuture;
+use tauri::AppHandle;
+
+/// Items requested per page of a fan's collection
+const COLLECTION_PAGE_SIZE: u32 = 100;
+
+/// A purchase listed in a fan's collection
+#[derive(Debug, Clone, PartialEq)]
+struct CollectionItem {
+    url: String,
+    is_album: bool,
+    title: String,
+    artist: String,
+}
+
+pub struct BandcampDownloader;
+
+impl BandcampDownloader {
+    /// Download a Bandcamp track using yt-dlp (the 128 kbps stream, also for purchased tracks)
+    pub async fn download_track(request: DownloadRequest<'_>) -> AppResult<String> {
+        let DownloadRequest { app, url, base_output_dir, download_context, job_id, update_status, emit_queue, update_metadata } = request;
+        println!("[Bandcamp] Starting download for URL: {}", url);
+
+        // Step 1: Get metadata
+        update_status(job_id, DownloadStatus::Downloading, 5.0, "Fetching metadata...");
+        emit_queue();
+
+        let mut metadata = Self::fetch_metadata(app, url).await?;
+        // Album expansion already set the track number and total
+        if let Ok(job) = crate::download::QueueManager::get_job(job_id) {
+            metadata.fill_missing(&job.metadata);
+        }
+
+        // Transliterate if English Only mode is enabled (BEFORE calculating path)
+        metadata = crate::download::transliteration::transliterate_if_needed(&metadata)
+            .await
+            .unwrap_or(metadata);
+
+        update_metadata(metadata.clone());
+        emit_queue();
+
+        println!(
+            "[Bandcamp] Title: '{}', Artist: '{}', Album: '{}'",
+            metadata.title, metadata.artist, metadata.album
+        );
+
+        // Step 2: Calculate output path (uses transliterated metadata)
+        let output_path = crate::utils::filesystem::get_organized_output_path(
+            base_output_dir,
+            &metadata,
+            download_context,
+        );
+        // Apply the duplicate policy if this track is already on disk
+        let output_path = crate::download::dedup::resolve_output_path(job_id, output_path)?;
+        let output_dir = output_path.parent().unwrap().to_string_lossy().to_string();
+
+        std::fs::create_dir_all(&output_dir)
+            .or_error(ErrorKind::Io, "Failed to create directory")?;
+
+        let output_template = crate::utils::filesystem::ytdlp_output_template(&output_path);
+
+        // Step 3: Download with yt-dlp
+        let audio_format = crate::utils::get_audio_format();
+        let mut args: Vec<&str> = vec![url, "-f", "bestaudio"];
+        args.extend(audio_format.ytdlp_args());
+        args.extend([
+            "--output", output_template.as_str(),
+            "--no-warnings",
+        ]);
+
+        let output = YtDlpRunner::download_audio(app, job_id, &args, (10.0, 90.0), &update_status, &emit_queue).await?;
+
+        let final_path = output.output_path.unwrap_or_else(|| {
+            crate::utils::filesystem::find_audio_file(&output_path)
+                .unwrap_or(output_path)
+                .to_string_lossy()
+                .to_string()
+        });
+        println!("[Bandcamp] Returning output path: {}", final_path);
+        Ok(final_path)
+    }
+
+    /// Fetch a track's metadata with yt-dlp (without downloading it)
+    pub async fn fetch_metadata(app: &AppHandle, url: &str) -> AppResult<TrackMetadata> {
+        let json = YtDlpRunner::dump_json(app, &["--dump-json", "--no-download", "--no-playlist", url]).await?;
+        Ok(Self::parse_bandcamp_metadata(&json))
+    }
+
+    /// Parse Bandcamp track metadata from yt-dlp JSON output
+    fn parse_bandcamp_metadata(json: &serde_json::Value) -> TrackMetadata {
+        let text = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let number = |key: &str| json.get(key).and_then(|v| v.as_u64()).map(|n| n as u32);
+
+        let artist = text("artist")
+            .or_else(|| text("uploader"))
+            .unwrap_or_else(|| "Unknown Artist".to_string());
+
+        TrackMetadata {
+            // "title" is "Artist - Track"; "track" is just the track name
+            title: text("track").or_else(|| text("title")).unwrap_or_else(|| "Unknown".to_string()),
+            artists: crate::download::split_artists(&artist),
+            album_artist: text("album_artist").or_else(|| text("uploader")),
+            artist,
+            album: text("album").unwrap_or_else(|| "Unknown Album".to_string()),
+            duration: json.get("duration").and_then(|v| v.as_f64()).map(|d| d.round() as u32),
+            thumbnail: text("thumbnail"),
+            track_number: number("track_number"),
+            release_date: YouTubeDownloader::parse_ytdlp_date(json, "release_date")
+                .or_else(|| YouTubeDownloader::parse_ytdlp_date(json, "upload_date")),
+            source_ids: SourceIds {
+                bandcamp: text("id"),
+                ..SourceIds::default()
+            },
+            ..TrackMetadata::default()
+        }
+    }
+
+    /// Expand an album into one job per track, numbered in album order
+    pub async fn expand_album(app: &AppHandle, album_url: &str) -> AppResult<Collection> {
+        println!("[Bandcamp] Processing album: {}", album_url);
+
+        let json = YtDlpRunner::dump_json(app, &["--flat-playlist", "--dump-single-json", "--no-warnings", album_url]).await?;
+        let collection = Self::parse_album(&json)?;
+        println!("[Bandcamp] Album: '{}' ({} tracks)", collection.name, collection.jobs.len());
+        Ok(collection)
+    }
+
+    /// Build the album's jobs from yt-dlp's flat playlist JSON
+    fn parse_album(json: &serde_json::Value) -> AppResult<Collection> {
+        let entries = json.get("entries")
+            .and_then(|v| v.as_array())
+            .filter(|entries| !entries.is_empty())
+            .ok_or_else(|| AppError::new(ErrorKind::NotFound, "No tracks found on Bandcamp album"))?;
+
+        let text = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let album_name = text("album").or_else(|| text("title")).unwrap_or_else(|| "Bandcamp Album".to_string());
+        let album_artist = text("artist").or_else(|| text("uploader"));
+        let track_total = entries.len() as u32;
+
+        let album_context = DownloadContext::Album(album_name.clone());
+        let mut jobs = Vec::new();
+
+        for (index, entry) in entries.iter().enumerate() {
+            let Some(track_url) = entry.get("url").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let mut job = DownloadJob::new(track_url.to_string());
+            if let Some(title) = entry.get("title").and_then(|v| v.as_str()) {
+                job.metadata.title = title.to_string();
+            }
+            job.metadata.album = album_name.clone();
+            job.metadata.album_artist = album_artist.clone();
+            job.metadata.track_number = Some(index as u32 + 1);
+            job.metadata.track_total = Some(track_total);
+            job.download_context = Some(album_context.clone());
+            jobs.push(job);
+        }
+
+        Ok(Collection {
+            name: album_name,
+            jobs,
+        })
+    }
+
+    /// Queue a fan's collection: every purchased album expanded into its tracks, plus single tracks
+    pub async fn expand_fan_collection(app: &AppHandle, collection_url: &str) -> AppResult<Collection> {
+        println!("[Bandcamp] Processing fan collection: {}", collection_url);
+
+        let html = reqwest::get(collection_url)
+            .await
+            .or_error(ErrorKind::Network, "Failed to open Bandcamp collection")?
+            .text()
+            .await
+            .or_error(ErrorKind::Network, "Failed to read Bandcamp collection")?;
+        let blob = Self::page_blob(&html)
+            .ok_or_else(|| AppError::new(ErrorKind::Parse, "No collection data on Bandcamp fan page"))?;
+        let fan_id = blob
+            .pointer("/fan_data/fan_id")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| AppError::new(ErrorKind::NotFound, "Not a Bandcamp fan page"))?;
+        let fan_name = blob
+            .pointer("/fan_data/name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Bandcamp")
+            .to_string();
+
+        // The page only shows the first few purchases - the collection API pages through all of them
+        let client = reqwest::Client::new();
+        let mut items = Vec::new();
+        let mut token = format!("{}::a::", chrono::Utc::now().timestamp());
+        loop {
+            let response = client
+                .post("https://bandcamp.com/api/fancollection/1/collection_items")
+                .json(&serde_json::json!({
+                    "fan_id": fan_id,
+                    "older_than_token": token,
+                    "count": COLLECTION_PAGE_SIZE,
+                }))
+                .send()
+                .await
+                .or_error(ErrorKind::Network, "Bandcamp collection request failed")?;
+            if !response.status().is_success() {
+                return Err(AppError::http(response.status(), format!("Bandcamp collection error: {}", response.status())));
+            }
+            let page: serde_json::Value = response
+                .json()
+                .await
+                .or_error(ErrorKind::Parse, "Failed to parse Bandcamp collection")?;
+
+            let (page_items, next_token) = Self::parse_collection_page(&page);
+            items.extend(page_items);
+            match next_token {
+                Some(next) => token = next,
+                None => break,
+            }
+        }
+        if items.is_empty() {
+            return Err(AppError::new(
+                ErrorKind::NotFound,
+                "No purchases found - the Bandcamp collection is empty or private",
+            ));
+        }
+        println!("[Bandcamp] Collection of '{}': {} purchases", fan_name, items.len());
+
+        let mut jobs = Vec::new();
+        for item in items {
+            if item.is_album {
+                match Self::expand_album(app, &item.url).await {
+                    Ok(album) => jobs.extend(album.jobs),
+                    Err(e) => println!("[Bandcamp] Skipping album {}: {}", item.url, e),
+                }
+            } else {
+                let mut job = DownloadJob::new(item.url);
+                job.metadata.title = item.title;
+                job.metadata.artist = item.artist;
+                jobs.push(job);
+            }
+        }
+
+        Ok(Collection {
+            name: format!("{} - Bandcamp Collection", fan_name),
+            jobs,
+        })
+    }
+
+    /// The JSON in a page's data-blob attribute (HTML-escaped)
+    fn page_blob(html: &str) -> Option<serde_json::Value> {
+        let start = html.find("data-blob=\"")? + "data-blob=\"".len();
+        let end = start + html[start..].find('"')?;
+        let json = html[start..end]
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&");
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Items of one collection API page, and the token for the next page if there are more
+    fn parse_collection_page(page: &serde_json::Value) -> (Vec<CollectionItem>, Option<String>) {
+        let text = |item: &serde_json::Value, key: &str| {
+            item.get(key)
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let items: Vec<CollectionItem> = page
+            .get("items")
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| {
+                        Some(CollectionItem {
+                            url: text(item, "item_url")?,
+                            is_album: text(item, "item_type").as_deref() != Some("track"),
+                            title: text(item, "item_title")
+                                .or_else(|| text(item, "album_title"))
+                                .unwrap_or_else(|| "Unknown".to_string()),
+                            artist: text(item, "band_name").unwrap_or_else(|| "Unknown Artist".to_string()),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let more = page.get("more_available").and_then(|v| v.as_bool()).unwrap_or(false);
+        let next_token = text(page, "last_token").filter(|_| more && !items.is_empty());
+        (items, next_token)
+    }
+
+    fn is_album_url(url: &str) -> bool {
+        parse_link(url).is_some_and(|link| link.kind == LinkKind::Album)
+    }
+
+    fn is_fan_collection_url(url: &str) -> bool {
+        parse_link(url).is_some_and(|link| link.service == MusicService::Bandcamp && link.kind == LinkKind::Playlist)
+    }
+}
+
+impl MusicSource for BandcampDownloader {
+    fn service(&self) -> MusicService {
+        MusicService::Bandcamp
+    }
+
+    fn matches_url(&self, url: &str) -> bool {
+        // Artist sites on custom domains are recognized once resolve_link has seen them
+        host_matches(url, &["bandcamp.com"]) || parse_link(url).is_some_and(|link| link.service == MusicService::Bandcamp)
+    }
+
+    fn is_collection(&self, url: &str) -> bool {
+        Self::is_album_url(url) || Self::is_fan_collection_url(url)
+    }
+
+    fn resolve_metadata<'a>(&'a self, app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<TrackMetadata>> {
+        Box::pin(Self::fetch_metadata(app, url))
+    }
+
+    fn expand_collection<'a>(&'a self, app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<Collection>> {
+        if Self::is_fan_collection_url(url) {
+            return Box::pin(Self::expand_fan_collection(app, url));
+        }
+        Box::pin(Self::expand_album(app, url))
+    }
+
+    fn download<'a>(&'a self, request: DownloadRequest<'a>) -> BoxFuture<'a, AppResult<String>> {
+        Box::pin(Self::download_track(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bandcamp_metadata() {
+        let json = serde_json::json!({
+            "id": "2650410135",
+            "title": "Artist Name - Song Name",
+            "track": "Song Name",
+            "artist": "Artist Name",
+            "album": "Album Name",
+            "uploader": "Label Name",
+            "track_number": 3,
+            "duration": 241.5,
+            "release_date": "20230915",
+        });
+        let metadata = BandcampDownloader::parse_bandcamp_metadata(&json);
+        assert_eq!(metadata.title, "Song Name");
+        assert_eq!(metadata.artist, "Artist Name");
+        assert_eq!(metadata.album_artist.as_deref(), Some("Label Name"));
+        assert_eq!(metadata.track_number, Some(3));
+        assert_eq!(metadata.duration, Some(242));
+        assert_eq!(metadata.release_date.as_deref(), Some("2023-09-15"));
+        assert_eq!(metadata.source_ids.bandcamp.as_deref(), Some("2650410135"));
+    }
+
+    #[test]
+    fn test_parse_album() {
+        let json = serde_json::json!({
+            "_type": "playlist",
+            "title": "Album Name",
+            "uploader": "Artist Name",
+            "entries": [
+                {"_type": "url", "url": "https://artist.bandcamp.com/track/first-song", "title": "First Song"},
+                {"_type": "url", "title": "No URL"},
+                {"_type": "url", "url": "https://artist.bandcamp.com/track/second-song", "title": "Second Song"},
+            ],
+        });
+        let album = BandcampDownloader::parse_album(&json).unwrap();
+        assert_eq!(album.name, "Album Name");
+        assert_eq!(album.jobs.len(), 2);
+
+        let first = &album.jobs[0];
+        assert_eq!(first.url, "https://artist.bandcamp.com/track/first-song");
+        assert_eq!(first.metadata.title, "First Song");
+        assert_eq!(first.metadata.album_artist.as_deref(), Some("Artist Name"));
+        assert_eq!(first.metadata.track_number, Some(1));
+        assert_eq!(first.metadata.track_total, Some(3));
+        assert_eq!(first.download_context, Some(DownloadContext::Album("Album Name".to_string())));
+        // Numbered by position on the album, even when an entry is skipped
+        assert_eq!(album.jobs[1].metadata.track_number, Some(3));
+
+        assert!(BandcampDownloader::parse_album(&serde_json::json!({"title": "Empty", "entries": []})).is_err());
+    }
+
+    #[test]
+    fn test_fan_collection() {
+        let html = r#"<div id="pagedata" data-blob="{&quot;fan_data&quot;:{&quot;fan_id&quot;:12345,&quot;name&quot;:&quot;Dana &amp; Co&quot;}}"></div>"#;
+        let blob = BandcampDownloader::page_blob(html).unwrap();
+        assert_eq!(blob.pointer("/fan_data/fan_id").and_then(|v| v.as_u64()), Some(12345));
+        assert_eq!(blob.pointer("/fan_data/name").and_then(|v| v.as_str()), Some("Dana & Co"));
+
+        let page = serde_json::json!({
+            "items": [
+                {"item_type": "album", "item_url": "https://artist.bandcamp.com/album/name", "item_title": "Name", "band_name": "Artist"},
+                {"item_type": "track", "item_url": "https://other.bandcamp.com/track/song", "item_title": "Song", "band_name": "Other"},
+                {"item_type": "album", "item_title": "No URL"},
+            ],
+            "more_available": true,
+            "last_token": "1690000000:123:a::",
+        });
+        let (items, next_token) = BandcampDownloader::parse_collection_page(&page);
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_album);
+        assert_eq!(
+            items[1],
+            CollectionItem {
+                url: "https://other.bandcamp.com/track/song".to_string(),
+                is_album: false,
+                title: "Song".to_string(),
+                artist: "Other".to_string(),
+            }
+        );
+        assert_eq!(next_token.as_deref(), Some("1690000000:123:a::"));
+
+        let last = serde_json::json!({ "items": [], "more_available": false, "last_token": "x" });
+        assert_eq!(BandcampDownloader::parse_collection_page(&last), (vec![], None));
+
+        assert!(BandcampDownloader.is_collection("https://bandcamp.com/fanname"));
+        assert!(!BandcampDownloader.is_collection("https://artist.bandcamp.com/track/song"));
+    }
+}
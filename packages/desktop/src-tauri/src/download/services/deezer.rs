@@ -1,5 +1,7 @@
 // Deezer download and decryption service
 
+use std::path::Path;
+
 use blowfish::Blowfish;
 use cipher::{BlockDecryptMut, KeyIvInit};
 use cbc::Decryptor;
@@ -7,6 +9,11 @@ use tauri::AppHandle;
 use tauri_plugin_shell::ShellExt;
 
 use crate::api_types::{HasodApiClient, DeezerQuality};
+use crate::download::control::JobControl;
+use crate::download::format::AudioFormat;
+use crate::download::limits::DownloadResource;
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+use crate::utils::filesystem::split_audio_extension;
 
 type BlowfishCbc = Decryptor<Blowfish>;
 
@@ -19,13 +26,16 @@ pub struct DeezerDownloader;
 impl DeezerDownloader {
     /// Decrypt Deezer encrypted MP3/FLAC file using Blowfish CBC
     /// Deezer uses a custom encryption scheme where only certain chunks are encrypted
-    pub fn decrypt_file(encrypted_data: &[u8], decryption_key_hex: &str) -> Result<Vec<u8>, String> {
+    pub fn decrypt_file(encrypted_data: &[u8], decryption_key_hex: &str) -> AppResult<Vec<u8>> {
         // Parse hex key to bytes
         let key_bytes = hex::decode(decryption_key_hex)
-            .map_err(|e| format!("Invalid decryption key hex: {}", e))?;
+            .or_error(ErrorKind::Parse, "Invalid decryption key hex")?;
 
         if key_bytes.len() != 16 {
-            return Err(format!("Invalid key length: {} bytes (expected 16)", key_bytes.len()));
+            return Err(AppError::new(
+                ErrorKind::Parse,
+                format!("Invalid key length: {} bytes (expected 16)", key_bytes.len()),
+            ));
         }
 
         let mut decrypted_data = encrypted_data.to_vec();
@@ -48,7 +58,7 @@ impl DeezerDownloader {
                 if chunk_len >= 8 {
                     // Initialize CBC decryptor for this chunk
                     let cipher = BlowfishCbc::new_from_slices(&key_bytes, &iv)
-                        .map_err(|e| format!("Failed to initialize Blowfish CBC: {}", e))?;
+                        .map_err(|e| AppError::internal(format!("Failed to initialize Blowfish CBC: {}", e)))?;
 
                     // Get mutable slice for this chunk (must be aligned to 8-byte blocks)
                     let blocks_len = (chunk_len / 8) * 8; // Round down to block boundary
@@ -56,7 +66,7 @@ impl DeezerDownloader {
 
                     // Decrypt the chunk in-place
                     cipher.decrypt_padded_mut::<cipher::block_padding::NoPadding>(chunk_data)
-                        .map_err(|e| format!("Decryption failed: {}", e))?;
+                        .map_err(|e| AppError::internal(format!("Decryption failed: {}", e)))?;
                 }
             }
         }
@@ -64,22 +74,90 @@ impl DeezerDownloader {
         Ok(decrypted_data)
     }
 
+    /// Write decrypted audio with the extension of the quality Deezer actually delivered,
+    /// then transcode it if the audio format setting asks for something else
+    /// Returns the path of the final file
+    async fn save_audio(
+        app: &AppHandle,
+        decrypted_bytes: Vec<u8>,
+        delivered: &DeezerQuality,
+        output_path: &str,
+        audio_format: AudioFormat,
+    ) -> AppResult<String> {
+        let (stem, _) = split_audio_extension(Path::new(output_path));
+        let stem = stem.to_string_lossy();
+
+        // FLAC isn't available for every track - the backend falls back to MP3
+        let source_extension = match delivered {
+            DeezerQuality::Flac => "flac",
+            DeezerQuality::Mp3128 | DeezerQuality::Mp3320 => "mp3",
+        };
+        let source_path = format!("{}.{}", stem, source_extension);
+        std::fs::write(&source_path, decrypted_bytes)
+            .or_error(ErrorKind::Io, "Failed to write decrypted file")?;
+
+        let target_extension = match audio_format.extension() {
+            Some(ext) if audio_format.needs_transcode(source_extension) => ext,
+            _ => return Ok(source_path),
+        };
+        let target_path = format!("{}.{}", stem, target_extension);
+
+        match Self::transcode(app, &source_path, &target_path, audio_format).await {
+            Ok(()) => {
+                std::fs::remove_file(&source_path).ok();
+                println!("[Deezer] ✅ Converted to {}", audio_format.display_name());
+                Ok(target_path)
+            }
+            Err(e) => {
+                // A file in the wrong format is still better than no file
+                std::fs::remove_file(&target_path).ok();
+                println!(
+                    "[Deezer] ⚠️ Conversion to {} failed, keeping {}: {}",
+                    audio_format.display_name(),
+                    source_extension,
+                    e
+                );
+                Ok(source_path)
+            }
+        }
+    }
+
+    /// Convert an audio file with the ffmpeg sidecar
+    async fn transcode(app: &AppHandle, source: &str, target: &str, audio_format: AudioFormat) -> AppResult<()> {
+        let sidecar = app.shell().sidecar("ffmpeg")
+            .or_error(ErrorKind::DependencyMissing, "Failed to get ffmpeg sidecar")?;
+
+        let mut args = vec!["-i", source, "-vn"];
+        args.extend(audio_format.ffmpeg_args());
+        args.extend(["-y", target]);
+
+        let output = sidecar.args(args).output().await
+            .or_error(ErrorKind::DependencyMissing, "Failed to run ffmpeg")?;
+        if !output.status.success() {
+            return Err(AppError::new(
+                ErrorKind::ProcessFailed,
+                format!("ffmpeg exited with code: {:?}", output.status.code()),
+            ));
+        }
+        Ok(())
+    }
+
     /// Download and decrypt track from Deezer using ISRC
-    /// Returns the path to the decrypted MP3 file
+    /// Returns the path to the decrypted file
     pub async fn download_and_decrypt(
         app: &AppHandle,
         isrc: &str,
         auth_token: &str,
         output_path: &str,
-        artwork_url: Option<&str>,
-    ) -> Result<String, String> {
+    ) -> AppResult<String> {
         println!("[Deezer] Attempting download for ISRC: {}", isrc);
 
         // Step 1: Get download URL and decryption key from backend
         let api_client = HasodApiClient::production();
+        let audio_format = crate::utils::get_audio_format();
 
         let deezer_response = api_client
-            .get_deezer_download_url(isrc, auth_token, Some(DeezerQuality::Mp3320))
+            .get_deezer_download_url(isrc, auth_token, Some(audio_format.deezer_quality()))
             .await?;
 
         println!("[Deezer] ✅ Got download URL (quality: {:?})", deezer_response.quality);
@@ -91,22 +169,25 @@ impl DeezerDownloader {
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(300)) // 5 minute timeout
             .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+            .or_error(ErrorKind::Internal, "Failed to create HTTP client")?;
 
         let response = client
             .get(&deezer_response.download_url)
             .send()
             .await
-            .map_err(|e| format!("Failed to download from Deezer: {}", e))?;
+            .or_error(ErrorKind::Network, "Failed to download from Deezer")?;
 
         if !response.status().is_success() {
-            return Err(format!("Deezer download failed with status: {}", response.status()));
+            return Err(AppError::http(
+                response.status(),
+                format!("Deezer download failed with status: {}", response.status()),
+            ));
         }
 
         let encrypted_bytes = response
             .bytes()
             .await
-            .map_err(|e| format!("Failed to read download bytes: {}", e))?;
+            .or_error(ErrorKind::Network, "Failed to read download bytes")?;
 
         println!("[Deezer] Downloaded {} bytes", encrypted_bytes.len());
 
@@ -117,64 +198,14 @@ impl DeezerDownloader {
 
         println!("[Deezer] ✅ Decrypted successfully");
 
-        // Step 4: Write decrypted file
-        std::fs::write(output_path, decrypted_bytes)
-            .map_err(|e| format!("Failed to write decrypted file: {}", e))?;
+        // Step 4: Write decrypted file (converted to the configured format if needed)
+        let output_path =
+            Self::save_audio(app, decrypted_bytes, &deezer_response.quality, output_path, audio_format).await?;
+        let output_path = output_path.as_str();
 
         println!("[Deezer] ✅ Saved to: {}", output_path);
 
-        // Step 5: Download and embed artwork if available
-        if let Some(artwork_url) = artwork_url {
-            println!("[Deezer] Downloading and embedding artwork...");
-
-            // Download artwork
-            let artwork_response = client.get(artwork_url).send().await;
-
-            if let Ok(artwork_resp) = artwork_response {
-                if artwork_resp.status().is_success() {
-                    if let Ok(artwork_bytes) = artwork_resp.bytes().await {
-                        // Save artwork temporarily
-                        let artwork_path = format!("{}.jpg", output_path.trim_end_matches(".mp3"));
-                        if std::fs::write(&artwork_path, &artwork_bytes).is_ok() {
-                            // Use ffmpeg to embed artwork
-                            let temp_output = format!("{}.temp.mp3", output_path.trim_end_matches(".mp3"));
-
-                            match app.shell().sidecar("ffmpeg") {
-                                Ok(sidecar) => {
-                                    let result = sidecar.args(&[
-                                        "-i", output_path,
-                                        "-i", &artwork_path,
-                                        "-map", "0:a",
-                                        "-map", "1:0",
-                                        "-c", "copy",
-                                        "-id3v2_version", "3",
-                                        "-metadata:s:v", "title=Album cover",
-                                        "-metadata:s:v", "comment=Cover (front)",
-                                        "-y",
-                                        &temp_output,
-                                    ]).status().await;
-
-                                    if let Ok(status) = result {
-                                        if status.success() {
-                                            // Replace original with artwork-embedded version
-                                            std::fs::rename(&temp_output, output_path).ok();
-                                            println!("[Deezer] ✅ Artwork embedded");
-                                        }
-                                    }
-                                }
-                                Err(_) => {
-                                    println!("[Deezer] ⚠️ ffmpeg not available, skipping artwork");
-                                }
-                            }
-
-                            // Clean up temp artwork file
-                            std::fs::remove_file(&artwork_path).ok();
-                        }
-                    }
-                }
-            }
-        }
-
+        // Tags and cover art are written afterwards by the tag writer
         Ok(output_path.to_string())
     }
 
@@ -185,11 +216,10 @@ impl DeezerDownloader {
         isrc: &str,
         auth_token: &str,
         output_path: &str,
-        artwork_url: Option<&str>,
         job_id: &str,
         update_status_fn: &impl Fn(&str, crate::download::DownloadStatus, f32, &str),
         emit_queue_fn: &impl Fn(),
-    ) -> Result<String, String> {
+    ) -> AppResult<String> {
         use futures_util::StreamExt;
 
         println!("[Deezer] Attempting download for ISRC: {} with progress tracking", isrc);
@@ -199,9 +229,10 @@ impl DeezerDownloader {
 
         // Step 1: Get download URL and decryption key from backend
         let api_client = HasodApiClient::production();
+        let audio_format = crate::utils::get_audio_format();
 
         let deezer_response = api_client
-            .get_deezer_download_url(isrc, auth_token, Some(DeezerQuality::Mp3320))
+            .get_deezer_download_url(isrc, auth_token, Some(audio_format.deezer_quality()))
             .await?;
 
         println!("[Deezer] ✅ Got download URL (quality: {:?})", deezer_response.quality);
@@ -209,33 +240,49 @@ impl DeezerDownloader {
         update_status_fn(job_id, crate::download::DownloadStatus::Downloading, 15.0, "Downloading from Deezer...");
         emit_queue_fn();
 
+        let deezer_permit = DownloadResource::Deezer.acquire_for_job(job_id).await?;
+
         // Step 2: Download encrypted file with progress tracking
         let client = reqwest::Client::builder()
             .timeout(std::time::Duration::from_secs(300))
             .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+            .or_error(ErrorKind::Internal, "Failed to create HTTP client")?;
 
         let response = client
             .get(&deezer_response.download_url)
             .send()
             .await
-            .map_err(|e| format!("Failed to download from Deezer: {}", e))?;
+            .or_error(ErrorKind::Network, "Failed to download from Deezer")?;
 
         if !response.status().is_success() {
-            return Err(format!("Deezer download failed with status: {}", response.status()));
+            return Err(AppError::http(
+                response.status(),
+                format!("Deezer download failed with status: {}", response.status()),
+            ));
         }
 
         // Get total size if available
         let total_size = response.content_length().unwrap_or(0);
         println!("[Deezer] Total size: {} bytes", total_size);
 
-        // Stream the download with progress
+        // Stream the download with progress (dropping the stream aborts the HTTP request)
         let mut stream = response.bytes_stream();
         let mut encrypted_bytes = Vec::new();
         let mut downloaded: u64 = 0;
-
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
+        let mut control = JobControl::subscribe(job_id);
+
+        loop {
+            let chunk = tokio::select! {
+                chunk = stream.next() => match chunk {
+                    Some(chunk) => chunk,
+                    None => break,
+                },
+                signal = control.stopped() => {
+                    println!("[Deezer] {}, aborting stream", signal.stop_message());
+                    return Err(signal.stop_error());
+                }
+            };
+            let chunk = chunk.or_error(ErrorKind::Network, "Download error")?;
             downloaded += chunk.len() as u64;
             encrypted_bytes.extend_from_slice(&chunk);
 
@@ -253,6 +300,7 @@ impl DeezerDownloader {
         }
 
         println!("[Deezer] Downloaded {} bytes", encrypted_bytes.len());
+        drop(deezer_permit);
 
         update_status_fn(job_id, crate::download::DownloadStatus::Converting, 80.0, "Decrypting...");
         emit_queue_fn();
@@ -265,62 +313,14 @@ impl DeezerDownloader {
         update_status_fn(job_id, crate::download::DownloadStatus::Converting, 90.0, "Saving file...");
         emit_queue_fn();
 
-        // Step 4: Write decrypted file
-        std::fs::write(output_path, decrypted_bytes)
-            .map_err(|e| format!("Failed to write decrypted file: {}", e))?;
+        // Step 4: Write decrypted file (converted to the configured format if needed)
+        let output_path =
+            Self::save_audio(app, decrypted_bytes, &deezer_response.quality, output_path, audio_format).await?;
+        let output_path = output_path.as_str();
 
         println!("[Deezer] ✅ Saved to: {}", output_path);
 
-        // Step 5: Download and embed artwork if available
-        if let Some(artwork_url) = artwork_url {
-            println!("[Deezer] Downloading and embedding artwork...");
-            update_status_fn(job_id, crate::download::DownloadStatus::Converting, 95.0, "Adding artwork...");
-            emit_queue_fn();
-
-            // Download artwork
-            let artwork_response = client.get(artwork_url).send().await;
-
-            if let Ok(artwork_resp) = artwork_response {
-                if artwork_resp.status().is_success() {
-                    if let Ok(artwork_bytes) = artwork_resp.bytes().await {
-                        // Save artwork temporarily
-                        let artwork_path = format!("{}.jpg", output_path.trim_end_matches(".mp3"));
-                        if std::fs::write(&artwork_path, &artwork_bytes).is_ok() {
-                            // Use ffmpeg to embed artwork
-                            let temp_output = format!("{}.temp.mp3", output_path.trim_end_matches(".mp3"));
-
-                            match app.shell().sidecar("ffmpeg") {
-                                Ok(sidecar) => {
-                                    let result = sidecar.args(&[
-                                        "-i", output_path,
-                                        "-i", &artwork_path,
-                                        "-map", "0:a",
-                                        "-map", "1",
-                                        "-c", "copy",
-                                        "-id3v2_version", "3",
-                                        "-metadata:s:v", "title=Album cover",
-                                        "-metadata:s:v", "comment=Cover (front)",
-                                        &temp_output,
-                                        "-y",
-                                    ]).output().await;
-
-                                    if result.is_ok() {
-                                        if std::fs::rename(&temp_output, output_path).is_ok() {
-                                            println!("[Deezer] ✅ Embedded artwork successfully");
-                                        }
-                                    }
-                                }
-                                Err(e) => println!("[Deezer] ⚠️ ffmpeg not available: {}", e),
-                            }
-
-                            // Clean up temporary artwork file
-                            let _ = std::fs::remove_file(&artwork_path);
-                        }
-                    }
-                }
-            }
-        }
-
+        // Tags and cover art are written afterwards by the tag writer
         Ok(output_path.to_string())
     }
 }
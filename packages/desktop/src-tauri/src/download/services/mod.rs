@@ -5,10 +5,16 @@ pub mod spotify;
 pub mod soundcloud;
 pub mod deezer;
 pub mod apple_music;
+pub mod bandcamp;
+pub mod registry;
 
 // Re-export service modules
-pub use youtube::YouTubeDownloader;
-pub use spotify::{SpotifyDownloader, SpotifyTrackInfo};
+pub use youtube::{YouTubeDownloader, YouTubeMatch};
+pub use spotify::{ArtistReleaseFilter, SpotifyArtistReleases, SpotifyDownloader, SpotifyRelease, SpotifyTrackInfo};
 pub use soundcloud::SoundCloudDownloader;
 pub use deezer::DeezerDownloader;
 pub use apple_music::{AppleMusicDownloader, AppleMusicTrackInfo};
+pub use bandcamp::BandcampDownloader;
+pub use registry::{
+    collection_source_for_url, source_for_service, source_for_url, Collection, DownloadRequest, MusicSource,
+};
@@ -0,0 +1,215 @@
+// Music source trait and registry
+// Every service that can download from its own links implements MusicSource and is
+// listed in SOURCES. URL detection, metadata lookup, collection expansion and job
+// dispatch all go through the registry, so adding a service means adding one impl.
+
+use std::sync::LazyLock;
+
+use futures_util::future::BoxFuture;
+use tauri::AppHandle;
+
+use crate::download::services::{
+    AppleMusicDownloader, BandcampDownloader, SoundCloudDownloader, SpotifyDownloader, YouTubeDownloader,
+};
+use crate::download::links::{parse_link, LinkKind};
+use crate::download::{DownloadContext, DownloadJob, DownloadStatus, MusicService, TrackMetadata};
+use crate::error::{AppError, AppResult, ErrorKind};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Everything a source needs to download one job
+pub struct DownloadRequest<'a> {
+    pub app: &'a AppHandle,
+    pub url: &'a str,
+    pub base_output_dir: &'a str,
+    pub download_context: &'a DownloadContext,
+    pub job_id: &'a str,
+    pub update_status: &'a (dyn Fn(&str, DownloadStatus, f32, &str) + Send + Sync),
+    pub emit_queue: &'a (dyn Fn() + Send + Sync),
+    pub update_metadata: &'a (dyn Fn(TrackMetadata) + Send + Sync),
+}
+
+/// An album or playlist expanded into one job per track
+#[derive(Debug, Clone)]
+pub struct Collection {
+    pub name: String,
+    pub jobs: Vec<DownloadJob>,
+}
+
+impl Collection {
+    /// Build a collection from track URLs, all sharing the same download context
+    pub fn from_urls(name: String, context: DownloadContext, urls: Vec<String>) -> Self {
+        let jobs = urls
+            .into_iter()
+            .map(|url| {
+                let mut job = DownloadJob::new(url);
+                job.download_context = Some(context.clone());
+                job
+            })
+            .collect();
+        Collection { name, jobs }
+    }
+}
+
+/// A service the app can download from
+pub trait MusicSource: Send + Sync {
+    fn service(&self) -> MusicService;
+
+    /// Whether a link belongs to this service
+    fn matches_url(&self, url: &str) -> bool;
+
+    /// Whether a link is an album/playlist to expand rather than a single track
+    fn is_collection(&self, _url: &str) -> bool {
+        false
+    }
+
+    /// Metadata for a single track link, without downloading it (link previews before queueing)
+    fn resolve_metadata<'a>(&'a self, app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<TrackMetadata>>;
+
+    /// Expand an album/playlist link into one job per track
+    fn expand_collection<'a>(&'a self, _app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<Collection>> {
+        let error = AppError::new(
+            ErrorKind::Unsupported,
+            format!("{} collections are not supported: {}", self.service().display_name(), url),
+        );
+        Box::pin(async move { Err(error) })
+    }
+
+    /// Download a job and return the path of the audio file
+    fn download<'a>(&'a self, request: DownloadRequest<'a>) -> BoxFuture<'a, AppResult<String>>;
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Registered sources, checked in order
+static SOURCES: LazyLock<Vec<Box<dyn MusicSource>>> = LazyLock::new(|| {
+    vec![
+        Box::new(YouTubeDownloader),
+        Box::new(SpotifyDownloader),
+        Box::new(SoundCloudDownloader),
+        Box::new(AppleMusicDownloader),
+        Box::new(BandcampDownloader),
+    ]
+});
+
+/// Services we recognize but can't download from their own links
+const UNSUPPORTED_HOSTS: &[(&str, MusicService)] = &[
+    ("deezer.com", MusicService::Deezer),
+    ("deezer.page.link", MusicService::Deezer),
+    ("tidal.com", MusicService::Tidal),
+];
+
+pub fn sources() -> &'static [Box<dyn MusicSource>] {
+    &SOURCES
+}
+
+/// The source that handles a link
+pub fn source_for_url(url: &str) -> Option<&'static dyn MusicSource> {
+    sources().iter().find(|s| s.matches_url(url)).map(|s| s.as_ref())
+}
+
+/// The source that expands a collection link
+/// Only Spotify (top tracks) and SoundCloud (the user's tracks) can expand artist pages;
+/// other artist links are rejected rather than handed to an album/playlist expander
+pub fn collection_source_for_url(url: &str) -> AppResult<&'static dyn MusicSource> {
+    let source = source_for_url(url)
+        .ok_or_else(|| AppError::new(ErrorKind::Unsupported, format!("Unsupported link: {}", url)))?;
+    let is_artist = parse_link(url).is_some_and(|link| link.kind == LinkKind::Artist);
+    if is_artist && !source.is_collection(url) {
+        return Err(AppError::new(
+            ErrorKind::Unsupported,
+            format!(
+                "{} artist pages can't be downloaded - paste an album, playlist or track link instead",
+                source.service().display_name()
+            ),
+        ));
+    }
+    Ok(source)
+}
+
+/// The source that downloads a service's jobs
+pub fn source_for_service(service: &MusicService) -> Option<&'static dyn MusicSource> {
+    sources().iter().find(|s| s.service() == *service).map(|s| s.as_ref())
+}
+
+/// Service a link belongs to, including services without a registered source
+pub fn service_for_url(url: &str) -> MusicService {
+    if let Some(source) = source_for_url(url) {
+        return source.service();
+    }
+    UNSUPPORTED_HOSTS
+        .iter()
+        .find(|(host, _)| host_matches(url, &[host]))
+        .map(|(_, service)| service.clone())
+        .unwrap_or(MusicService::Unknown)
+}
+
+/// Whether the link's host is one of `domains` or a subdomain of one
+/// ("music.youtube.com" matches "youtube.com"; "notyoutube.com" doesn't)
+/// Links without a scheme ("youtu.be/abc") are accepted too
+pub fn host_matches(url: &str, domains: &[&str]) -> bool {
+    let url = url.trim();
+    let parsed = url::Url::parse(url)
+        .ok()
+        .filter(|u| u.has_host())
+        .or_else(|| url::Url::parse(&format!("https://{}", url)).ok());
+    let Some(host) = parsed.as_ref().and_then(|u| u.host_str()) else {
+        return false;
+    };
+    let host = host.to_lowercase();
+    domains
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_for_url() {
+        let cases = [
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ", MusicService::YouTube),
+            ("https://music.youtube.com/watch?v=dQw4w9WgXcQ", MusicService::YouTube),
+            ("youtu.be/dQw4w9WgXcQ", MusicService::YouTube),
+            ("https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC", MusicService::Spotify),
+            ("spotify:track:4uLU6hMCjMI75M1A2tKUQC", MusicService::Spotify),
+            ("https://soundcloud.com/artist/track", MusicService::SoundCloud),
+            ("https://music.apple.com/us/song/name/1234567891", MusicService::AppleMusic),
+            ("https://www.deezer.com/track/3135556", MusicService::Deezer),
+            ("https://artist.bandcamp.com/track/name", MusicService::Bandcamp),
+            ("https://notyoutube.com/watch?v=x", MusicService::Unknown),
+            ("https://example.com/?next=youtube.com", MusicService::Unknown),
+        ];
+        for (url, service) in cases {
+            assert_eq!(service_for_url(url), service, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_collection_source_for_artist_links() {
+        let expandable = [
+            "https://open.spotify.com/artist/0OdUWJ0sBjDrqHygGUXeCF",
+            "https://soundcloud.com/artist",
+        ];
+        for url in expandable {
+            assert!(collection_source_for_url(url).is_ok(), "{}", url);
+        }
+
+        let rejected = [
+            "https://artist.bandcamp.com/music",
+            "https://www.youtube.com/@artist",
+            "https://music.apple.com/us/artist/name/1234567890",
+        ];
+        for url in rejected {
+            let error = collection_source_for_url(url).err().expect(url);
+            assert_eq!(error.kind(), ErrorKind::Unsupported, "{}", url);
+        }
+
+        assert!(collection_source_for_url("https://artist.bandcamp.com/album/name").is_ok());
+    }
+}
@@ -1,7 +1,14 @@
 // SoundCloud Download Service
 // Uses yt-dlp for downloading from SoundCloud
 
-use crate::download::{DownloadContext, DownloadStatus, TrackMetadata};
+use crate::download::{DownloadContext, DownloadJob, DownloadStatus, MusicService, TrackMetadata};
+use crate::download::limits::DownloadResource;
+use crate::download::links::{parse_link, LinkKind};
+use crate::download::services::registry::{host_matches, Collection, DownloadRequest, MusicSource};
+use crate::download::services::YouTubeDownloader;
+use crate::download::ytdlp::{FlatEntry, YtDlpRunner};
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+use futures_util::future::BoxFuture;
 use tauri::AppHandle;
 use tauri_plugin_shell::ShellExt;
 
@@ -9,53 +16,26 @@ pub struct SoundCloudDownloader;
 
 impl SoundCloudDownloader {
     /// Download track from SoundCloud using yt-dlp
-    pub async fn download_track(
-        app: &AppHandle,
-        url: &str,
-        base_output_dir: &str,
-        download_context: &DownloadContext,
-        job_id: &str,
-        update_status_fn: impl Fn(&str, DownloadStatus, f32, &str),
-        emit_queue_fn: impl Fn(),
-        update_metadata_fn: impl Fn(TrackMetadata),
-    ) -> Result<String, String> {
-        use tauri_plugin_shell::ShellExt;
-
+    pub async fn download_track(request: DownloadRequest<'_>) -> AppResult<String> {
+        let DownloadRequest { app, url, base_output_dir, download_context, job_id, update_status, emit_queue, update_metadata } = request;
         println!("[SoundCloud] Starting download for URL: {}", url);
 
         // Step 1: Get metadata
-        update_status_fn(job_id, DownloadStatus::Downloading, 5.0, "Fetching metadata...");
-        emit_queue_fn();
-
-        let sidecar = app.shell().sidecar("yt-dlp")
-            .map_err(|e| format!("Failed to get yt-dlp sidecar: {}", e))?;
+        update_status(job_id, DownloadStatus::Downloading, 5.0, "Fetching metadata...");
+        emit_queue();
 
-        let (mut rx, _child) = sidecar
-            .args(["--dump-json", "--no-download", url])
-            .spawn()
-            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
-
-        let mut json_output = String::new();
-        while let Some(event) = rx.recv().await {
-            match event {
-                tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
-                    json_output.push_str(&String::from_utf8_lossy(&line));
-                }
-                tauri_plugin_shell::process::CommandEvent::Terminated(_) => break,
-                _ => {}
-            }
+        let mut metadata = Self::fetch_metadata(app, url).await?;
+        if let Ok(job) = crate::download::QueueManager::get_job(job_id) {
+            metadata.fill_missing(&job.metadata);
         }
 
-        // Parse metadata
-        let mut metadata = Self::parse_soundcloud_metadata(&json_output);
-
         // Transliterate if English Only mode is enabled (BEFORE calculating path)
         metadata = crate::download::transliteration::transliterate_if_needed(&metadata)
             .await
             .unwrap_or(metadata);
 
-        update_metadata_fn(metadata.clone());
-        emit_queue_fn();
+        update_metadata(metadata.clone());
+        emit_queue();
 
         println!(
             "[SoundCloud] Title: '{}', Artist: '{}'",
@@ -68,140 +48,142 @@ impl SoundCloudDownloader {
             &metadata,
             download_context,
         );
+        // Apply the duplicate policy if this track is already on disk
+        let output_path = crate::download::dedup::resolve_output_path(job_id, output_path)?;
         let output_dir = output_path.parent().unwrap().to_string_lossy().to_string();
 
         std::fs::create_dir_all(&output_dir)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+            .or_error(ErrorKind::Io, "Failed to create directory")?;
 
-        let output_template = format!("{}/%(title)s.%(ext)s", output_dir);
+        let output_template = crate::utils::filesystem::ytdlp_output_template(&output_path);
 
         // Step 3: Download with yt-dlp
-        let sidecar = app.shell().sidecar("yt-dlp")
-            .map_err(|e| format!("Failed to get yt-dlp sidecar: {}", e))?;
-
-        let args: Vec<&str> = vec![
-            url,
-            "-f", "bestaudio",
-            "--extract-audio",
-            "--audio-format", "mp3",
-            "--audio-quality", "0",
-            "--embed-thumbnail",
-            "--add-metadata",
-            "--output", &output_template,
-            "--progress",
-            "--newline",
+        let audio_format = crate::utils::get_audio_format();
+        let mut args: Vec<&str> = vec![url, "-f", "bestaudio"];
+        args.extend(audio_format.ytdlp_args());
+        args.extend([
+            "--output", output_template.as_str(),
             "--no-warnings",
-        ];
+        ]);
 
-        let (mut rx, _child) = sidecar.args(args).spawn()
-            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+        let output = YtDlpRunner::download_audio(app, job_id, &args, (10.0, 90.0), &update_status, &emit_queue).await?;
 
-        update_status_fn(job_id, DownloadStatus::Downloading, 10.0, "Downloading...");
+        // Return actual path if captured, otherwise look for the calculated path with any audio extension
+        let final_path = output.output_path.unwrap_or_else(|| {
+            crate::utils::filesystem::find_audio_file(&output_path)
+                .unwrap_or(output_path)
+                .to_string_lossy()
+                .to_string()
+        });
+        println!("[SoundCloud] Returning output path: {}", final_path);
+        Ok(final_path)
+    }
 
-        // Step 4: Listen to progress and capture actual filename
-        let mut last_progress: f32 = 10.0;
-        let mut actual_output_path: Option<String> = None;
+    /// Whether a link lists several tracks rather than being a single track
+    /// - soundcloud.com/artist/sets/name    (set / album)
+    /// - soundcloud.com/artist              (all of an artist's tracks)
+    /// - soundcloud.com/artist/tracks|likes|reposts|albums|popular-tracks
+    fn is_collection_url(url: &str) -> bool {
+        // Unresolved on.soundcloud.com/<code> short links are treated as single tracks
+        parse_link(url).is_some_and(|link| link.kind.is_collection())
+    }
 
-        while let Some(event) = rx.recv().await {
-            match event {
-                tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
-                    let line_str = String::from_utf8_lossy(&line).to_string();
-                    println!("[yt-dlp] {}", line_str);
-
-                    // Capture actual filename from yt-dlp output
-                    // Can be: [ExtractAudio] Destination: /path/file.mp3
-                    // Or: [ExtractAudio] Not converting audio /path/file.mp3
-                    // Or: [Merger] Merging formats into "/path/file.mp3"
-                    if line_str.contains("[ExtractAudio]") || line_str.contains("[Merger]") {
-                        // Extract path from quotes or after "audio "
-                        if let Some(quoted) = line_str.split('"').nth(1) {
-                            if quoted.ends_with(".mp3") {
-                                actual_output_path = Some(quoted.to_string());
-                                println!("[SoundCloud] Captured actual output from quotes: {}", quoted);
-                            }
-                        } else if line_str.contains("audio /") {
-                            if let Some(path_part) = line_str.split("audio ").nth(1) {
-                                let path = path_part.split(';').next().unwrap_or(path_part).trim();
-                                if path.ends_with(".mp3") {
-                                    actual_output_path = Some(path.to_string());
-                                    println!("[SoundCloud] Captured actual output from audio: {}", path);
-                                }
-                            }
-                        } else if line_str.contains("Destination:") {
-                            if let Some(path_str) = line_str.split("Destination:").nth(1) {
-                                let path = path_str.trim();
-                                if path.ends_with(".mp3") {
-                                    actual_output_path = Some(path.to_string());
-                                    println!("[SoundCloud] Captured actual output from Destination: {}", path);
-                                }
-                            }
-                        }
-                    }
-
-                    // Parse progress from yt-dlp output
-                    if let Some(pct) = Self::parse_progress(&line_str) {
-                        last_progress = pct * 0.9;
-                        update_status_fn(
-                            job_id,
-                            DownloadStatus::Downloading,
-                            last_progress,
-                            &format!("Downloading... {:.1}%", pct),
-                        );
-                        emit_queue_fn();
-                    }
-
-                    if line_str.contains("[ExtractAudio]") || line_str.contains("[Merger]") {
-                        update_status_fn(job_id, DownloadStatus::Converting, 92.0, "Converting to MP3...");
-                        emit_queue_fn();
-                    }
-                }
-                tauri_plugin_shell::process::CommandEvent::Stderr(line) => {
-                    let line_str = String::from_utf8_lossy(&line).to_string();
-                    eprintln!("[yt-dlp stderr] {}", line_str);
-                }
-                tauri_plugin_shell::process::CommandEvent::Error(error) => {
-                    update_status_fn(
-                        job_id,
-                        DownloadStatus::Error,
-                        last_progress,
-                        &format!("Error: {}", error),
-                    );
-                    return Err(format!("yt-dlp error: {}", error));
-                }
-                tauri_plugin_shell::process::CommandEvent::Terminated(payload) => {
-                    if payload.code != Some(0) {
-                        let error_msg = format!("yt-dlp exited with code: {:?}", payload.code);
-                        update_status_fn(job_id, DownloadStatus::Error, last_progress, &error_msg);
-                        return Err(error_msg);
-                    }
-                    break;
+    /// Expand a set or a user's track list into one job per track, numbered in list order
+    /// A user's sets/albums page lists sets rather than tracks - each set is expanded in turn
+    pub async fn expand_playlist(app: &AppHandle, url: &str) -> AppResult<Collection> {
+        println!("[SoundCloud] Processing playlist: {}", url);
+
+        let playlist = YtDlpRunner::flat_playlist(app, url).await?;
+        let playlist_name = playlist.title.unwrap_or_else(|| "SoundCloud Playlist".to_string());
+        let (set_urls, tracks) = Self::split_sets(playlist.entries);
+
+        let mut jobs = Self::playlist_jobs(&playlist_name, tracks);
+        for set_url in set_urls {
+            match YtDlpRunner::flat_playlist(app, &set_url).await {
+                Ok(set) => {
+                    let set_name = set.title.unwrap_or_else(|| playlist_name.clone());
+                    // Sets hold tracks only; anything else is skipped rather than followed further
+                    let (_, set_tracks) = Self::split_sets(set.entries);
+                    println!("[SoundCloud] Set: '{}' ({} tracks)", set_name, set_tracks.len());
+                    jobs.extend(Self::playlist_jobs(&set_name, set_tracks));
                 }
-                _ => {}
+                Err(e) => println!("[SoundCloud] Failed to expand set {}: {}", set_url, e),
             }
         }
 
-        update_status_fn(job_id, DownloadStatus::Complete, 100.0, "Download complete!");
-        emit_queue_fn();
+        if jobs.is_empty() {
+            return Err(AppError::new(ErrorKind::NotFound, "No tracks found on SoundCloud page"));
+        }
+        println!("[SoundCloud] Playlist: '{}' ({} tracks)", playlist_name, jobs.len());
 
-        // Return actual path if captured, otherwise use calculated path
-        let final_path = actual_output_path.unwrap_or_else(|| output_path.to_string_lossy().to_string());
-        println!("[SoundCloud] Returning output path: {}", final_path);
-        Ok(final_path)
+        Ok(Collection {
+            name: playlist_name,
+            jobs,
+        })
     }
 
-    /// Parse yt-dlp progress output
-    fn parse_progress(line: &str) -> Option<f32> {
-        if line.contains("[download]") && line.contains("%") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            for part in parts {
-                if part.ends_with('%') {
-                    if let Ok(pct) = part.trim_end_matches('%').parse::<f32>() {
-                        return Some(pct);
-                    }
+    /// Split listed entries into set links and track entries (entries without a link are dropped)
+    fn split_sets(entries: Vec<FlatEntry>) -> (Vec<String>, Vec<FlatEntry>) {
+        let mut set_urls = Vec::new();
+        let mut tracks = Vec::new();
+        for entry in entries {
+            let Some(url) = entry.url.as_deref() else {
+                continue;
+            };
+            match parse_link(url) {
+                Some(link) if link.service == MusicService::SoundCloud && link.kind == LinkKind::Playlist => {
+                    set_urls.push(link.url)
                 }
+                _ => tracks.push(entry),
             }
         }
-        None
+        (set_urls, tracks)
+    }
+
+    /// One job per track entry, numbered in list order and grouped under the playlist's name
+    fn playlist_jobs(playlist_name: &str, tracks: Vec<FlatEntry>) -> Vec<DownloadJob> {
+        let playlist_context = DownloadContext::Playlist(playlist_name.to_string());
+        let track_total = tracks.len() as u32;
+        tracks
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                let mut job = DownloadJob::new(entry.url?);
+                if let Some(title) = entry.title {
+                    job.metadata.title = title;
+                }
+                job.metadata.track_number = Some(index as u32 + 1);
+                job.metadata.track_total = Some(track_total);
+                job.download_context = Some(playlist_context.clone());
+                Some(job)
+            })
+            .collect()
+    }
+
+    /// Fetch a track's metadata with yt-dlp (without downloading it)
+    pub async fn fetch_metadata(app: &AppHandle, url: &str) -> AppResult<TrackMetadata> {
+        let _search_permit = DownloadResource::YtDlpSearch.acquire().await;
+
+        let sidecar = app.shell().sidecar("yt-dlp")
+            .or_error(ErrorKind::DependencyMissing, "Failed to get yt-dlp sidecar")?;
+
+        let (mut rx, _child) = sidecar
+            .args(["--dump-json", "--no-download", url])
+            .spawn()
+            .or_error(ErrorKind::DependencyMissing, "Failed to spawn yt-dlp")?;
+
+        let mut json_output = String::new();
+        while let Some(event) = rx.recv().await {
+            match event {
+                tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
+                    json_output.push_str(&String::from_utf8_lossy(&line));
+                }
+                tauri_plugin_shell::process::CommandEvent::Terminated(_) => break,
+                _ => {}
+            }
+        }
+
+        Ok(Self::parse_soundcloud_metadata(&json_output))
     }
 
     /// Parse SoundCloud metadata from yt-dlp JSON output
@@ -218,9 +200,94 @@ impl SoundCloudDownloader {
                 album: "SoundCloud".to_string(),
                 duration: json.get("duration").and_then(|v| v.as_u64()).map(|d| d as u32),
                 thumbnail: json.get("thumbnail").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                release_date: YouTubeDownloader::parse_ytdlp_date(&json, "upload_date"),
+                genre: json.get("genre")
+                    .and_then(|v| v.as_str())
+                    .filter(|g| !g.is_empty())
+                    .map(|g| g.to_string()),
+                source_ids: crate::download::SourceIds {
+                    soundcloud: json.get("id").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                    ..Default::default()
+                },
+                ..TrackMetadata::default()
             }
         } else {
             TrackMetadata::default()
         }
     }
 }
+
+impl MusicSource for SoundCloudDownloader {
+    fn service(&self) -> MusicService {
+        MusicService::SoundCloud
+    }
+
+    fn matches_url(&self, url: &str) -> bool {
+        host_matches(url, &["soundcloud.com"])
+    }
+
+    fn is_collection(&self, url: &str) -> bool {
+        Self::is_collection_url(url)
+    }
+
+    fn resolve_metadata<'a>(&'a self, app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<TrackMetadata>> {
+        Box::pin(Self::fetch_metadata(app, url))
+    }
+
+    fn expand_collection<'a>(&'a self, app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<Collection>> {
+        Box::pin(Self::expand_playlist(app, url))
+    }
+
+    fn download<'a>(&'a self, request: DownloadRequest<'a>) -> BoxFuture<'a, AppResult<String>> {
+        Box::pin(Self::download_track(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_collection_url() {
+        for url in [
+            "https://soundcloud.com/artist/sets/summer-ep",
+            "https://soundcloud.com/artist",
+            "https://soundcloud.com/artist/tracks",
+            "https://soundcloud.com/artist/likes",
+        ] {
+            assert!(SoundCloudDownloader::is_collection_url(url), "{}", url);
+        }
+        for url in [
+            "https://soundcloud.com/artist/track-name",
+            "https://soundcloud.com/artist/track-name?in=artist/sets/summer-ep",
+        ] {
+            assert!(!SoundCloudDownloader::is_collection_url(url), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_sets_page_expands_each_set() {
+        use crate::download::ytdlp::FlatPlaylist;
+
+        let mut page = FlatPlaylist::default();
+        for line in include_str!("testdata/soundcloud_sets.jsonl").lines() {
+            page.push_line(line);
+        }
+        let (set_urls, tracks) = SoundCloudDownloader::split_sets(page.entries);
+        assert_eq!(
+            set_urls,
+            vec!["https://soundcloud.com/artist/sets/summer-ep", "https://soundcloud.com/artist/sets/live-at-barby"]
+        );
+        assert!(tracks.is_empty());
+
+        let set = vec![
+            FlatEntry { id: None, url: Some("https://soundcloud.com/artist/first".to_string()), title: Some("First".to_string()) },
+            FlatEntry { id: None, url: Some("https://soundcloud.com/artist/second".to_string()), title: None },
+        ];
+        let jobs = SoundCloudDownloader::playlist_jobs("Summer EP", set);
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].metadata.title, "First");
+        assert_eq!(jobs[1].metadata.track_number, Some(2));
+        assert_eq!(jobs[1].download_context, Some(DownloadContext::Playlist("Summer EP".to_string())));
+    }
+}
@@ -1,10 +1,18 @@
 // Spotify download service
 
 use base64::Engine;
+use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
 
 use crate::api_types::{HasodApiClient, SpotifyTrackMetadata};
+use crate::download::control::{JobControl, JobSignal};
+use crate::download::links::{parse_link, LinkKind};
+use crate::download::services::registry::{host_matches, Collection, DownloadRequest, MusicSource};
+use crate::download::ytdlp::YtDlpRunner;
+use crate::download::{split_artists, DownloadContext, DownloadJob, MusicService, SourceIds, TrackMetadata};
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
 
 // Spotify API credentials (loaded from environment at compile time)
 pub const SPOTIFY_CLIENT_ID_DEFAULT: &str = "c6b23f1e91f84b6a9361de16aba0ae17";
@@ -12,6 +20,13 @@ pub const SPOTIFY_CLIENT_SECRET_DEFAULT: &str = "237e355acaa24636abc79f1a089e620
 pub const SPOTIFY_CLIENT_ID: Option<&str> = option_env!("HASOD_SPOTIFY_CLIENT_ID");
 pub const SPOTIFY_CLIENT_SECRET: Option<&str> = option_env!("HASOD_SPOTIFY_CLIENT_SECRET");
 
+// Country for market-specific lookups like an artist's top tracks. Client-credentials tokens have
+// no user, so market=from_token isn't available.
+pub const SPOTIFY_MARKET: &str = match option_env!("HASOD_SPOTIFY_MARKET") {
+    Some(market) => market,
+    None => "IL",
+};
+
 // Cached Spotify token (access_token, expires_at)
 static SPOTIFY_TOKEN_CACHE: std::sync::LazyLock<Arc<Mutex<Option<(String, i64)>>>> =
     std::sync::LazyLock::new(|| Arc::new(Mutex::new(None)));
@@ -30,6 +45,66 @@ pub struct SpotifyTrackInfo {
     pub duration_ms: Option<u64>,  // Track duration in milliseconds for verification
 }
 
+/// A release listed on an artist's page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotifyRelease {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub album_type: String,   // "album", "single" or "compilation"
+    pub album_group: String,  // How the artist relates to it: album_type, or "appears_on"
+    pub artist: String,
+    pub release_date: String,
+    pub total_tracks: u32,
+    pub image_url: Option<String>,
+}
+
+/// Which releases to list for an artist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArtistReleaseFilter {
+    pub albums: bool,
+    pub singles: bool,
+    pub compilations: bool,
+    pub appears_on: bool,  // Releases by other artists the artist features on
+}
+
+impl Default for ArtistReleaseFilter {
+    fn default() -> Self {
+        Self {
+            albums: true,
+            singles: true,
+            compilations: false,
+            appears_on: false,
+        }
+    }
+}
+
+impl ArtistReleaseFilter {
+    /// Value of the Web API's include_groups parameter
+    fn include_groups(&self) -> String {
+        [
+            (self.albums, "album"),
+            (self.singles, "single"),
+            (self.compilations, "compilation"),
+            (self.appears_on, "appears_on"),
+        ]
+        .iter()
+        .filter(|(included, _)| *included)
+        .map(|(_, group)| *group)
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// An artist's releases, for the user to choose from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotifyArtistReleases {
+    pub artist_id: String,
+    pub artist_name: String,
+    pub releases: Vec<SpotifyRelease>,
+}
+
 // ============================================================================
 // Spotify Downloader
 // ============================================================================
@@ -38,13 +113,15 @@ pub struct SpotifyDownloader;
 
 impl SpotifyDownloader {
     /// Get Spotify access token using Client Credentials flow
-    pub async fn get_access_token() -> Result<String, String> {
-        let client_id = SPOTIFY_CLIENT_ID.ok_or("Spotify Client ID not configured")?;
-        let client_secret = SPOTIFY_CLIENT_SECRET.ok_or("Spotify Client Secret not configured")?;
+    pub async fn get_access_token() -> AppResult<String> {
+        let client_id = SPOTIFY_CLIENT_ID
+            .ok_or_else(|| AppError::new(ErrorKind::DependencyMissing, "Spotify Client ID not configured"))?;
+        let client_secret = SPOTIFY_CLIENT_SECRET
+            .ok_or_else(|| AppError::new(ErrorKind::DependencyMissing, "Spotify Client Secret not configured"))?;
 
         // Check cache first
         {
-            let cache = SPOTIFY_TOKEN_CACHE.lock().map_err(|e| format!("Lock error: {}", e))?;
+            let cache = SPOTIFY_TOKEN_CACHE.lock()?;
             if let Some((token, expires_at)) = cache.as_ref() {
                 let now = chrono::Utc::now().timestamp();
                 if *expires_at > now + 60 {  // 60 second buffer
@@ -68,11 +145,12 @@ impl SpotifyDownloader {
             .body("grant_type=client_credentials")
             .send()
             .await
-            .map_err(|e| format!("Spotify token request failed: {}", e))?;
+            .or_error(ErrorKind::Network, "Spotify token request failed")?;
 
-        if !response.status().is_success() {
+        let status = response.status();
+        if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(format!("Spotify token request failed: {}", error_text));
+            return Err(AppError::http(status, format!("Spotify token request failed: {}", error_text)));
         }
 
         #[derive(Deserialize)]
@@ -84,12 +162,12 @@ impl SpotifyDownloader {
         let token_data: TokenResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse Spotify token response: {}", e))?;
+            .or_error(ErrorKind::Parse, "Failed to parse Spotify token response")?;
 
         // Cache the token
         let expires_at = chrono::Utc::now().timestamp() + token_data.expires_in;
         {
-            let mut cache = SPOTIFY_TOKEN_CACHE.lock().map_err(|e| format!("Lock error: {}", e))?;
+            let mut cache = SPOTIFY_TOKEN_CACHE.lock()?;
             *cache = Some((token_data.access_token.clone(), expires_at));
         }
 
@@ -98,31 +176,20 @@ impl SpotifyDownloader {
     }
 
     /// Extract track ID from Spotify URL
+    /// - https://open.spotify.com/track/6rqhFgbbKwnb9MLmUQDhG6?si=xxx
+    /// - https://open.spotify.com/intl-he/track/6rqhFgbbKwnb9MLmUQDhG6
+    /// - spotify:track:6rqhFgbbKwnb9MLmUQDhG6
     pub fn extract_track_id(url: &str) -> Option<String> {
-        // Handle URLs like:
-        // - https://open.spotify.com/track/6rqhFgbbKwnb9MLmUQDhG6
-        // - https://open.spotify.com/track/6rqhFgbbKwnb9MLmUQDhG6?si=xxx
-        // - spotify:track:6rqhFgbbKwnb9MLmUQDhG6
-
-        if url.starts_with("spotify:track:") {
-            return Some(url.replace("spotify:track:", ""));
-        }
-
-        if url.contains("/track/") {
-            let parts: Vec<&str> = url.split("/track/").collect();
-            if parts.len() > 1 {
-                // Remove query string if present
-                let id_part = parts[1].split('?').next().unwrap_or(parts[1]);
-                return Some(id_part.to_string());
-            }
-        }
+        Self::extract_id(url, LinkKind::Track)
+    }
 
-        None
+    fn extract_id(url: &str, kind: LinkKind) -> Option<String> {
+        parse_link(url)?.id_of(MusicService::Spotify, kind).map(|id| id.to_string())
     }
 
     /// Get Spotify track metadata from our backend API
     /// Uses Groover API (primary) + ISRC Finder (fallback) for complete metadata
-    pub async fn get_metadata_from_api(url: &str) -> Result<SpotifyTrackMetadata, String> {
+    pub async fn get_metadata_from_api(url: &str) -> AppResult<SpotifyTrackMetadata> {
         println!("[Spotify API] Fetching metadata from backend...");
 
         let api_client = HasodApiClient::production();
@@ -134,8 +201,352 @@ impl SpotifyDownloader {
         Ok(metadata)
     }
 
+    /// Track metadata from the backend API response
+    pub fn track_metadata(metadata: &SpotifyTrackMetadata) -> TrackMetadata {
+        TrackMetadata {
+            title: metadata.name.clone(),
+            artist: metadata.artist.clone(),
+            album: metadata.album.clone(),
+            duration: Some(metadata.duration_ms / 1000),
+            thumbnail: Some(metadata.image_url.clone()),
+            artists: split_artists(&metadata.artist),
+            release_date: Some(metadata.release_date.clone()).filter(|date| !date.is_empty()),
+            isrc: Some(metadata.isrc.clone()),
+            source_ids: SourceIds {
+                spotify: Some(metadata.track_id.clone()),
+                ..SourceIds::default()
+            },
+            ..TrackMetadata::default()
+        }
+    }
+
+    /// Fill in what the backend metadata doesn't carry (explicit flag, genre, track and disc numbers,
+    /// album artist) from the Web API. Without Spotify credentials the metadata is returned as is.
+    pub async fn add_web_api_details(mut metadata: TrackMetadata) -> TrackMetadata {
+        let Some(track_id) = metadata.source_ids.spotify.clone() else {
+            return metadata;
+        };
+        match Self::web_api_details(&track_id).await {
+            Ok(details) => metadata.fill_missing(&details),
+            Err(e) => println!("[Spotify API] Track details unavailable: {}", e),
+        }
+        metadata
+    }
+
+    async fn web_api_details(track_id: &str) -> AppResult<TrackMetadata> {
+        let track = Self::web_api_get(&format!("/tracks/{}", track_id)).await?;
+        // Spotify only tags artists with genres, not tracks
+        let artist = match track.pointer("/artists/0/id").and_then(|v| v.as_str()) {
+            Some(artist_id) => Self::web_api_get(&format!("/artists/{}", artist_id)).await?,
+            None => serde_json::Value::Null,
+        };
+        Ok(Self::track_details(&track, &artist))
+    }
+
+    /// Details from a Web API track object and its first artist
+    fn track_details(track: &serde_json::Value, artist: &serde_json::Value) -> TrackMetadata {
+        let number = |key: &str| track.get(key).and_then(|v| v.as_u64()).map(|n| n as u32);
+        let release = track.get("album").and_then(Self::parse_release);
+        TrackMetadata {
+            explicit: track.get("explicit").and_then(|v| v.as_bool()),
+            genre: artist.pointer("/genres/0").and_then(|v| v.as_str()).map(|g| g.to_string()),
+            track_number: number("track_number"),
+            disc_number: number("disc_number"),
+            track_total: release.as_ref().map(|r| r.total_tracks).filter(|n| *n > 0),
+            album_artist: release.map(|r| r.artist).filter(|a| !a.is_empty()),
+            ..TrackMetadata::default()
+        }
+    }
+
+    /// A job for a track of an expanded album or playlist
+    fn track_job(metadata: TrackMetadata, context: &DownloadContext) -> DownloadJob {
+        let track_id = metadata.source_ids.spotify.clone().unwrap_or_default();
+        let mut job = DownloadJob::new(format!("https://open.spotify.com/track/{}", track_id));
+        job.isrc = metadata.isrc.clone();
+        job.metadata = metadata;
+        job.download_context = Some(context.clone());
+        job
+    }
+
+    /// Expand an album into one job per track (album context, with track numbers)
+    pub async fn expand_album(album_url: &str) -> AppResult<Collection> {
+        println!("[Album] Processing Spotify album: {}", album_url);
+
+        let api_client = HasodApiClient::production();
+        let album_metadata = api_client.get_spotify_album_metadata(album_url).await?;
+
+        println!("[Album] Album: '{}' by '{}' ({} tracks)",
+                 album_metadata.album.name,
+                 album_metadata.album.artist,
+                 album_metadata.tracks.len());
+
+        let album_context = DownloadContext::Album(album_metadata.album.name.clone());
+        let jobs = album_metadata
+            .tracks
+            .iter()
+            .map(|track| {
+                let mut metadata = Self::track_metadata(&SpotifyTrackMetadata::from(track));
+                metadata.album_artist = Some(album_metadata.album.artist.clone());
+                metadata.track_number = Some(track.position);
+                metadata.track_total = Some(album_metadata.album.total_tracks);
+                Self::track_job(metadata, &album_context)
+            })
+            .collect();
+
+        Ok(Collection {
+            name: album_metadata.album.name,
+            jobs,
+        })
+    }
+
+    /// Expand a playlist into one job per track (playlist context)
+    pub async fn expand_playlist(playlist_url: &str) -> AppResult<Collection> {
+        println!("[Playlist] Processing Spotify playlist: {}", playlist_url);
+
+        let api_client = HasodApiClient::production();
+        let playlist_metadata = api_client.get_spotify_playlist_metadata(playlist_url).await?;
+
+        println!("[Playlist] Playlist: '{}' by '{}' ({} tracks)",
+                 playlist_metadata.playlist.name,
+                 playlist_metadata.playlist.owner,
+                 playlist_metadata.tracks.len());
+
+        // track.position is the playlist position, not the track number on its album
+        let playlist_context = DownloadContext::Playlist(playlist_metadata.playlist.name.clone());
+        let jobs = playlist_metadata
+            .tracks
+            .iter()
+            .map(|track| Self::track_job(Self::track_metadata(&SpotifyTrackMetadata::from(track)), &playlist_context))
+            .collect();
+
+        Ok(Collection {
+            name: playlist_metadata.playlist.name,
+            jobs,
+        })
+    }
+
+    /// Extract artist ID from Spotify URL
+    /// - https://open.spotify.com/artist/0TnOYISbd1XYRBk9myaseg
+    /// - https://open.spotify.com/intl-de/artist/0TnOYISbd1XYRBk9myaseg?si=xxx
+    /// - spotify:artist:0TnOYISbd1XYRBk9myaseg
+    pub fn extract_artist_id(url: &str) -> Option<String> {
+        Self::extract_id(url, LinkKind::Artist)
+    }
+
+    /// GET a Spotify Web API endpoint (path like "/artists/ID" or a full "next" URL)
+    async fn web_api_get(path: &str) -> AppResult<serde_json::Value> {
+        let token = Self::get_access_token().await?;
+        let url = if path.starts_with("https://") {
+            path.to_string()
+        } else {
+            format!("https://api.spotify.com/v1{}", path)
+        };
+
+        let client = reqwest::Client::new();
+        let response = client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .or_error(ErrorKind::Network, "Spotify API request failed")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::http(status, format!("Spotify API error: {}", error_text)));
+        }
+
+        response
+            .json()
+            .await
+            .or_error(ErrorKind::Parse, "Failed to parse Spotify API response")
+    }
+
+    /// "Artist A, Artist B" from a Web API artists array
+    fn join_artist_names(json: &serde_json::Value) -> String {
+        json.get("artists")
+            .and_then(|v| v.as_array())
+            .map(|artists| {
+                artists.iter()
+                    .filter_map(|a| a.get("name").and_then(|n| n.as_str()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Release info from a Web API (simplified) album object
+    fn parse_release(album: &serde_json::Value) -> Option<SpotifyRelease> {
+        let text = |key: &str| album.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let id = text("id")?;
+        let album_type = text("album_type").unwrap_or_else(|| "album".to_string());
+        Some(SpotifyRelease {
+            url: format!("https://open.spotify.com/album/{}", id),
+            id,
+            name: text("name").unwrap_or_else(|| "Unknown Album".to_string()),
+            album_group: text("album_group").unwrap_or_else(|| album_type.clone()),
+            album_type,
+            artist: Self::join_artist_names(album),
+            release_date: text("release_date").unwrap_or_default(),
+            total_tracks: album.get("total_tracks").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
+            // Images are sorted largest first
+            image_url: album.get("images")
+                .and_then(|v| v.as_array())
+                .and_then(|images| images.first())
+                .and_then(|image| image.get("url"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+        })
+    }
+
+    /// List an artist's releases (newest first) so the user can pick which to download
+    pub async fn get_artist_releases(artist_url: &str, filter: &ArtistReleaseFilter) -> AppResult<SpotifyArtistReleases> {
+        let artist_id = Self::extract_artist_id(artist_url)
+            .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, "Could not extract Spotify artist ID"))?;
+        let include_groups = filter.include_groups();
+        if include_groups.is_empty() {
+            return Err(AppError::new(ErrorKind::InvalidInput, "Select at least one kind of release"));
+        }
+
+        let artist = Self::web_api_get(&format!("/artists/{}", artist_id)).await?;
+        let artist_name = artist.get("name").and_then(|v| v.as_str()).unwrap_or("Unknown Artist").to_string();
+        println!("[Spotify] Listing releases of '{}' ({})", artist_name, include_groups);
+
+        let mut releases = Vec::new();
+        let mut next = Some(format!("/artists/{}/albums?include_groups={}&limit=50", artist_id, include_groups));
+        while let Some(page_url) = next {
+            let page = Self::web_api_get(&page_url).await?;
+            if let Some(items) = page.get("items").and_then(|v| v.as_array()) {
+                releases.extend(items.iter().filter_map(Self::parse_release));
+            }
+            next = page.get("next").and_then(|v| v.as_str()).map(|s| s.to_string());
+        }
+
+        releases.sort_by(|a, b| b.release_date.cmp(&a.release_date));
+        println!("[Spotify] Found {} releases", releases.len());
+
+        Ok(SpotifyArtistReleases {
+            artist_id,
+            artist_name,
+            releases,
+        })
+    }
+
+    /// Expand the chosen releases into one job per track (album context per release)
+    /// A track on several releases (single, then album, then a compilation) is only queued once, by ISRC
+    pub async fn expand_releases(name: &str, release_urls: &[String]) -> AppResult<Collection> {
+        let mut seen_isrcs = std::collections::HashSet::new();
+        let mut jobs = Vec::new();
+        let mut skipped = 0;
+
+        for release_url in release_urls {
+            let release = match Self::expand_album(release_url).await {
+                Ok(release) => release,
+                Err(e) => {
+                    eprintln!("[Spotify] Skipping release {}: {}", release_url, e);
+                    continue;
+                }
+            };
+            for job in release.jobs {
+                let isrc = job.isrc.clone().filter(|isrc| !isrc.is_empty());
+                if let Some(isrc) = isrc {
+                    if !seen_isrcs.insert(isrc.to_uppercase()) {
+                        skipped += 1;
+                        continue;
+                    }
+                }
+                jobs.push(job);
+            }
+        }
+
+        if jobs.is_empty() {
+            return Err(AppError::new(ErrorKind::NotFound, "No tracks found on the selected releases"));
+        }
+        println!("[Spotify] {} tracks from {} releases ({} duplicates skipped)", jobs.len(), release_urls.len(), skipped);
+
+        Ok(Collection {
+            name: name.to_string(),
+            jobs,
+        })
+    }
+
+    /// An artist's most popular tracks, each queued in the context of its album
+    pub async fn expand_top_tracks(artist_url: &str) -> AppResult<Collection> {
+        let artist_id = Self::extract_artist_id(artist_url)
+            .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, "Could not extract Spotify artist ID"))?;
+
+        // The endpoint requires a market; popularity is per country
+        let json = Self::web_api_get(&format!("/artists/{}/top-tracks?market={}", artist_id, SPOTIFY_MARKET)).await?;
+        let tracks = json.get("tracks")
+            .and_then(|v| v.as_array())
+            .filter(|tracks| !tracks.is_empty())
+            .ok_or_else(|| AppError::new(ErrorKind::NotFound, "No top tracks found for artist"))?;
+
+        let mut seen_isrcs = std::collections::HashSet::new();
+        let mut jobs = Vec::new();
+        for track in tracks {
+            let text = |value: &serde_json::Value, key: &str| value.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+            let Some(track_id) = text(track, "id") else {
+                continue;
+            };
+            let isrc = track.get("external_ids").and_then(|ids| text(ids, "isrc"));
+            if let Some(isrc) = &isrc {
+                if !seen_isrcs.insert(isrc.to_uppercase()) {
+                    continue;
+                }
+            }
+
+            let album = track.get("album").cloned().unwrap_or_default();
+            let release = Self::parse_release(&album);
+            let artist = Self::join_artist_names(track);
+            let album_name = text(&album, "name").unwrap_or_else(|| "Unknown Album".to_string());
+
+            let mut job = DownloadJob::new(format!("https://open.spotify.com/track/{}", track_id));
+            job.metadata = TrackMetadata {
+                title: text(track, "name").unwrap_or_else(|| "Unknown".to_string()),
+                artists: split_artists(&artist),
+                artist,
+                album: album_name.clone(),
+                duration: track.get("duration_ms").and_then(|v| v.as_u64()).map(|ms| (ms / 1000) as u32),
+                thumbnail: release.as_ref().and_then(|r| r.image_url.clone()),
+                album_artist: release.as_ref().map(|r| r.artist.clone()).filter(|a| !a.is_empty()),
+                track_number: track.get("track_number").and_then(|v| v.as_u64()).map(|n| n as u32),
+                disc_number: track.get("disc_number").and_then(|v| v.as_u64()).map(|n| n as u32),
+                track_total: release.as_ref().map(|r| r.total_tracks).filter(|n| *n > 0),
+                release_date: release.map(|r| r.release_date).filter(|date| !date.is_empty()),
+                isrc: isrc.clone(),
+                explicit: track.get("explicit").and_then(|v| v.as_bool()),
+                source_ids: SourceIds {
+                    spotify: Some(track_id),
+                    ..SourceIds::default()
+                },
+                ..TrackMetadata::default()
+            };
+            job.isrc = isrc;
+            job.download_context = Some(DownloadContext::Album(album_name));
+            jobs.push(job);
+        }
+
+        let artist_name = Self::artist_name_in_tracks(tracks, &artist_id).unwrap_or("Artist");
+        Ok(Collection {
+            name: format!("{} - Top Tracks", artist_name),
+            jobs,
+        })
+    }
+
+    /// The artist's name as credited on their tracks (saves a request for the artist itself)
+    fn artist_name_in_tracks<'a>(tracks: &'a [serde_json::Value], artist_id: &str) -> Option<&'a str> {
+        tracks
+            .iter()
+            .filter_map(|track| track.get("artists").and_then(|v| v.as_array()))
+            .flatten()
+            .find(|artist| artist.get("id").and_then(|v| v.as_str()) == Some(artist_id))
+            .and_then(|artist| artist.get("name"))
+            .and_then(|v| v.as_str())
+    }
+
     /// Get full track metadata from Spotify Web API
-    pub async fn get_track_from_api(track_id: &str) -> Result<SpotifyTrackInfo, String> {
+    pub async fn get_track_from_api(track_id: &str) -> AppResult<SpotifyTrackInfo> {
         let token = Self::get_access_token().await?;
 
         let client = reqwest::Client::new();
@@ -144,17 +555,18 @@ impl SpotifyDownloader {
             .header("Authorization", format!("Bearer {}", token))
             .send()
             .await
-            .map_err(|e| format!("Spotify API request failed: {}", e))?;
+            .or_error(ErrorKind::Network, "Spotify API request failed")?;
 
-        if !response.status().is_success() {
+        let status = response.status();
+        if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
-            return Err(format!("Spotify API error: {}", error_text));
+            return Err(AppError::http(status, format!("Spotify API error: {}", error_text)));
         }
 
         let json: serde_json::Value = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse Spotify track response: {}", e))?;
+            .or_error(ErrorKind::Parse, "Failed to parse Spotify track response")?;
 
         // Extract track info
         let title = json.get("name")
@@ -210,20 +622,19 @@ impl SpotifyDownloader {
     }
 
     /// Extract Spotify track info - uses Web API if credentials available, falls back to oEmbed scraping
-    pub async fn get_track_info(url: &str) -> Result<(String, String, Option<SpotifyTrackInfo>), String> {
+    pub async fn get_track_info(url: &str) -> AppResult<(String, String, Option<SpotifyTrackInfo>)> {
         // Check if this is a track URL (not artist, album, or playlist)
-        let url_lower = url.to_lowercase();
-        if url_lower.contains("/artist/") {
-            return Err("Artist pages cannot be downloaded. Please use a specific track URL.".to_string());
-        }
-        if url_lower.contains("/album/") {
-            return Err("Album pages are not yet supported. Please use individual track URLs.".to_string());
-        }
-        if url_lower.contains("/playlist/") {
-            return Err("Playlist pages are not yet supported. Please use individual track URLs.".to_string());
-        }
-        if !url_lower.contains("/track/") && !url_lower.contains("spotify:track:") {
-            return Err("Please use a Spotify track URL (e.g., open.spotify.com/track/...).".to_string());
+        match parse_link(url).map(|link| link.kind) {
+            Some(LinkKind::Track) => {}
+            Some(LinkKind::Artist) => {
+                return Err(AppError::new(ErrorKind::Unsupported, "Artist pages must be added as a discography or top tracks, not as a single track."));
+            }
+            Some(LinkKind::Album) | Some(LinkKind::Playlist) => {
+                return Err(AppError::new(ErrorKind::Unsupported, "Album and playlist pages must be added as a collection, not as a single track."));
+            }
+            _ => {
+                return Err(AppError::new(ErrorKind::InvalidInput, "Please use a Spotify track URL (e.g., open.spotify.com/track/...)."));
+            }
         }
 
         // Try Spotify Web API first if credentials are configured
@@ -248,11 +659,11 @@ impl SpotifyDownloader {
     }
 
     /// Fallback method: Get track info by scraping the Spotify embed page
-    async fn get_track_info_from_oembed(url: &str) -> Result<(String, String, Option<SpotifyTrackInfo>), String> {
+    async fn get_track_info_from_oembed(url: &str) -> AppResult<(String, String, Option<SpotifyTrackInfo>)> {
         println!("[Spotify] Scraping embed page for metadata (no API credentials configured)");
 
         let track_id = Self::extract_track_id(url)
-            .ok_or("Could not extract Spotify track ID")?;
+            .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, "Could not extract Spotify track ID"))?;
 
         let embed_url = format!("https://open.spotify.com/embed/track/{}", track_id);
 
@@ -261,14 +672,17 @@ impl SpotifyDownloader {
             .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch Spotify embed page: {}", e))?;
+            .or_error(ErrorKind::Network, "Failed to fetch Spotify embed page")?;
 
         if !response.status().is_success() {
-            return Err(format!("Spotify embed page failed with status: {}", response.status()));
+            return Err(AppError::http(
+                response.status(),
+                format!("Spotify embed page failed with status: {}", response.status()),
+            ));
         }
 
         let html = response.text().await
-            .map_err(|e| format!("Failed to read Spotify embed page: {}", e))?;
+            .or_error(ErrorKind::Network, "Failed to read Spotify embed page")?;
 
         // Extract artist from JSON data in HTML
         let artist = if let Some(artists_start) = html.find("\"artists\":[") {
@@ -324,37 +738,32 @@ impl SpotifyDownloader {
 
     /// Download a Spotify track (tries Deezer first, falls back to YouTube)
     /// Returns the path to the downloaded file
-    pub async fn download_track(
-        app: &tauri::AppHandle,
-        url: &str,
-        base_output_dir: &str,
-        download_context: &crate::download::DownloadContext,
-        job_id: &str,
-        update_status_fn: impl Fn(&str, crate::download::DownloadStatus, f32, &str),
-        emit_queue_fn: impl Fn(),
-        update_metadata_fn: impl Fn(crate::download::TrackMetadata),
-    ) -> Result<String, String> {
+    pub async fn download_track(request: DownloadRequest<'_>) -> AppResult<String> {
         use crate::auth::get_auth_from_keychain;
         use crate::download::services::{DeezerDownloader, YouTubeDownloader};
-        use crate::download::{DownloadStatus, TrackMetadata};
-        use tauri_plugin_shell::ShellExt;
+        use crate::download::DownloadStatus;
+
+        let DownloadRequest { app, url, base_output_dir, download_context, job_id, update_status, emit_queue, update_metadata } = request;
 
         println!("[Spotify] Using backend API for metadata extraction");
 
-        update_status_fn(job_id, DownloadStatus::Downloading, 5.0, "Getting track info...");
-        emit_queue_fn();
+        update_status(job_id, DownloadStatus::Downloading, 5.0, "Getting track info...");
+        emit_queue();
 
         // Step 1: Get metadata from backend API
         let spotify_metadata = Self::get_metadata_from_api(url).await?;
 
+        crate::download::QueueManager::update_job_metadata(job_id, |job| {
+            job.isrc = Some(spotify_metadata.isrc.clone());
+        })?;
+
         // Step 2: Create track metadata
-        let mut track_metadata = TrackMetadata {
-            title: spotify_metadata.name.clone(),
-            artist: spotify_metadata.artist.clone(),
-            album: spotify_metadata.album.clone(),
-            duration: Some((spotify_metadata.duration_ms / 1000) as u32),
-            thumbnail: Some(spotify_metadata.image_url.clone()),
-        };
+        let mut track_metadata = Self::add_web_api_details(Self::track_metadata(&spotify_metadata)).await;
+
+        // Keep album details (track number, album artist...) known when the job was queued
+        if let Ok(job) = crate::download::QueueManager::get_job(job_id) {
+            track_metadata.fill_missing(&job.metadata);
+        }
 
         // Step 2.5: Transliterate if English Only mode is enabled (BEFORE calculating path)
         track_metadata = crate::download::transliteration::transliterate_if_needed(&track_metadata)
@@ -362,7 +771,7 @@ impl SpotifyDownloader {
             .unwrap_or(track_metadata);
 
         // Step 3: Update job metadata (now with transliterated values)
-        update_metadata_fn(track_metadata.clone());
+        update_metadata(track_metadata.clone());
 
         // Step 4: Calculate output path (uses transliterated metadata)
         let output_path = crate::utils::filesystem::get_organized_output_path(
@@ -370,12 +779,14 @@ impl SpotifyDownloader {
             &track_metadata,
             download_context,
         );
+        // Apply the duplicate policy if this track is already on disk
+        let output_path = crate::download::dedup::resolve_output_path(job_id, output_path)?;
         let output_path_str = output_path.to_string_lossy().to_string();
 
         // Step 4: Try Deezer download first
         println!("[Spotify] Attempting Deezer download using ISRC: {}", spotify_metadata.isrc);
-        update_status_fn(job_id, DownloadStatus::Downloading, 10.0, "Trying Deezer...");
-        emit_queue_fn();
+        update_status(job_id, DownloadStatus::Downloading, 10.0, "Trying Deezer...");
+        emit_queue();
 
         let auth_token = get_auth_from_keychain()
             .map(|auth| auth.id_token)
@@ -389,23 +800,24 @@ impl SpotifyDownloader {
                 &spotify_metadata.isrc,
                 &auth_token,
                 &output_path_str,
-                Some(&spotify_metadata.image_url),
                 job_id,
-                &update_status_fn,
-                &emit_queue_fn,
+                &update_status,
+                &emit_queue,
             )
             .await
             {
                 Ok(deezer_file_path) => {
                     println!("[Spotify] ✅ Deezer download successful!");
+                    crate::download::QueueManager::set_job_source(job_id, MusicService::Deezer, None);
                     println!("[Spotify] File ready at: {}", deezer_file_path);
 
-                    update_status_fn(job_id, DownloadStatus::Complete, 100.0, "Download complete");
-                    emit_queue_fn();
-
                     return Ok(deezer_file_path);
                 }
                 Err(e) => {
+                    // Paused/cancelled by the user - don't fall back to YouTube
+                    if JobControl::requested(job_id) != JobSignal::Run {
+                        return Err(e);
+                    }
                     println!("[Spotify] ⚠️ Deezer download failed: {}", e);
                     println!("[Spotify] Falling back to YouTube search...");
                 }
@@ -420,110 +832,150 @@ impl SpotifyDownloader {
             spotify_metadata.artist, spotify_metadata.name, spotify_metadata.album
         );
 
-        update_status_fn(
+        update_status(
             job_id,
             DownloadStatus::Downloading,
             15.0,
             &format!("Searching: {}", spotify_metadata.name),
         );
-        emit_queue_fn();
+        emit_queue();
 
-        let youtube_url = YouTubeDownloader::find_best_source(
+        let duration_secs = Some(u64::from(spotify_metadata.duration_ms) / 1000).filter(|d| *d > 0);
+        let youtube_match = YouTubeDownloader::find_best_source(
             app,
             &spotify_metadata.artist,
             &spotify_metadata.name,
+            duration_secs,
             job_id,
-            &update_status_fn,
-            &emit_queue_fn,
+            &update_status,
+            &emit_queue,
         )
         .await?;
+        let youtube_url = youtube_match.url;
 
-        println!("[Spotify] Found YouTube match: {}", youtube_url);
+        println!("[Spotify] Found YouTube match: {} ({:.2})", youtube_url, youtube_match.confidence);
+        crate::download::QueueManager::set_job_match(job_id, &youtube_url, youtube_match.confidence);
 
         // Step 6: Download from YouTube using yt-dlp
         let output_dir = output_path.parent().unwrap().to_string_lossy().to_string();
         std::fs::create_dir_all(&output_dir)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
-
-        let output_template = format!("{}/%(title)s.%(ext)s", output_dir);
+            .or_error(ErrorKind::Io, "Failed to create directory")?;
 
-        let sidecar = app.shell().sidecar("yt-dlp")
-            .map_err(|e| format!("Failed to get yt-dlp sidecar: {}", e))?;
+        let output_template = crate::utils::filesystem::ytdlp_output_template(&output_path);
 
-        let args: Vec<&str> = vec![
-            &youtube_url,
-            "-f", "bestaudio",
-            "--extract-audio",
-            "--audio-format", "mp3",
-            "--audio-quality", "0",
+        let audio_format = crate::utils::get_audio_format();
+        let mut args: Vec<&str> = vec![&youtube_url, "-f", "bestaudio"];
+        args.extend(audio_format.ytdlp_args());
+        args.extend([
             "--prefer-free-formats",
-            "--embed-thumbnail",
-            "--add-metadata",
-            "--output", &output_template,
-            "--progress",
-            "--newline",
+            "--output", output_template.as_str(),
             "--no-warnings",
-        ];
+        ]);
 
-        let (mut rx, _child) = sidecar.args(args).spawn()
-            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
-
-        update_status_fn(job_id, DownloadStatus::Downloading, 20.0, "Downloading...");
-
-        let mut last_progress: f32 = 20.0;
-        let track_title = track_metadata.title.clone();
-
-        while let Some(event) = rx.recv().await {
-            match event {
-                tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
-                    let line_str = String::from_utf8_lossy(&line).to_string();
-                    println!("[yt-dlp] {}", line_str);
-
-                    if let Some(pct) = YouTubeDownloader::parse_ytdlp_progress(&line_str) {
-                        last_progress = 20.0 + (pct * 0.7); // 20-90%
-                        update_status_fn(
-                            job_id,
-                            DownloadStatus::Downloading,
-                            last_progress,
-                            &format!("Downloading... {:.1}%", pct),
-                        );
-                        // Real-time UI update
-                        emit_queue_fn();
-                    }
+        YtDlpRunner::download_audio(app, job_id, &args, (20.0, 90.0), &update_status, &emit_queue).await?;
 
-                    if line_str.contains("[ExtractAudio]") || line_str.contains("[Merger]") {
-                        update_status_fn(job_id, DownloadStatus::Converting, 92.0, "Converting to MP3...");
-                        emit_queue_fn();
-                    }
-                }
-                tauri_plugin_shell::process::CommandEvent::Stderr(line) => {
-                    let line_str = String::from_utf8_lossy(&line).to_string();
-                    eprintln!("[yt-dlp stderr] {}", line_str);
-                }
-                tauri_plugin_shell::process::CommandEvent::Error(error) => {
-                    update_status_fn(
-                        job_id,
-                        DownloadStatus::Error,
-                        last_progress,
-                        &format!("Error: {}", error),
-                    );
-                    return Err(format!("yt-dlp error: {}", error));
-                }
-                tauri_plugin_shell::process::CommandEvent::Terminated(payload) => {
-                    if payload.code != Some(0) {
-                        let error_msg = format!("yt-dlp exited with code: {:?}", payload.code);
-                        update_status_fn(job_id, DownloadStatus::Error, last_progress, &error_msg);
-                        return Err(error_msg);
-                    }
-                    break;
-                }
-                _ => {}
+        // The extension may differ from the planned one when the original format is kept
+        let output_path = crate::utils::filesystem::find_audio_file(&output_path).unwrap_or(output_path);
+        Ok(output_path.to_string_lossy().to_string())
+    }
+}
+
+impl MusicSource for SpotifyDownloader {
+    fn service(&self) -> MusicService {
+        MusicService::Spotify
+    }
+
+    fn matches_url(&self, url: &str) -> bool {
+        url.trim().starts_with("spotify:") || host_matches(url, &["spotify.com", "spotify.link"])
+    }
+
+    fn is_collection(&self, url: &str) -> bool {
+        parse_link(url).is_some_and(|link| link.kind.is_collection())
+    }
+
+    fn resolve_metadata<'a>(&'a self, _app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<TrackMetadata>> {
+        Box::pin(async move {
+            let metadata = Self::get_metadata_from_api(url).await?;
+            Ok(Self::add_web_api_details(Self::track_metadata(&metadata)).await)
+        })
+    }
+
+    fn expand_collection<'a>(&'a self, _app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<Collection>> {
+        Box::pin(async move {
+            match parse_link(url).map(|link| link.kind) {
+                Some(LinkKind::Playlist) => Self::expand_playlist(url).await,
+                // Without a choice of releases, an artist link means their top tracks
+                Some(LinkKind::Artist) => Self::expand_top_tracks(url).await,
+                _ => Self::expand_album(url).await,
             }
+        })
+    }
+
+    fn download<'a>(&'a self, request: DownloadRequest<'a>) -> BoxFuture<'a, AppResult<String>> {
+        Box::pin(Self::download_track(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_artist_id() {
+        let cases = [
+            ("https://open.spotify.com/artist/0TnOYISbd1XYRBk9myaseg", Some("0TnOYISbd1XYRBk9myaseg")),
+            ("https://open.spotify.com/intl-de/artist/0TnOYISbd1XYRBk9myaseg?si=abc", Some("0TnOYISbd1XYRBk9myaseg")),
+            ("spotify:artist:0TnOYISbd1XYRBk9myaseg", Some("0TnOYISbd1XYRBk9myaseg")),
+            ("https://open.spotify.com/album/4aawyAB9vmqN3uQ7FjRGTy", None),
+        ];
+        for (url, id) in cases {
+            assert_eq!(SpotifyDownloader::extract_artist_id(url).as_deref(), id, "{}", url);
         }
+    }
 
-        update_status_fn(job_id, DownloadStatus::Complete, 100.0, "Download complete!");
-        emit_queue_fn();
+    #[test]
+    fn test_include_groups() {
+        assert_eq!(ArtistReleaseFilter::default().include_groups(), "album,single");
+        let everything = ArtistReleaseFilter {
+            compilations: true,
+            appears_on: true,
+            ..ArtistReleaseFilter::default()
+        };
+        assert_eq!(everything.include_groups(), "album,single,compilation,appears_on");
+    }
 
-        Ok(output_path_str)
+    #[test]
+    fn test_track_details() {
+        let track = serde_json::json!({
+            "explicit": true,
+            "track_number": 3,
+            "disc_number": 1,
+            "album": {
+                "id": "album1",
+                "name": "Album",
+                "total_tracks": 12,
+                "artists": [{ "name": "Album Artist" }],
+            },
+        });
+        let artist = serde_json::json!({ "genres": ["israeli pop", "mizrahi"] });
+
+        let details = SpotifyDownloader::track_details(&track, &artist);
+        assert_eq!(details.explicit, Some(true));
+        assert_eq!(details.genre.as_deref(), Some("israeli pop"));
+        assert_eq!((details.track_number, details.disc_number, details.track_total), (Some(3), Some(1), Some(12)));
+        assert_eq!(details.album_artist.as_deref(), Some("Album Artist"));
+
+        let details = SpotifyDownloader::track_details(&serde_json::json!({}), &serde_json::Value::Null);
+        assert_eq!((details.explicit, details.genre, details.album_artist), (None, None, None));
+    }
+
+    #[test]
+    fn test_artist_name_in_tracks() {
+        let tracks = vec![
+            serde_json::json!({ "artists": [{ "id": "feat", "name": "Featured" }] }),
+            serde_json::json!({ "artists": [{ "id": "feat", "name": "Featured" }, { "id": "main", "name": "Main Artist" }] }),
+        ];
+        assert_eq!(SpotifyDownloader::artist_name_in_tracks(&tracks, "main"), Some("Main Artist"));
+        assert_eq!(SpotifyDownloader::artist_name_in_tracks(&tracks, "other"), None);
     }
 }
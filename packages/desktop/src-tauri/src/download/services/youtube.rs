@@ -1,35 +1,77 @@
 // YouTube download service using yt-dlp
 
+use futures_util::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use tauri_plugin_shell::ShellExt;
 
-use crate::download::{TrackMetadata, DownloadStatus};
+use crate::download::{
+    TrackMetadata, DownloadStatus, DownloadContext, MusicService, QueueManager, SourceCandidate, SourceTier,
+};
+use crate::download::control::JobControl;
+use crate::download::limits::DownloadResource;
+use crate::download::links::{parse_link, LinkKind};
+use crate::download::matching::{self, MatchScore, MatchTarget};
+use crate::download::services::registry::{host_matches, Collection, DownloadRequest, MusicSource};
+use crate::download::ytdlp::YtDlpRunner;
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
 
 // ============================================================================
 // YouTube Quality Search Strategy
 // ============================================================================
 
-/// Quality tier for YouTube sources (higher = better)
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-enum YouTubeSourceTier {
-    Regular = 0,      // Any result
-    OfficialAudio = 1, // "official audio" in title
-    VEVO = 2,         // VEVO channel
-    Topic = 3,        // Artist - Topic channel (Art Tracks, best quality)
-}
-
 /// YouTube search result with quality info
 #[derive(Debug, Clone)]
 struct YouTubeSearchResult {
     url: String,
     title: String,
     uploader: String,
-    tier: YouTubeSourceTier,
+    tier: SourceTier,
     audio_bitrate: Option<u32>,
     duration_secs: Option<u64>,  // Video duration in seconds for verification
 }
 
+impl YouTubeSearchResult {
+    fn to_candidate(&self, score: &MatchScore) -> SourceCandidate {
+        SourceCandidate {
+            url: self.url.clone(),
+            title: self.title.clone(),
+            uploader: self.uploader.clone(),
+            tier: self.tier.clone(),
+            duration_secs: self.duration_secs,
+            audio_bitrate: self.audio_bitrate,
+            confidence: score.confidence,
+        }
+    }
+}
+
+/// A Regular-tier upload (not Topic/VEVO/official audio) is only used without asking
+/// when it matches at least this well
+const AUTO_ACCEPT_REGULAR_CONFIDENCE: f32 = 0.85;
+
+/// Candidates offered to the user when a job needs review
+const MAX_REVIEW_CANDIDATES: usize = 5;
+
+/// Prefix of the placeholder URL of a job queued by artist/title (DownloadJob::for_search)
+/// yt-dlp understands it too, but these jobs always go through find_best_source instead
+const SEARCH_URL_PREFIX: &str = "ytsearch:";
+
+/// Placeholder URL of a search job
+pub fn search_url(artist: &str, title: &str) -> String {
+    format!("{}{} {}", SEARCH_URL_PREFIX, artist, title).trim_end().to_string()
+}
+
+fn is_search_url(url: &str) -> bool {
+    url.starts_with(SEARCH_URL_PREFIX)
+}
+
+/// Video chosen by find_best_source
+#[derive(Debug, Clone)]
+pub struct YouTubeMatch {
+    pub url: String,
+    pub confidence: f32,  // 0.0-1.0, see download::matching
+}
+
 /// Analyze a yt-dlp JSON result to determine quality tier
 fn analyze_youtube_result(json: &serde_json::Value) -> Option<YouTubeSearchResult> {
     let url = json.get("webpage_url")
@@ -53,15 +95,15 @@ fn analyze_youtube_result(json: &serde_json::Value) -> Option<YouTubeSearchResul
 
     // Determine quality tier
     let tier = if uploader.ends_with(" - Topic") {
-        YouTubeSourceTier::Topic
+        SourceTier::Topic
     } else if uploader.contains("VEVO") || uploader.ends_with("VEVO") {
-        YouTubeSourceTier::VEVO
+        SourceTier::Vevo
     } else if title.to_lowercase().contains("official audio")
            || title.to_lowercase().contains("official music")
            || description.contains("Provided to YouTube") {
-        YouTubeSourceTier::OfficialAudio
+        SourceTier::OfficialAudio
     } else {
-        YouTubeSourceTier::Regular
+        SourceTier::Regular
     };
 
     // Try to get audio bitrate
@@ -91,25 +133,12 @@ fn analyze_youtube_result(json: &serde_json::Value) -> Option<YouTubeSearchResul
 pub struct YouTubeDownloader;
 
 impl YouTubeDownloader {
-    /// Parse yt-dlp progress output to extract percentage
-    pub fn parse_ytdlp_progress(line: &str) -> Option<f32> {
-        // Format: [download]  45.2% of 10.00MiB at 1.00MiB/s ETA 00:05
-        if line.contains("[download]") && line.contains("%") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            for part in parts {
-                if part.ends_with('%') {
-                    if let Ok(pct) = part.trim_end_matches('%').parse::<f32>() {
-                        return Some(pct);
-                    }
-                }
-            }
-        }
-        None
-    }
-
     /// Parse yt-dlp metadata output
     pub fn parse_ytdlp_metadata(json_str: &str) -> TrackMetadata {
         if let Ok(json) = serde_json::from_str::<serde_json::Value>(json_str) {
+            let text = |key: &str| json.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+            let number = |key: &str| json.get(key).and_then(|v| v.as_u64()).map(|n| n as u32);
+
             TrackMetadata {
                 title: json.get("title").and_then(|v| v.as_str()).unwrap_or("Unknown").to_string(),
                 artist: json.get("artist")
@@ -124,22 +153,102 @@ impl YouTubeDownloader {
                     .to_string(),
                 duration: json.get("duration").and_then(|v| v.as_u64()).map(|d| d as u32),
                 thumbnail: json.get("thumbnail").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                // Music-specific fields are only present for YouTube Music / auto-generated "Topic" uploads
+                artists: json.get("artists")
+                    .and_then(|v| v.as_array())
+                    .map(|a| a.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect())
+                    .unwrap_or_default(),
+                album_artist: text("album_artist"),
+                track_number: number("track_number"),
+                disc_number: number("disc_number"),
+                release_date: Self::parse_ytdlp_date(&json, "release_date")
+                    .or_else(|| number("release_year").map(|year| year.to_string())),
+                genre: text("genre"),
+                source_ids: crate::download::SourceIds {
+                    youtube: text("id"),
+                    ..Default::default()
+                },
+                ..TrackMetadata::default()
             }
         } else {
             TrackMetadata::default()
         }
     }
 
+    /// Fetch a video's metadata with yt-dlp (without downloading it)
+    pub async fn fetch_metadata(app: &AppHandle, url: &str) -> AppResult<TrackMetadata> {
+        let _search_permit = DownloadResource::YtDlpSearch.acquire().await;
+
+        let sidecar = app.shell().sidecar("yt-dlp")
+            .or_error(ErrorKind::DependencyMissing, "Failed to get yt-dlp sidecar")?;
+
+        let (mut rx, _child) = sidecar
+            .args(["--dump-json", "--no-download", url])
+            .spawn()
+            .or_error(ErrorKind::DependencyMissing, "Failed to spawn yt-dlp")?;
+
+        let mut json_output = String::new();
+        while let Some(event) = rx.recv().await {
+            match event {
+                tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
+                    json_output.push_str(&String::from_utf8_lossy(&line));
+                }
+                tauri_plugin_shell::process::CommandEvent::Terminated(_) => break,
+                _ => {}
+            }
+        }
+
+        let mut metadata = Self::parse_ytdlp_metadata(&json_output);
+
+        // For Spotify-style titles (Artist - Title), extract artist
+        if metadata.artist == "Unknown Artist" {
+            if let Some(dash_pos) = metadata.title.find(" - ") {
+                let artist = metadata.title[..dash_pos].trim().to_string();
+                let title = metadata.title[dash_pos + 3..].trim().to_string();
+                if !artist.is_empty() {
+                    metadata.artist = artist;
+                    metadata.title = title;
+                }
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Date field from yt-dlp JSON ("20190517" -> "2019-05-17")
+    pub(crate) fn parse_ytdlp_date(json: &serde_json::Value, key: &str) -> Option<String> {
+        json.get(key)
+            .and_then(|v| v.as_str())
+            .filter(|d| d.len() == 8 && d.chars().all(|c| c.is_ascii_digit()))
+            .map(|d| format!("{}-{}-{}", &d[..4], &d[4..6], &d[6..]))
+    }
+
     /// Search YouTube with multiple strategies to find the best quality source
-    /// Returns the URL of the best matching video
+    /// Candidates are scored against the track (title/artist similarity, duration, version keywords)
+    /// and only confident matches are considered; among those the best quality tier wins.
+    /// When the result is ambiguous the top candidates are stored on the job and a NeedsReview
+    /// error is returned - the user picks one with resolve_job_source and the job runs again.
     pub async fn find_best_source(
         app: &AppHandle,
         artist: &str,
         title: &str,
+        duration_secs: Option<u64>,
         job_id: &str,
         update_status_fn: impl Fn(&str, DownloadStatus, f32, &str),
         emit_queue_fn: impl Fn(),
-    ) -> Result<String, String> {
+    ) -> AppResult<YouTubeMatch> {
+        // The user already picked a video for this job
+        if let Some(url) = QueueManager::get_job(job_id).ok().and_then(|job| job.manual_source) {
+            println!("[Search] Using source picked by the user: {}", url);
+            return Ok(YouTubeMatch { url, confidence: 1.0 });
+        }
+
+        let target = MatchTarget {
+            artist: artist.to_string(),
+            title: title.to_string(),
+            duration_secs,
+        };
+
         // Search queries in priority order
         let search_queries = vec![
             // Priority 1: Exact match targeting Topic channels (Art Tracks)
@@ -150,9 +259,13 @@ impl YouTubeDownloader {
             format!("{} {}", artist, title),
         ];
 
-        let mut best_result: Option<YouTubeSearchResult> = None;
+        let mut best_result: Option<(YouTubeSearchResult, MatchScore)> = None;
+        let mut candidates: Vec<SourceCandidate> = Vec::new();
 
         for (idx, query) in search_queries.iter().enumerate() {
+            // Paused or cancelled during the metadata lookup or an earlier query
+            JobControl::check(job_id)?;
+
             let progress = 5.0 + (idx as f32 * 2.0);
             update_status_fn(job_id, DownloadStatus::Downloading, progress,
                 &format!("Searching: {} ({}/{})", query, idx + 1, search_queries.len()));
@@ -160,11 +273,14 @@ impl YouTubeDownloader {
 
             println!("[Search] Trying query {}: '{}'", idx + 1, query);
 
+            // YouTube rate-limits searches, so they share a tighter concurrency limit
+            let _search_permit = DownloadResource::YtDlpSearch.acquire_for_job(job_id).await?;
+
             // Search for 5 results to find the best one
             let search_url = format!("ytsearch5:{}", query);
 
             let sidecar = app.shell().sidecar("yt-dlp")
-                .map_err(|e| format!("Failed to get yt-dlp sidecar: {}", e))?;
+                .or_error(ErrorKind::DependencyMissing, "Failed to get yt-dlp sidecar")?;
 
             let (mut rx, _child) = sidecar
                 .args([
@@ -175,7 +291,7 @@ impl YouTubeDownloader {
                     &search_url
                 ])
                 .spawn()
-                .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+                .or_error(ErrorKind::DependencyMissing, "Failed to spawn yt-dlp")?;
 
             let mut json_lines = Vec::new();
             let mut current_line = String::new();
@@ -202,24 +318,36 @@ impl YouTubeDownloader {
             // Analyze results from this search
             for json in &json_lines {
                 if let Some(result) = analyze_youtube_result(json) {
-                    println!("[Search] Found: '{}' by '{}' - Tier: {:?}",
-                        result.title, result.uploader, result.tier);
+                    let score = matching::score_match(&target, &result.title, &result.uploader, result.duration_secs);
+                    println!("[Search] Found: '{}' by '{}' - Tier: {:?}, confidence {:.2} (title {:.2}, artist {:.2}, duration {:.2}, penalty {:.2})",
+                        result.title, result.uploader, result.tier, score.confidence,
+                        score.title, score.artist, score.duration, score.penalty);
+
+                    // Every result is kept in case the user has to choose (queries often overlap)
+                    if !candidates.iter().any(|c| c.url == result.url) {
+                        candidates.push(result.to_candidate(&score));
+                    }
+                    if !score.is_confident() {
+                        continue;
+                    }
 
-                    // Keep if this is better than what we have
-                    let dominated = best_result.as_ref().is_some_and(|best| result.tier <= best.tier);
-                    if !dominated {
+                    // Keep if this is better than what we have: higher tier, or same tier with a closer match
+                    let better = best_result.as_ref().map_or(true, |(best, best_score)| {
+                        (&result.tier, score.confidence) > (&best.tier, best_score.confidence)
+                    });
+                    if better {
                         // Found a Topic channel - this is the best, stop searching
-                        if result.tier == YouTubeSourceTier::Topic {
+                        if result.tier == SourceTier::Topic {
                             println!("[Search] Found Topic channel (best quality) - stopping search");
-                            return Ok(result.url);
+                            return Ok(YouTubeMatch { url: result.url, confidence: score.confidence });
                         }
-                        best_result = Some(result);
+                        best_result = Some((result, score));
                     }
                 }
             }
 
             // If we found VEVO, that's good enough - no need to try more queries
-            if best_result.as_ref().is_some_and(|r| r.tier == YouTubeSourceTier::VEVO) {
+            if best_result.as_ref().is_some_and(|(r, _)| r.tier == SourceTier::Vevo) {
                 println!("[Search] Found VEVO channel - good enough");
                 break;
             }
@@ -227,15 +355,30 @@ impl YouTubeDownloader {
 
         // Return the best result we found
         match best_result {
-            Some(result) => {
-                println!("[Search] Best result: '{}' by '{}' (Tier: {:?})",
-                    result.title, result.uploader, result.tier);
-                Ok(result.url)
+            Some((result, score))
+                if result.tier > SourceTier::Regular || score.confidence >= AUTO_ACCEPT_REGULAR_CONFIDENCE =>
+            {
+                println!("[Search] Best result: '{}' by '{}' (Tier: {:?}, confidence {:.2})",
+                    result.title, result.uploader, result.tier, score.confidence);
+                Ok(YouTubeMatch { url: result.url, confidence: score.confidence })
             }
-            None => {
-                // Fallback: just use first result from basic search
-                println!("[Search] No results found, using fallback");
-                Ok(format!("ytsearch1:{} {}", artist, title))
+            _ if candidates.is_empty() => Err(AppError::new(
+                ErrorKind::NotFound,
+                format!("No YouTube results for {} - {}", artist, title),
+            )),
+            _ => {
+                // Better to ask than to download a live version, cover or 10-hour loop
+                candidates.sort_by(|a, b| {
+                    b.confidence.total_cmp(&a.confidence).then_with(|| b.tier.cmp(&a.tier))
+                });
+                candidates.truncate(MAX_REVIEW_CANDIDATES);
+                println!("[Search] No clear match for {} - {}, {} candidates need review",
+                    artist, title, candidates.len());
+                QueueManager::set_job_review(job_id, candidates);
+                Err(AppError::new(
+                    ErrorKind::NeedsReview,
+                    format!("No clear YouTube match for {} - {}", artist, title),
+                ))
             }
         }
     }
@@ -244,55 +387,22 @@ impl YouTubeDownloader {
     pub async fn extract_playlist_urls(
         app: &AppHandle,
         playlist_url: &str,
-    ) -> Result<(String, Vec<String>), String> {
+    ) -> AppResult<(String, Vec<String>)> {
         println!("[YouTube Playlist] Processing: {}", playlist_url);
 
-        // Use yt-dlp to extract playlist info
-        let sidecar = app.shell().sidecar("yt-dlp")
-            .map_err(|e| format!("Failed to get yt-dlp: {}", e))?;
-
-        let (mut rx, _child) = sidecar
-            .args(&[
-                "--flat-playlist",
-                "--dump-json",
-                playlist_url,
-            ])
-            .spawn()
-            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
-
-        let mut playlist_name = String::from("Unknown Playlist");
-        let mut video_urls = Vec::new();
-
-        // Collect JSON output
-        let mut json_lines = Vec::new();
-        while let Some(event) = rx.recv().await {
-            if let tauri_plugin_shell::process::CommandEvent::Stdout(line) = event {
-                json_lines.push(line);
-            }
-        }
-
-        // Parse JSON lines to extract video URLs and playlist name
-        for line in json_lines {
-            let line_str = String::from_utf8_lossy(&line);
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line_str) {
-                // First entry should have playlist title
-                if playlist_name == "Unknown Playlist" {
-                    if let Some(playlist_title) = json.get("playlist_title").and_then(|v| v.as_str()) {
-                        playlist_name = playlist_title.to_string();
-                    }
-                }
-
-                // Extract video URL
-                if let Some(video_id) = json.get("id").and_then(|v| v.as_str()) {
-                    video_urls.push(format!("https://www.youtube.com/watch?v={}", video_id));
-                }
-            }
-        }
+        let playlist = YtDlpRunner::flat_playlist(app, playlist_url).await?;
+        let playlist_name = playlist.title.unwrap_or_else(|| "Unknown Playlist".to_string());
+        let video_urls: Vec<String> = playlist
+            .entries
+            .iter()
+            .filter_map(|entry| entry.id.as_ref())
+            .map(|video_id| format!("https://www.youtube.com/watch?v={}", video_id))
+            .collect();
 
         println!("[YouTube Playlist] Playlist: '{}' ({} videos)", playlist_name, video_urls.len());
 
         if video_urls.is_empty() {
-            return Err("No videos found in playlist".to_string());
+            return Err(AppError::new(ErrorKind::NotFound, "No videos found in playlist"));
         }
 
         Ok((playlist_name, video_urls))
@@ -300,54 +410,17 @@ impl YouTubeDownloader {
 
     /// Download a YouTube video/track directly
     /// Returns the path to the downloaded file
-    pub async fn download_track(
-        app: &tauri::AppHandle,
-        url: &str,
-        base_output_dir: &str,
-        download_context: &crate::download::DownloadContext,
-        job_id: &str,
-        update_status_fn: impl Fn(&str, crate::download::DownloadStatus, f32, &str),
-        emit_queue_fn: impl Fn(),
-        update_metadata_fn: impl Fn(crate::download::TrackMetadata),
-    ) -> Result<String, String> {
-        use crate::download::{DownloadStatus, TrackMetadata};
-        use tauri_plugin_shell::ShellExt;
-
+    pub async fn download_track(request: DownloadRequest<'_>) -> AppResult<String> {
+        let DownloadRequest { app, url, base_output_dir, download_context, job_id, update_status, emit_queue, update_metadata } = request;
         // Step 1: Get metadata
-        update_status_fn(job_id, DownloadStatus::Downloading, 8.0, "Fetching metadata...");
-        emit_queue_fn();
-
-        let sidecar = app.shell().sidecar("yt-dlp")
-            .map_err(|e| format!("Failed to get yt-dlp sidecar: {}", e))?;
-
-        let (mut rx, _child) = sidecar
-            .args(["--dump-json", "--no-download", url])
-            .spawn()
-            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
-
-        let mut json_output = String::new();
-        while let Some(event) = rx.recv().await {
-            match event {
-                tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
-                    json_output.push_str(&String::from_utf8_lossy(&line));
-                }
-                tauri_plugin_shell::process::CommandEvent::Terminated(_) => break,
-                _ => {}
-            }
-        }
+        update_status(job_id, DownloadStatus::Downloading, 8.0, "Fetching metadata...");
+        emit_queue();
 
-        let mut metadata = Self::parse_ytdlp_metadata(&json_output);
+        let mut metadata = Self::fetch_metadata(app, url).await?;
 
-        // For Spotify-style titles (Artist - Title), extract artist
-        if metadata.artist == "Unknown Artist" {
-            if let Some(dash_pos) = metadata.title.find(" - ") {
-                let artist = metadata.title[..dash_pos].trim().to_string();
-                let title = metadata.title[dash_pos + 3..].trim().to_string();
-                if !artist.is_empty() {
-                    metadata.artist = artist;
-                    metadata.title = title;
-                }
-            }
+        // Keep details known when the job was queued (e.g. from an album)
+        if let Ok(job) = crate::download::QueueManager::get_job(job_id) {
+            metadata.fill_missing(&job.metadata);
         }
 
         // Step 2: Transliterate metadata if English Only mode is enabled
@@ -356,106 +429,137 @@ impl YouTubeDownloader {
             .unwrap_or(metadata);
 
         // Step 3: Update job metadata
-        update_metadata_fn(metadata.clone());
-        emit_queue_fn();
+        update_metadata(metadata.clone());
+        emit_queue();
 
         println!(
             "[YouTube] Title: '{}', Artist: '{}', Album: '{}'",
             metadata.title, metadata.artist, metadata.album
         );
 
+        Self::save_audio(app, url, &metadata, base_output_dir, download_context, job_id, update_status, emit_queue).await
+    }
+
+    /// Download a job queued by artist/title only (see DownloadJob::for_search)
+    /// The video is chosen with find_best_source; the queued artist/title/album stay as the tags,
+    /// since video titles are often "Artist - Title (Official Video)"
+    async fn download_search(request: DownloadRequest<'_>) -> AppResult<String> {
+        let DownloadRequest { app, base_output_dir, download_context, job_id, update_status, emit_queue, update_metadata, .. } = request;
+        let queued = QueueManager::get_job(job_id)?.metadata;
+
+        println!("[YouTube] Finding best source for: {} - {}", queued.artist, queued.title);
+        update_status(
+            job_id,
+            DownloadStatus::Downloading,
+            3.0,
+            &format!("Finding best quality: {} - {}", queued.artist, queued.title),
+        );
+        emit_queue();
+
+        let youtube_match = Self::find_best_source(
+            app,
+            &queued.artist,
+            &queued.title,
+            queued.duration.map(u64::from),
+            job_id,
+            update_status,
+            emit_queue,
+        )
+        .await?;
+        println!("[YouTube] Best source found: {} ({:.2})", youtube_match.url, youtube_match.confidence);
+        QueueManager::set_job_match(job_id, &youtube_match.url, youtube_match.confidence);
+
+        // Cover and video ID from the video; a failed lookup just leaves them out
+        let mut metadata = queued;
+        if let Ok(video) = Self::fetch_metadata(app, &youtube_match.url).await {
+            metadata.fill_missing(&video);
+            metadata.duration = metadata.duration.or(video.duration);
+        }
+
+        let metadata = crate::download::transliteration::transliterate_if_needed(&metadata)
+            .await
+            .unwrap_or(metadata);
+        update_metadata(metadata.clone());
+        emit_queue();
+
+        Self::save_audio(app, &youtube_match.url, &metadata, base_output_dir, download_context, job_id, update_status, emit_queue).await
+    }
+
+    /// Download a video's audio to the organized path for `metadata`
+    async fn save_audio(
+        app: &AppHandle,
+        url: &str,
+        metadata: &TrackMetadata,
+        base_output_dir: &str,
+        download_context: &DownloadContext,
+        job_id: &str,
+        update_status_fn: impl Fn(&str, DownloadStatus, f32, &str),
+        emit_queue_fn: impl Fn(),
+    ) -> AppResult<String> {
         // Step 4: Calculate output path (now uses transliterated metadata)
         let output_path = crate::utils::filesystem::get_organized_output_path(
             base_output_dir,
-            &metadata,
+            metadata,
             download_context,
         );
+        // Apply the duplicate policy if this track is already on disk
+        let output_path = crate::download::dedup::resolve_output_path(job_id, output_path)?;
         let output_dir = output_path.parent().unwrap().to_string_lossy().to_string();
 
         std::fs::create_dir_all(&output_dir)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
+            .or_error(ErrorKind::Io, "Failed to create directory")?;
 
-        let output_template = format!("{}/%(title)s.%(ext)s", output_dir);
+        let output_template = crate::utils::filesystem::ytdlp_output_template(&output_path);
 
-        // Step 4: Build yt-dlp command
-        let sidecar = app.shell().sidecar("yt-dlp")
-            .map_err(|e| format!("Failed to get yt-dlp sidecar: {}", e))?;
-
-        let args: Vec<&str> = vec![
-            url,
-            "-f", "bestaudio",
-            "--extract-audio",
-            "--audio-format", "mp3",
-            "--audio-quality", "0",
+        // Step 5: Download with yt-dlp
+        let audio_format = crate::utils::get_audio_format();
+        let mut args: Vec<&str> = vec![url, "-f", "bestaudio"];
+        args.extend(audio_format.ytdlp_args());
+        args.extend([
             "--prefer-free-formats",
-            "--embed-thumbnail",
-            "--add-metadata",
-            "--output", &output_template,
-            "--progress",
-            "--newline",
+            "--output", output_template.as_str(),
             "--no-warnings",
-        ];
+        ]);
 
-        let (mut rx, _child) = sidecar.args(args).spawn()
-            .map_err(|e| format!("Failed to spawn yt-dlp: {}", e))?;
+        YtDlpRunner::download_audio(app, job_id, &args, (5.0, 90.0), &update_status_fn, &emit_queue_fn).await?;
 
-        update_status_fn(job_id, DownloadStatus::Downloading, 5.0, "Downloading...");
+        // The extension may differ from the planned one when the original format is kept
+        let output_path = crate::utils::filesystem::find_audio_file(&output_path).unwrap_or(output_path);
+        Ok(output_path.to_string_lossy().to_string())
+    }
+}
 
-        // Step 5: Listen to progress
-        let mut last_progress: f32 = 5.0;
-        let track_title = metadata.title.clone();
+impl MusicSource for YouTubeDownloader {
+    fn service(&self) -> MusicService {
+        MusicService::YouTube
+    }
 
-        while let Some(event) = rx.recv().await {
-            match event {
-                tauri_plugin_shell::process::CommandEvent::Stdout(line) => {
-                    let line_str = String::from_utf8_lossy(&line).to_string();
-                    println!("[yt-dlp] {}", line_str);
-
-                    if let Some(pct) = Self::parse_ytdlp_progress(&line_str) {
-                        last_progress = pct * 0.9;
-                        update_status_fn(
-                            job_id,
-                            DownloadStatus::Downloading,
-                            last_progress,
-                            &format!("Downloading... {:.1}%", pct),
-                        );
-                        // Emit queue update for real-time UI refresh
-                        emit_queue_fn();
-                    }
+    fn matches_url(&self, url: &str) -> bool {
+        host_matches(url, &["youtube.com", "youtu.be"])
+    }
 
-                    if line_str.contains("[ExtractAudio]") || line_str.contains("[Merger]") {
-                        update_status_fn(job_id, DownloadStatus::Converting, 92.0, "Converting to MP3...");
-                        emit_queue_fn();
-                    }
-                }
-                tauri_plugin_shell::process::CommandEvent::Stderr(line) => {
-                    let line_str = String::from_utf8_lossy(&line).to_string();
-                    eprintln!("[yt-dlp stderr] {}", line_str);
-                }
-                tauri_plugin_shell::process::CommandEvent::Error(error) => {
-                    update_status_fn(
-                        job_id,
-                        DownloadStatus::Error,
-                        last_progress,
-                        &format!("Error: {}", error),
-                    );
-                    return Err(format!("yt-dlp error: {}", error));
-                }
-                tauri_plugin_shell::process::CommandEvent::Terminated(payload) => {
-                    if payload.code != Some(0) {
-                        let error_msg = format!("yt-dlp exited with code: {:?}", payload.code);
-                        update_status_fn(job_id, DownloadStatus::Error, last_progress, &error_msg);
-                        return Err(error_msg);
-                    }
-                    break;
-                }
-                _ => {}
-            }
-        }
+    /// Playlist pages, and watch links that only carry a playlist ID
+    /// (a video played from a playlist, "watch?v=...&list=...", is still a single track)
+    fn is_collection(&self, url: &str) -> bool {
+        parse_link(url).is_some_and(|link| link.kind == LinkKind::Playlist)
+    }
 
-        update_status_fn(job_id, DownloadStatus::Complete, 100.0, "Download complete!");
-        emit_queue_fn();
+    fn resolve_metadata<'a>(&'a self, app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<TrackMetadata>> {
+        Box::pin(Self::fetch_metadata(app, url))
+    }
 
-        Ok(output_path.to_string_lossy().to_string())
+    fn expand_collection<'a>(&'a self, app: &'a AppHandle, url: &'a str) -> BoxFuture<'a, AppResult<Collection>> {
+        Box::pin(async move {
+            let (name, video_urls) = Self::extract_playlist_urls(app, url).await?;
+            let context = DownloadContext::Playlist(name.clone());
+            Ok(Collection::from_urls(name, context, video_urls))
+        })
+    }
+
+    fn download<'a>(&'a self, request: DownloadRequest<'a>) -> BoxFuture<'a, AppResult<String>> {
+        if is_search_url(request.url) {
+            return Box::pin(Self::download_search(request));
+        }
+        Box::pin(Self::download_track(request))
     }
 }
@@ -0,0 +1,530 @@
+// Native tag writer - ID3v2 (MP3), Vorbis comments (FLAC/Opus) and MP4 atoms (M4A/ALAC)
+// Tags are written in-process after every download from the metadata the job holds,
+// so they no longer depend on yt-dlp's --add-metadata or the ffmpeg sidecar.
+
+use std::path::Path;
+
+use lofty::config::WriteOptions;
+use lofty::file::FileType;
+use lofty::id3::v2::{
+    BinaryFrame, Frame, FrameId, Id3v2Tag, SyncTextContentType, SynchronizedTextFrame, TimestampFormat,
+};
+use lofty::mp4::{Atom, AtomData, AtomIdent, Ilst};
+use lofty::ogg::VorbisComments;
+use lofty::picture::{MimeType, Picture, PictureType};
+use lofty::tag::{Accessor, ItemKey, Tag, TagExt, TagType};
+use lofty::TextEncoding;
+
+use crate::download::lyrics::Lyrics;
+use crate::download::{dedup, DownloadJob, MusicService};
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+
+// ============================================================================
+// Track Tags
+// ============================================================================
+
+/// Everything written to a file, gathered from the job before touching the disk
+#[derive(Debug, Clone, Default)]
+pub struct TrackTags {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub album_artist: Option<String>,
+    /// Individual artists, written as a multi-value ARTISTS field when there is more than one
+    pub artists: Vec<String>,
+    pub track_number: Option<u32>,
+    pub track_total: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub disc_total: Option<u32>,
+    pub release_date: Option<String>,
+    pub genre: Option<String>,
+    pub isrc: Option<String>,
+    /// Service IDs as (key, value), e.g. ("SPOTIFY_TRACK_ID", "4uLU6hMCjMI75M1A2tKUQC")
+    pub ids: Vec<(&'static str, String)>,
+    pub cover: Option<(MimeType, Vec<u8>)>,
+    pub lyrics: Option<Lyrics>,
+}
+
+impl TrackTags {
+    /// Collect tags from a finished job (without cover art)
+    pub fn from_job(job: &DownloadJob) -> Self {
+        let metadata = &job.metadata;
+        let source_ids = &metadata.source_ids;
+
+        // IDs recorded by the services first, then whatever the URLs give away
+        let spotify_id = source_ids.spotify.clone().or_else(|| dedup::spotify_track_id(&job.url));
+        let deezer_url = match &job.source {
+            Some(source) if source.service == MusicService::Deezer => source.url.as_deref(),
+            _ => None,
+        };
+        let deezer_id = source_ids.deezer.clone().or_else(|| {
+            [Some(job.url.as_str()), deezer_url]
+                .into_iter()
+                .flatten()
+                .find_map(deezer_track_id)
+        });
+        let ids = [
+            ("SPOTIFY_TRACK_ID", spotify_id),
+            ("DEEZER_TRACK_ID", deezer_id),
+            ("APPLE_MUSIC_TRACK_ID", source_ids.apple_music.clone()),
+            ("YOUTUBE_VIDEO_ID", source_ids.youtube.clone()),
+            ("SOUNDCLOUD_TRACK_ID", source_ids.soundcloud.clone()),
+            ("BANDCAMP_TRACK_ID", source_ids.bandcamp.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(key, id)| id.filter(|id| !id.is_empty()).map(|id| (key, id)))
+        .collect();
+
+        // Placeholders from TrackMetadata::default() are better left out than written
+        let known = |value: &str, placeholder: &str| {
+            if value.trim() == placeholder {
+                String::new()
+            } else {
+                value.trim().to_string()
+            }
+        };
+
+        let non_empty = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(String::from);
+
+        TrackTags {
+            title: known(&metadata.title, "Unknown"),
+            artist: known(&metadata.artist, "Unknown Artist"),
+            album: known(&metadata.album, "Unknown Album"),
+            album_artist: non_empty(&metadata.album_artist),
+            artists: metadata.artists.clone(),
+            track_number: metadata.track_number,
+            track_total: metadata.track_total,
+            disc_number: metadata.disc_number,
+            disc_total: metadata.disc_total,
+            release_date: non_empty(&metadata.release_date),
+            genre: non_empty(&metadata.genre),
+            isrc: non_empty(&metadata.isrc).or_else(|| non_empty(&job.isrc)),
+            ids,
+            cover: None,
+            lyrics: None,
+        }
+    }
+}
+
+/// Extract the track ID from a deezer.com/track/ID link
+fn deezer_track_id(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    if !parsed.host_str()?.ends_with("deezer.com") {
+        return None;
+    }
+    let mut segments = parsed.path_segments()?.skip_while(|s| *s != "track");
+    segments.next()?;
+    let id = segments.next()?;
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+// ============================================================================
+// Tag Writer
+// ============================================================================
+
+pub struct TagWriter;
+
+impl TagWriter {
+    /// Write tags (and cover art from the job's thumbnail) to a downloaded file
+    pub async fn write_for_job(job: &DownloadJob, path: &Path, lyrics: Option<Lyrics>) -> AppResult<()> {
+        let mut tags = TrackTags::from_job(job);
+        tags.lyrics = lyrics;
+        if let Some(url) = job.metadata.thumbnail.as_deref() {
+            tags.cover = Self::fetch_cover(url).await;
+        }
+
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::write(&path, &tags))
+            .await
+            .map_err(|e| AppError::internal(format!("Tagging task failed: {}", e)))?
+    }
+
+    /// Write tags to a file in the native tag format for its type
+    pub fn write(path: &Path, tags: &TrackTags) -> AppResult<()> {
+        let file_type = FileType::from_path(path).ok_or_else(|| {
+            AppError::new(ErrorKind::Unsupported, format!("Can't tag file type: {}", path.display()))
+        })?;
+        let tag_type = file_type.primary_tag_type();
+        let tag = Self::build_tag(tag_type, tags);
+
+        // The generic tag covers the standard fields; service IDs and the artist list
+        // need format-specific custom fields
+        let artists = (tags.artists.len() > 1).then(|| tags.artists.as_slice());
+        let result = match tag_type {
+            TagType::Id3v2 => {
+                let mut id3 = Id3v2Tag::from(tag);
+                if let Some(artists) = artists {
+                    // ID3v2.4 separates multiple values with a null byte
+                    id3.insert_user_text("ARTISTS".to_string(), artists.join("\0"));
+                }
+                for (key, value) in &tags.ids {
+                    id3.insert_user_text(key.to_string(), value.clone());
+                }
+                if let Some(lines) = tags.lyrics.as_ref().and_then(|l| l.synced.as_ref()) {
+                    Self::insert_synced_lyrics(&mut id3, lines);
+                }
+                id3.save_to_path(path, WriteOptions::default())
+            }
+            TagType::VorbisComments => {
+                let mut comments = VorbisComments::from(tag);
+                for artist in artists.unwrap_or_default() {
+                    comments.push("ARTISTS".to_string(), artist.clone());
+                }
+                for (key, value) in &tags.ids {
+                    comments.insert(key.to_string(), value.clone());
+                }
+                comments.save_to_path(path, WriteOptions::default())
+            }
+            TagType::Mp4Ilst => {
+                let mut ilst = Ilst::from(tag);
+                let freeform = |key: &str, value: &str| {
+                    Atom::new(
+                        AtomIdent::Freeform {
+                            mean: "com.apple.iTunes".into(),
+                            name: key.to_string().into(),
+                        },
+                        AtomData::UTF8(value.to_string()),
+                    )
+                };
+                for artist in artists.unwrap_or_default() {
+                    ilst.insert(freeform("ARTISTS", artist));
+                }
+                for (key, value) in &tags.ids {
+                    ilst.insert(freeform(key, value));
+                }
+                ilst.save_to_path(path, WriteOptions::default())
+            }
+            _ => tag.save_to_path(path, WriteOptions::default()),
+        };
+
+        result.or_error(ErrorKind::Io, format!("Failed to write tags to {}", path.display()))?;
+        println!("[Tags] ✅ Tagged {}", path.display());
+        Ok(())
+    }
+
+    /// Add a SYLT frame - ID3v2 is the only format with a dedicated synced lyrics field
+    fn insert_synced_lyrics(id3: &mut Id3v2Tag, lines: &[crate::download::lyrics::LyricLine]) {
+        let frame = SynchronizedTextFrame::new(
+            TextEncoding::UTF8,
+            *b"XXX", // Unknown language
+            TimestampFormat::MS,
+            SyncTextContentType::Lyrics,
+            None,
+            lines.iter().map(|line| (line.time_ms, line.text.clone())).collect(),
+        );
+        match frame.as_bytes() {
+            Ok(bytes) => {
+                id3.insert(Frame::Binary(BinaryFrame::new(FrameId::Valid("SYLT".into()), bytes)));
+            }
+            Err(e) => println!("[Tags] ⚠️ Failed to encode synced lyrics: {}", e),
+        }
+    }
+
+    /// Standard fields shared by every tag format
+    fn build_tag(tag_type: TagType, tags: &TrackTags) -> Tag {
+        let mut tag = Tag::new(tag_type);
+
+        if !tags.title.is_empty() {
+            tag.set_title(tags.title.clone());
+        }
+        if !tags.artist.is_empty() {
+            tag.set_artist(tags.artist.clone());
+        }
+        if !tags.album.is_empty() {
+            tag.set_album(tags.album.clone());
+        }
+        if let Some(album_artist) = &tags.album_artist {
+            tag.insert_text(ItemKey::AlbumArtist, album_artist.clone());
+        }
+        if let Some(track) = tags.track_number {
+            tag.set_track(track);
+        }
+        if let Some(total) = tags.track_total {
+            tag.set_track_total(total);
+        }
+        if let Some(disc) = tags.disc_number {
+            tag.set_disk(disc);
+        }
+        if let Some(total) = tags.disc_total {
+            tag.set_disk_total(total);
+        }
+        if let Some(date) = &tags.release_date {
+            // Players read the year from this; a full date goes in RecordingDate as well
+            if let Some(year) = date.get(..4).and_then(|y| y.parse().ok()) {
+                tag.set_year(year);
+            }
+            tag.insert_text(ItemKey::RecordingDate, date.clone());
+        }
+        if let Some(genre) = &tags.genre {
+            tag.set_genre(genre.clone());
+        }
+        if let Some(isrc) = &tags.isrc {
+            tag.insert_text(ItemKey::Isrc, isrc.clone());
+        }
+        if let Some(lyrics) = &tags.lyrics {
+            // USLT in ID3v2 gets plain text (synced lyrics go in SYLT). Other formats have a single
+            // lyrics field, where players that support synced lyrics read LRC timestamps
+            let text = match tag_type {
+                TagType::Id3v2 => lyrics.plain.clone(),
+                _ => lyrics.to_lrc().or_else(|| lyrics.plain.clone()),
+            };
+            if let Some(text) = text {
+                tag.insert_text(ItemKey::Lyrics, text);
+            }
+        }
+        if let Some((mime_type, data)) = &tags.cover {
+            tag.push_picture(Picture::new_unchecked(
+                PictureType::CoverFront,
+                Some(mime_type.clone()),
+                None,
+                data.clone(),
+            ));
+        }
+
+        tag
+    }
+
+    /// Download cover art; only JPEG and PNG are embedded since players rarely show anything else
+    async fn fetch_cover(url: &str) -> Option<(MimeType, Vec<u8>)> {
+        let url = jpeg_thumbnail_url(url);
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .ok()?;
+
+        let response = match client.get(&url).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                println!("[Tags] ⚠️ Cover art request failed with status: {}", response.status());
+                return None;
+            }
+            Err(e) => {
+                println!("[Tags] ⚠️ Failed to download cover art: {}", e);
+                return None;
+            }
+        };
+        let data = response.bytes().await.ok()?.to_vec();
+
+        match image_mime_type(&data) {
+            Some(mime_type) => Some((mime_type, data)),
+            None => {
+                println!("[Tags] ⚠️ Cover art is not JPEG or PNG, skipping: {}", url);
+                None
+            }
+        }
+    }
+}
+
+/// YouTube serves WebP thumbnails by default; the same image is available as JPEG
+/// ("https://i.ytimg.com/vi_webp/ID/maxresdefault.webp" -> "https://i.ytimg.com/vi/ID/maxresdefault.jpg")
+fn jpeg_thumbnail_url(url: &str) -> String {
+    if url.contains("ytimg.com/vi_webp/") && url.ends_with(".webp") {
+        let url = url.replacen("/vi_webp/", "/vi/", 1);
+        format!("{}.jpg", url.trim_end_matches(".webp"))
+    } else {
+        url.to_string()
+    }
+}
+
+/// Detect JPEG/PNG data from its magic bytes
+fn image_mime_type(data: &[u8]) -> Option<MimeType> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(MimeType::Jpeg)
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+        Some(MimeType::Png)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_job() {
+        let mut job = DownloadJob::new("https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC?si=x".to_string());
+        job.metadata.title = "Title".to_string();
+        job.metadata.artist = "Unknown Artist".to_string();
+        job.metadata.track_number = Some(2);
+        job.metadata.release_date = Some(" ".to_string());
+        job.metadata.source_ids.youtube = Some("dQw4w9WgXcQ".to_string());
+        job.isrc = Some("ILA000000001".to_string());
+        job.source = Some(crate::download::DownloadSource {
+            service: MusicService::Deezer,
+            url: Some("https://www.deezer.com/en/track/3135556".to_string()),
+        });
+
+        let tags = TrackTags::from_job(&job);
+        assert_eq!(tags.title, "Title");
+        assert_eq!(tags.artist, "");
+        assert_eq!(tags.track_number, Some(2));
+        assert_eq!(tags.release_date, None);
+        assert_eq!(tags.isrc.as_deref(), Some("ILA000000001"));
+        assert_eq!(
+            tags.ids,
+            vec![
+                ("SPOTIFY_TRACK_ID", "4uLU6hMCjMI75M1A2tKUQC".to_string()),
+                ("DEEZER_TRACK_ID", "3135556".to_string()),
+                ("YOUTUBE_VIDEO_ID", "dQw4w9WgXcQ".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jpeg_thumbnail_url() {
+        assert_eq!(
+            jpeg_thumbnail_url("https://i.ytimg.com/vi_webp/dQw4w9WgXcQ/maxresdefault.webp"),
+            "https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg"
+        );
+        assert_eq!(jpeg_thumbnail_url("https://i.scdn.co/image/abc"), "https://i.scdn.co/image/abc");
+    }
+
+    // ------------------------------------------------------------------------
+    // Round trips: write with TagWriter, read back with lofty
+    // ------------------------------------------------------------------------
+
+    use std::fs::File;
+    use std::path::PathBuf;
+
+    use lofty::config::ParseOptions;
+    use lofty::file::AudioFile;
+    use lofty::flac::FlacFile;
+    use lofty::mp4::Mp4File;
+    use lofty::mpeg::MpegFile;
+
+    use crate::download::lyrics::LyricLine;
+
+    fn sample_tags() -> TrackTags {
+        TrackTags {
+            title: "Title".to_string(),
+            artist: "Artist A, Artist B".to_string(),
+            album: "Album".to_string(),
+            artists: vec!["Artist A".to_string(), "Artist B".to_string()],
+            track_number: Some(3),
+            isrc: Some("ILA000000001".to_string()),
+            ids: vec![("SPOTIFY_TRACK_ID", "4uLU6hMCjMI75M1A2tKUQC".to_string())],
+            lyrics: Some(Lyrics {
+                plain: Some("First line\nSecond line".to_string()),
+                synced: Some(vec![
+                    LyricLine { time_ms: 1000, text: "First line".to_string() },
+                    LyricLine { time_ms: 2500, text: "Second line".to_string() },
+                ]),
+            }),
+            ..TrackTags::default()
+        }
+    }
+
+    /// Write `bytes` to a fresh temp dir; returns (dir, file path)
+    fn fixture(file_name: &str, bytes: &[u8]) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("hasod_tags_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(file_name);
+        std::fs::write(&path, bytes).unwrap();
+        (dir, path)
+    }
+
+    fn read_options() -> ParseOptions {
+        ParseOptions::new().read_properties(false)
+    }
+
+    /// Three silent MPEG-1 Layer III frames (128 kbps, 44.1 kHz)
+    fn mp3_bytes() -> Vec<u8> {
+        let mut frame = vec![0u8; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        frame.repeat(3)
+    }
+
+    /// "fLaC" and a STREAMINFO block, no audio frames
+    fn flac_bytes() -> Vec<u8> {
+        let mut bytes = b"fLaC".to_vec();
+        bytes.extend([0x80, 0, 0, 34]); // Last metadata block, STREAMINFO, 34 bytes
+        bytes.extend(4096u16.to_be_bytes()); // Min block size
+        bytes.extend(4096u16.to_be_bytes()); // Max block size
+        bytes.extend([0u8; 6]); // Min/max frame size unknown
+        // Sample rate (20 bits), channels - 1 (3), bits per sample - 1 (5), total samples (36)
+        let packed: u64 = (44_100 << 44) | (1 << 41) | (15 << 36);
+        bytes.extend(packed.to_be_bytes());
+        bytes.extend([0u8; 16]); // MD5
+        bytes
+    }
+
+    /// ftyp and a moov with only an mvhd - enough for lofty to add udta/meta/ilst
+    fn m4a_bytes() -> Vec<u8> {
+        fn atom(name: &[u8; 4], content: &[u8]) -> Vec<u8> {
+            let mut atom = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+            atom.extend(name);
+            atom.extend(content);
+            atom
+        }
+        let ftyp = atom(b"ftyp", b"M4A \0\0\0\0M4A isom");
+        let moov = atom(b"moov", &atom(b"mvhd", &[0u8; 100]));
+        [ftyp, moov].concat()
+    }
+
+    #[test]
+    fn test_write_id3v2_round_trip() {
+        let (dir, path) = fixture("track.mp3", &mp3_bytes());
+        TagWriter::write(&path, &sample_tags()).unwrap();
+
+        let file = MpegFile::read_from(&mut File::open(&path).unwrap(), read_options()).unwrap();
+        let id3 = file.id3v2().expect("ID3v2 tag");
+        assert_eq!(id3.title().as_deref(), Some("Title"));
+        assert_eq!(id3.track(), Some(3));
+        assert_eq!(id3.get_user_text("SPOTIFY_TRACK_ID"), Some("4uLU6hMCjMI75M1A2tKUQC"));
+        assert_eq!(id3.get_user_text("ARTISTS"), Some("Artist A\0Artist B"));
+        assert!(id3.get(&FrameId::Valid("SYLT".into())).is_some());
+
+        // USLT holds the plain lyrics (SYLT has the timestamps)
+        let tag = Tag::from(id3.clone());
+        assert_eq!(tag.get_string(&ItemKey::Lyrics), Some("First line\nSecond line"));
+        assert_eq!(tag.get_string(&ItemKey::Isrc), Some("ILA000000001"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_write_vorbis_comments_round_trip() {
+        let (dir, path) = fixture("track.flac", &flac_bytes());
+        TagWriter::write(&path, &sample_tags()).unwrap();
+
+        let file = FlacFile::read_from(&mut File::open(&path).unwrap(), read_options()).unwrap();
+        let comments = file.vorbis_comments().expect("Vorbis comments");
+        assert_eq!(comments.title().as_deref(), Some("Title"));
+        assert_eq!(comments.get("SPOTIFY_TRACK_ID"), Some("4uLU6hMCjMI75M1A2tKUQC"));
+        assert_eq!(comments.get_all("ARTISTS").collect::<Vec<_>>(), vec!["Artist A", "Artist B"]);
+        assert_eq!(comments.get("ISRC"), Some("ILA000000001"));
+        // A single lyrics field, as LRC so synced-lyrics players keep the timing
+        assert!(comments.get("LYRICS").is_some_and(|lyrics| lyrics.contains("[00:02.50]Second line")));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_write_mp4_round_trip() {
+        let (dir, path) = fixture("track.m4a", &m4a_bytes());
+        TagWriter::write(&path, &sample_tags()).unwrap();
+
+        let file = Mp4File::read_from(&mut File::open(&path).unwrap(), read_options()).unwrap();
+        let ilst = file.ilst().expect("ilst");
+        assert_eq!(ilst.title().as_deref(), Some("Title"));
+
+        let freeform = |name: &str| -> Vec<String> {
+            let ident = AtomIdent::Freeform { mean: "com.apple.iTunes".into(), name: name.to_string().into() };
+            ilst.get(&ident)
+                .map(|atom| {
+                    atom.data()
+                        .filter_map(|data| match data {
+                            AtomData::UTF8(text) => Some(text.clone()),
+                            _ => None,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        assert_eq!(freeform("SPOTIFY_TRACK_ID"), vec!["4uLU6hMCjMI75M1A2tKUQC"]);
+        assert_eq!(freeform("ARTISTS"), vec!["Artist A", "Artist B"]);
+        assert!(Tag::from(ilst.clone())
+            .get_string(&ItemKey::Lyrics)
+            .is_some_and(|lyrics| lyrics.contains("[00:01.00]First line")));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -2,11 +2,12 @@
 
 use crate::api_types::{HasodApiClient, MediaItem, TransliterateRequest};
 use crate::auth::get_auth_from_keychain;
-use crate::download::TrackMetadata;
-use crate::utils::needs_transliteration;
+use crate::download::{split_artists, TrackMetadata};
+use crate::error::AppResult;
+use crate::utils::{contains_hebrew, needs_transliteration};
 
 /// Transliterate metadata if English Only mode is enabled and text contains Hebrew
-pub async fn transliterate_if_needed(metadata: &TrackMetadata) -> Result<TrackMetadata, String> {
+pub async fn transliterate_if_needed(metadata: &TrackMetadata) -> AppResult<TrackMetadata> {
     // Check if English Only mode is enabled
     if !crate::utils::get_english_only_mode() {
         println!("[Transliteration] English Only mode disabled, skipping");
@@ -14,7 +15,8 @@ pub async fn transliterate_if_needed(metadata: &TrackMetadata) -> Result<TrackMe
     }
 
     // Check if metadata contains Hebrew
-    if !needs_transliteration(&metadata.title, &metadata.artist, &metadata.album) {
+    let album_artist = metadata.album_artist.as_deref().filter(|a| contains_hebrew(a));
+    if !needs_transliteration(&metadata.title, &metadata.artist, &metadata.album) && album_artist.is_none() {
         println!("[Transliteration] No Hebrew detected, skipping");
         return Ok(metadata.clone());
     }
@@ -34,28 +36,63 @@ pub async fn transliterate_if_needed(metadata: &TrackMetadata) -> Result<TrackMe
     // Call transliteration API
     let api_client = HasodApiClient::production();
 
-    let media_item = MediaItem {
-        title: metadata.title.clone(),
-        artist: metadata.artist.clone(),
-        album: metadata.album.clone(),
-    };
+    // Only Hebrew fields are sent; the rest are left empty and keep their original value
+    let hebrew = |text: &str| if contains_hebrew(text) { text.to_string() } else { String::new() };
+    let mut media_items = vec![MediaItem {
+        title: hebrew(&metadata.title),
+        artist: hebrew(&metadata.artist),
+        album: hebrew(&metadata.album),
+    }];
+    // An album artist that differs from the track credit needs its own item
+    let separate_album_artist = album_artist.filter(|a| *a != metadata.artist);
+    if let Some(album_artist) = separate_album_artist {
+        media_items.push(MediaItem {
+            title: String::new(),
+            artist: album_artist.to_string(),
+            album: String::new(),
+        });
+    }
+
+    let sent = media_items[0].clone();
 
-    match api_client.transliterate(vec![media_item], &auth_token).await {
+    match api_client.transliterate(media_items, &auth_token).await {
         Ok(response) => {
             if let Some(item) = response.items.first() {
-                let transliterated = &item.transliterated;
+                let pick = |sent: &str, transliterated: &str, original: &str| {
+                    if sent.is_empty() || transliterated.trim().is_empty() {
+                        original.to_string()
+                    } else {
+                        transliterated.to_string()
+                    }
+                };
+                let title = pick(&sent.title, &item.transliterated.title, &metadata.title);
+                let artist = pick(&sent.artist, &item.transliterated.artist, &metadata.artist);
+                let album = pick(&sent.album, &item.transliterated.album, &metadata.album);
 
                 println!("[Transliteration] ✅ Success!");
-                println!("[Transliteration] Transliterated: {} - {} ({})",
-                    transliterated.artist, transliterated.title, transliterated.album);
+                println!("[Transliteration] Transliterated: {} - {} ({})", artist, title, album);
+
+                let album_artist = match (album_artist, separate_album_artist, response.items.get(1)) {
+                    (Some(_), Some(_), Some(item)) => Some(item.transliterated.artist.clone()),
+                    // Same as the track credit
+                    (Some(_), None, _) => Some(artist.clone()),
+                    _ => metadata.album_artist.clone(),
+                };
+                // The artist list follows the transliterated credit
+                let artists = if metadata.artists.is_empty() {
+                    Vec::new()
+                } else {
+                    split_artists(&artist)
+                };
 
                 // Return new metadata with transliterated values
                 Ok(TrackMetadata {
-                    title: transliterated.title.clone(),
-                    artist: transliterated.artist.clone(),
-                    album: transliterated.album.clone(),
-                    duration: metadata.duration,
-                    thumbnail: metadata.thumbnail.clone(),
+                    title,
+                    artist,
+                    album,
+                    artists,
+                    album_artist,
+                    ..metadata.clone()
                 })
             } else {
                 println!("[Transliteration] Warning: API returned no items");
@@ -0,0 +1,508 @@
+// Shared yt-dlp download runner
+// Every service that downloads through yt-dlp goes through here: progress is read from
+// --progress-template JSON lines, stderr is kept to classify failures, and the process
+// is killed when the job is paused or cancelled.
+
+use tauri::AppHandle;
+use tauri_plugin_shell::process::CommandEvent;
+use tauri_plugin_shell::ShellExt;
+
+use crate::download::control::JobControl;
+use crate::download::limits::DownloadResource;
+use crate::download::{DownloadStatus, QueueManager};
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+
+/// Prefixes of the lines printed by our progress templates
+const PROGRESS_PREFIX: &str = "[hasod:progress]";
+const POSTPROCESS_PREFIX: &str = "[hasod:postprocess]";
+
+/// Number of stderr lines kept for error messages
+const STDERR_LINES_KEPT: usize = 5;
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Transfer state reported by yt-dlp while downloading
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransferProgress {
+    pub downloaded_bytes: Option<u64>,
+    pub total_bytes: Option<u64>, // Exact size, or yt-dlp's estimate when unknown
+    pub speed: Option<f64>,       // Bytes per second
+    pub eta_secs: Option<u64>,
+    pub fragment_index: Option<u64>,
+    pub fragment_count: Option<u64>,
+}
+
+impl TransferProgress {
+    /// Percentage done, from bytes or (for fragmented streams without a size) fragments
+    pub fn percent(&self) -> Option<f32> {
+        if let (Some(done), Some(total)) = (self.downloaded_bytes, self.total_bytes) {
+            if total > 0 {
+                return Some((done as f32 / total as f32 * 100.0).clamp(0.0, 100.0));
+            }
+        }
+        match (self.fragment_index, self.fragment_count) {
+            (Some(index), Some(count)) if count > 0 => Some((index as f32 / count as f32 * 100.0).clamp(0.0, 100.0)),
+            _ => None,
+        }
+    }
+}
+
+/// What a running yt-dlp reports
+#[derive(Debug, Clone, PartialEq)]
+pub enum YtDlpEvent {
+    Progress(TransferProgress),
+    /// A postprocessor started, by its key, e.g. "FFmpegExtractAudio", "FFmpegMerger", "FFmpegMetadata"
+    PostProcessing(String),
+}
+
+/// Whether a postprocessor produces the final audio file (extracting audio or merging formats)
+/// Keys are matched with and without the "FFmpeg" prefix, which yt-dlp versions report differently
+fn is_conversion_step(postprocessor: &str) -> bool {
+    matches!(
+        postprocessor.strip_prefix("FFmpeg").unwrap_or(postprocessor),
+        "ExtractAudio" | "Merger"
+    )
+}
+
+/// Result of a successful yt-dlp run
+#[derive(Debug, Clone, Default)]
+pub struct YtDlpOutput {
+    /// Final audio file announced by yt-dlp, when it printed one
+    pub output_path: Option<String>,
+}
+
+/// One entry of a playlist listed with --flat-playlist (not downloaded or fully extracted)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlatEntry {
+    pub id: Option<String>,
+    pub url: Option<String>,
+    pub title: Option<String>,
+}
+
+/// A playlist, set or channel listed with --flat-playlist, in playlist order
+#[derive(Debug, Clone, Default)]
+pub struct FlatPlaylist {
+    pub title: Option<String>,
+    pub entries: Vec<FlatEntry>,
+}
+
+impl FlatPlaylist {
+    /// Add one line of --flat-playlist --dump-json output; other lines are ignored
+    pub fn push_line(&mut self, line: &str) {
+        let Some((title, entry)) = parse_flat_entry(line) else {
+            return;
+        };
+        if self.title.is_none() {
+            self.title = title;
+        }
+        self.entries.push(entry);
+    }
+}
+
+/// Parse one --flat-playlist --dump-json line into (playlist title, entry)
+fn parse_flat_entry(line: &str) -> Option<(Option<String>, FlatEntry)> {
+    let json: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    let text = |key: &str| {
+        json.get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let entry = FlatEntry {
+        id: text("id"),
+        url: text("url").or_else(|| text("webpage_url")),
+        title: text("title"),
+    };
+    Some((text("playlist_title").or_else(|| text("playlist")), entry))
+}
+
+/// Parse one stdout line into an event, if it's one of our template lines
+pub fn parse_event(line: &str) -> Option<YtDlpEvent> {
+    let line = line.trim();
+
+    if let Some(json) = line.strip_prefix(PROGRESS_PREFIX) {
+        let json: serde_json::Value = serde_json::from_str(json.trim()).ok()?;
+        let status = json.get("status").and_then(|v| v.as_str())?;
+        if status != "downloading" && status != "finished" {
+            return None;
+        }
+        // Sizes and ETA are sometimes floats (estimates), so read everything as f64
+        let number = |key: &str| json.get(key).and_then(|v| v.as_f64()).filter(|n| *n >= 0.0);
+        return Some(YtDlpEvent::Progress(TransferProgress {
+            downloaded_bytes: number("downloaded_bytes").map(|n| n as u64),
+            total_bytes: number("total_bytes").or_else(|| number("total_bytes_estimate")).map(|n| n as u64),
+            speed: number("speed"),
+            eta_secs: number("eta").map(|n| n as u64),
+            fragment_index: number("fragment_index").map(|n| n as u64),
+            fragment_count: number("fragment_count").map(|n| n as u64),
+        }));
+    }
+
+    if let Some(json) = line.strip_prefix(POSTPROCESS_PREFIX) {
+        let json: serde_json::Value = serde_json::from_str(json.trim()).ok()?;
+        if json.get("status").and_then(|v| v.as_str()) != Some("started") {
+            return None;
+        }
+        let postprocessor = json.get("postprocessor").and_then(|v| v.as_str())?;
+        return Some(YtDlpEvent::PostProcessing(postprocessor.to_string()));
+    }
+
+    None
+}
+
+/// Final audio path announced by a postprocessor line, if any
+/// - [ExtractAudio] Destination: /path/file.mp3
+/// - [ExtractAudio] Not converting audio /path/file.mp3; the file is already in a common audio format
+/// - [Merger] Merging formats into "/path/file.m4a"
+fn output_path_from_line(line: &str) -> Option<String> {
+    if !line.contains("[ExtractAudio]") && !line.contains("[Merger]") {
+        return None;
+    }
+    let path = if let Some(quoted) = line.split('"').nth(1) {
+        quoted
+    } else if let Some(rest) = line.split("Not converting audio ").nth(1) {
+        rest.split(';').next().unwrap_or(rest)
+    } else {
+        line.split("Destination:").nth(1)?
+    };
+    let path = path.trim();
+    let (_, extension) = crate::utils::filesystem::split_audio_extension(std::path::Path::new(path));
+    extension.map(|_| path.to_string())
+}
+
+/// Error kind for a non-zero yt-dlp exit, given its last error line
+/// Unrecognised failures are mostly extractor or connection hiccups, so they're worth a retry;
+/// only a failed postprocessing step (ffmpeg on a file already on disk) is final
+fn exit_error_kind(detail: &str) -> ErrorKind {
+    match ErrorKind::from_message(detail) {
+        ErrorKind::ProcessFailed if !detail.to_lowercase().contains("postprocessing") => ErrorKind::Network,
+        kind => kind,
+    }
+}
+
+/// "1.2 MB/s"
+pub fn format_speed(bytes_per_sec: f64) -> String {
+    const UNITS: &[&str] = &["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// "0:42", "12:05", "1:02:05"
+pub fn format_eta(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+// ============================================================================
+// Runner
+// ============================================================================
+
+pub struct YtDlpRunner;
+
+impl YtDlpRunner {
+    /// Run yt-dlp with the given arguments, reporting typed events until it exits
+    /// Waits for a download slot first; stops early (killing yt-dlp) if the job is paused or cancelled
+    pub async fn run(
+        app: &AppHandle,
+        job_id: &str,
+        args: &[&str],
+        mut on_event: impl FnMut(YtDlpEvent),
+    ) -> AppResult<YtDlpOutput> {
+        let sidecar = app.shell().sidecar("yt-dlp")
+            .or_error(ErrorKind::DependencyMissing, "Failed to get yt-dlp sidecar")?;
+
+        let progress_template = format!("download:{} %(progress)j", PROGRESS_PREFIX);
+        let postprocess_template = format!("postprocess:{} %(progress)j", POSTPROCESS_PREFIX);
+        let mut all_args: Vec<&str> = args.to_vec();
+        all_args.extend([
+            "--progress",
+            "--newline",
+            "--progress-template", progress_template.as_str(),
+            "--progress-template", postprocess_template.as_str(),
+        ]);
+
+        let _download_permit = DownloadResource::YtDlpDownload.acquire_for_job(job_id).await?;
+
+        let (mut rx, child) = sidecar.args(all_args).spawn()
+            .or_error(ErrorKind::DependencyMissing, "Failed to spawn yt-dlp")?;
+        let mut control = JobControl::subscribe(job_id);
+        let mut output = YtDlpOutput::default();
+        let mut last_error_line = String::new();
+        let mut stderr_tail: Vec<String> = Vec::new();
+
+        loop {
+            let event = tokio::select! {
+                event = rx.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                signal = control.stopped() => {
+                    println!("[yt-dlp] {}, stopping yt-dlp", signal.stop_message());
+                    child.kill().ok();
+                    return Err(signal.stop_error());
+                }
+            };
+
+            match event {
+                CommandEvent::Stdout(line) => {
+                    let line_str = String::from_utf8_lossy(&line).to_string();
+                    if let Some(event) = parse_event(&line_str) {
+                        on_event(event);
+                        continue;
+                    }
+                    println!("[yt-dlp] {}", line_str);
+                    JobControl::track_ytdlp_destination(job_id, &line_str);
+                    if let Some(path) = output_path_from_line(&line_str) {
+                        output.output_path = Some(path);
+                    }
+                }
+                CommandEvent::Stderr(line) => {
+                    let line_str = String::from_utf8_lossy(&line).trim().to_string();
+                    eprintln!("[yt-dlp stderr] {}", line_str);
+                    if line_str.is_empty() {
+                        continue;
+                    }
+                    // Keep the last error so failures can be classified for retry
+                    if line_str.contains("ERROR") {
+                        last_error_line = line_str.clone();
+                    }
+                    stderr_tail.push(line_str);
+                    if stderr_tail.len() > STDERR_LINES_KEPT {
+                        stderr_tail.remove(0);
+                    }
+                }
+                CommandEvent::Error(error) => {
+                    return Err(AppError::new(ErrorKind::ProcessFailed, format!("yt-dlp error: {}", error)));
+                }
+                CommandEvent::Terminated(payload) => {
+                    if payload.code != Some(0) {
+                        // Without an ERROR line, the last thing yt-dlp printed is the best clue
+                        let detail = if last_error_line.is_empty() {
+                            stderr_tail.last().cloned().unwrap_or_default()
+                        } else {
+                            last_error_line
+                        };
+                        let error_msg = if detail.is_empty() {
+                            format!("yt-dlp exited with code: {:?}", payload.code)
+                        } else {
+                            format!("yt-dlp exited with code: {:?}: {}", payload.code, detail)
+                        };
+                        return Err(AppError::new(exit_error_kind(&detail), error_msg));
+                    }
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// List a playlist's entries without extracting each one (fast, one request per page)
+    pub async fn flat_playlist(app: &AppHandle, url: &str) -> AppResult<FlatPlaylist> {
+        let mut playlist = FlatPlaylist::default();
+        let last_error =
+            Self::run_lookup(app, &["--flat-playlist", "--dump-json", "--no-warnings", url], |line| playlist.push_line(line))
+                .await?;
+
+        match last_error {
+            Some(error) if playlist.entries.is_empty() => Err(AppError::new(ErrorKind::from_message(&error), error)),
+            _ => Ok(playlist),
+        }
+    }
+
+    /// Run a --dump-json / --dump-single-json lookup and parse its output as one JSON document
+    pub async fn dump_json(app: &AppHandle, args: &[&str]) -> AppResult<serde_json::Value> {
+        let mut output = String::new();
+        let last_error = Self::run_lookup(app, args, |line| output.push_str(line)).await?;
+
+        serde_json::from_str(&output).map_err(|_| match last_error {
+            Some(error) => AppError::new(ErrorKind::from_message(&error), error),
+            None => AppError::new(ErrorKind::Parse, "yt-dlp returned no JSON"),
+        })
+    }
+
+    /// Run yt-dlp for a lookup (nothing is downloaded), passing each stdout line to `on_line`
+    /// Returns the last ERROR line yt-dlp printed, if any
+    async fn run_lookup(app: &AppHandle, args: &[&str], mut on_line: impl FnMut(&str)) -> AppResult<Option<String>> {
+        let _search_permit = DownloadResource::YtDlpSearch.acquire().await;
+
+        let sidecar = app.shell().sidecar("yt-dlp")
+            .or_error(ErrorKind::DependencyMissing, "Failed to get yt-dlp sidecar")?;
+
+        let (mut rx, _child) = sidecar
+            .args(args)
+            .spawn()
+            .or_error(ErrorKind::DependencyMissing, "Failed to spawn yt-dlp")?;
+
+        let mut last_error_line = None;
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => on_line(&String::from_utf8_lossy(&line)),
+                CommandEvent::Stderr(line) => {
+                    let line_str = String::from_utf8_lossy(&line);
+                    if line_str.contains("ERROR") {
+                        last_error_line = Some(line_str.trim().to_string());
+                    }
+                }
+                CommandEvent::Terminated(_) => break,
+                _ => {}
+            }
+        }
+        Ok(last_error_line)
+    }
+
+    /// Download audio with yt-dlp, reporting progress on the job
+    /// Download progress is mapped onto `progress_range` of the job's overall progress;
+    /// speed and ETA are stored on the job and shown in its status message
+    pub async fn download_audio(
+        app: &AppHandle,
+        job_id: &str,
+        args: &[&str],
+        progress_range: (f32, f32),
+        update_status_fn: impl Fn(&str, DownloadStatus, f32, &str),
+        emit_queue_fn: impl Fn(),
+    ) -> AppResult<YtDlpOutput> {
+        let audio_format = crate::utils::get_audio_format();
+        let (start, end) = progress_range;
+        let mut last_progress = start;
+        let mut converting = false;
+
+        update_status_fn(job_id, DownloadStatus::Downloading, start, "Downloading...");
+
+        let result = Self::run(app, job_id, args, |event| match event {
+            YtDlpEvent::Progress(progress) => {
+                let Some(pct) = progress.percent() else {
+                    return;
+                };
+                last_progress = start + pct / 100.0 * (end - start);
+                QueueManager::set_job_transfer(job_id, progress.speed, progress.eta_secs);
+
+                let mut message = format!("Downloading... {:.1}%", pct);
+                if let Some(speed) = progress.speed {
+                    message.push_str(&format!(" · {}", format_speed(speed)));
+                }
+                if let Some(eta) = progress.eta_secs {
+                    message.push_str(&format!(" · {} left", format_eta(eta)));
+                }
+                update_status_fn(job_id, DownloadStatus::Downloading, last_progress, &message);
+                // Emit queue update for real-time UI refresh
+                emit_queue_fn();
+            }
+            YtDlpEvent::PostProcessing(postprocessor) => {
+                if converting || !is_conversion_step(&postprocessor) {
+                    return;
+                }
+                converting = true;
+                update_status_fn(
+                    job_id,
+                    DownloadStatus::Converting,
+                    92.0,
+                    &format!("Converting to {}...", audio_format.display_name()),
+                );
+                emit_queue_fn();
+            }
+        })
+        .await;
+
+        if let Err(error) = &result {
+            if !matches!(error.kind(), ErrorKind::Paused | ErrorKind::Cancelled) {
+                update_status_fn(job_id, DownloadStatus::Error, last_progress, error.message());
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        let line = r#"[hasod:progress] {"status": "downloading", "downloaded_bytes": 524288, "total_bytes": null, "total_bytes_estimate": 2097152.0, "speed": 131072.5, "eta": 12, "fragment_index": 3, "fragment_count": 12}"#;
+        let Some(YtDlpEvent::Progress(progress)) = parse_event(line) else {
+            panic!("expected a progress event");
+        };
+        assert_eq!(progress.downloaded_bytes, Some(524288));
+        assert_eq!(progress.total_bytes, Some(2097152));
+        assert_eq!(progress.eta_secs, Some(12));
+        assert_eq!(progress.fragment_count, Some(12));
+        assert_eq!(progress.percent(), Some(25.0));
+
+        let line = r#"[hasod:postprocess] {"status": "started", "postprocessor": "ExtractAudio"}"#;
+        assert_eq!(parse_event(line), Some(YtDlpEvent::PostProcessing("ExtractAudio".to_string())));
+        assert_eq!(parse_event(r#"[hasod:postprocess] {"status": "finished", "postprocessor": "ExtractAudio"}"#), None);
+        assert_eq!(parse_event("[download]  45.2% of 10.00MiB at 1.00MiB/s ETA 00:05"), None);
+
+        let line = r#"[hasod:postprocess] {"status": "started", "postprocessor": "FFmpegMerger"}"#;
+        let Some(YtDlpEvent::PostProcessing(postprocessor)) = parse_event(line) else {
+            panic!("expected a postprocessing event");
+        };
+        assert!(is_conversion_step(&postprocessor));
+        assert!(is_conversion_step("Merger"));
+        assert!(is_conversion_step("FFmpegExtractAudio"));
+        assert!(!is_conversion_step("FFmpegMetadata"));
+        assert!(!is_conversion_step("MoveFiles"));
+    }
+
+    #[test]
+    fn test_parse_flat_entry() {
+        let line = r#"{"_type": "url", "ie_key": "Soundcloud", "id": "123", "url": "https://soundcloud.com/artist/track", "title": "Track", "playlist_title": "My Set", "playlist_index": 1}"#;
+        let (title, entry) = parse_flat_entry(line).unwrap();
+        assert_eq!(title.as_deref(), Some("My Set"));
+        assert_eq!(entry.url.as_deref(), Some("https://soundcloud.com/artist/track"));
+        assert_eq!(entry.id.as_deref(), Some("123"));
+        assert!(parse_flat_entry("WARNING: not json").is_none());
+    }
+
+    #[test]
+    fn test_output_path_from_line() {
+        assert_eq!(
+            output_path_from_line("[ExtractAudio] Destination: /music/A - B.mp3").as_deref(),
+            Some("/music/A - B.mp3")
+        );
+        assert_eq!(
+            output_path_from_line("[ExtractAudio] Not converting audio /music/A - B.opus; the file is already in a common audio format").as_deref(),
+            Some("/music/A - B.opus")
+        );
+        assert_eq!(
+            output_path_from_line(r#"[Merger] Merging formats into "/music/A - B.m4a""#).as_deref(),
+            Some("/music/A - B.m4a")
+        );
+        assert_eq!(output_path_from_line("[download] Destination: /music/A - B.webm.part"), None);
+    }
+
+    #[test]
+    fn test_exit_error_kind() {
+        // A bare non-zero exit is retried, like any other transient failure
+        assert_eq!(exit_error_kind(""), ErrorKind::Network);
+        assert_eq!(exit_error_kind("ERROR: [youtube] abc: Something went wrong"), ErrorKind::Network);
+        assert!(exit_error_kind("").is_transient());
+        assert_eq!(exit_error_kind("ERROR: [youtube] abc: Video unavailable"), ErrorKind::NotFound);
+        assert_eq!(exit_error_kind("ERROR: Postprocessing: something broke"), ErrorKind::ProcessFailed);
+    }
+
+    #[test]
+    fn test_format_speed_and_eta() {
+        assert_eq!(format_speed(512.0), "512 B/s");
+        assert_eq!(format_speed(1_240_000.0), "1.2 MB/s");
+        assert_eq!(format_eta(42), "0:42");
+        assert_eq!(format_eta(3725), "1:02:05");
+    }
+}
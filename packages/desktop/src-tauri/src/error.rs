@@ -0,0 +1,282 @@
+// Crate-wide error type
+// Every fallible function returns AppResult<T>. Errors carry a machine-readable kind,
+// a human message and an optional source chain, and serialize to the frontend as
+// { code, message, causes } so the UI can react to "license expired" vs "yt-dlp missing".
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+// ============================================================================
+// Error Kind
+// ============================================================================
+
+/// Machine-readable error category (sent to the frontend as `code`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Network,           // Connection failure, timeout, DNS
+    RateLimited,       // HTTP 429 / YouTube "Too Many Requests"
+    ServerError,       // HTTP 5xx from an upstream service
+    NotFound,          // Track, album or video doesn't exist
+    Unsupported,       // Service or page type we can't download
+    InvalidInput,      // Malformed URL or setting value
+    AuthRequired,      // Not logged in or token expired
+    LicenseRequired,   // No active hasod-downloader subscription
+    DependencyMissing, // yt-dlp / ffmpeg sidecar unavailable
+    ProcessFailed,     // Sidecar exited with an error
+    Io,                // Filesystem error
+    InsufficientSpace, // Not enough free space on the download drive
+    Database,          // Download history database error
+    Parse,             // Unexpected response format
+    Duplicate,         // Already downloaded and the duplicate policy says skip
+    NeedsReview,       // No clear YouTube match - waiting for the user to pick a source
+    Cancelled,         // Stopped by the user
+    Paused,            // Paused by the user
+    Internal,          // Lock poisoning and other bugs
+}
+
+impl ErrorKind {
+    /// Whether retrying the same operation later may succeed
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ErrorKind::Network | ErrorKind::RateLimited | ErrorKind::ServerError)
+    }
+
+    /// Map an HTTP status code to an error kind
+    pub fn from_status(status: reqwest::StatusCode) -> Self {
+        match status.as_u16() {
+            401 => ErrorKind::AuthRequired,
+            402 | 403 => ErrorKind::LicenseRequired,
+            404 | 410 => ErrorKind::NotFound,
+            429 => ErrorKind::RateLimited,
+            400..=499 => ErrorKind::InvalidInput,
+            500..=599 => ErrorKind::ServerError,
+            _ => ErrorKind::Network,
+        }
+    }
+
+    /// Classify a free-form error line (e.g. yt-dlp stderr) into a kind
+    pub fn from_message(message: &str) -> Self {
+        let lower = message.to_lowercase();
+
+        if lower.contains("429") || lower.contains("too many requests") || lower.contains("rate limit")
+            || lower.contains("confirm you're not a bot")
+        {
+            return ErrorKind::RateLimited;
+        }
+        if has_server_error_status(&lower) {
+            return ErrorKind::ServerError;
+        }
+        if lower.contains("404") || lower.contains("not found") || lower.contains("video unavailable")
+            || lower.contains("private video") || lower.contains("has been removed")
+        {
+            return ErrorKind::NotFound;
+        }
+        if lower.contains("unsupported url") || lower.contains("not available in your country") {
+            return ErrorKind::Unsupported;
+        }
+        if lower.contains("401") || lower.contains("403") || lower.contains("sign in") {
+            return ErrorKind::AuthRequired;
+        }
+        if lower.contains("no space left on device") || lower.contains("disk full") {
+            return ErrorKind::InsufficientSpace;
+        }
+        if lower.contains("timed out") || lower.contains("timeout") || lower.contains("connection")
+            || lower.contains("network") || lower.contains("temporarily") || lower.contains("unable to download")
+        {
+            return ErrorKind::Network;
+        }
+
+        ErrorKind::ProcessFailed
+    }
+}
+
+/// Detect "status 503" / "HTTP Error 502" style server errors
+fn has_server_error_status(lower: &str) -> bool {
+    let tokens: Vec<&str> = lower
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    tokens.windows(2).any(|pair| {
+        (pair[0] == "status" || pair[0] == "error")
+            && pair[1].len() == 3
+            && pair[1].starts_with('5')
+            && pair[1].chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+// ============================================================================
+// App Error
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct AppError {
+    kind: ErrorKind,
+    message: String,
+    source: Option<Arc<dyn std::error::Error + Send + Sync + 'static>>,
+}
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Attach the underlying error
+    pub fn with_source(mut self, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    /// Error for a non-success HTTP response
+    pub fn http(status: reqwest::StatusCode, message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::from_status(status), message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Internal, message)
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Messages of every error in the source chain, outermost first
+    pub fn causes(&self) -> Vec<String> {
+        let mut causes = Vec::new();
+        let mut current: Option<&(dyn std::error::Error + 'static)> =
+            self.source.as_deref().map(|s| s as &(dyn std::error::Error + 'static));
+        while let Some(err) = current {
+            causes.push(err.to_string());
+            current = err.source();
+        }
+        causes
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(source) = &self.source {
+            write!(f, ": {}", source)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|s| s as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// Serialized form sent to the frontend
+#[derive(Serialize)]
+struct SerializedError {
+    code: ErrorKind,
+    message: String,
+    causes: Vec<String>,
+}
+
+impl Serialize for AppError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedError {
+            code: self.kind,
+            message: self.to_string(),
+            causes: self.causes(),
+        }
+        .serialize(serializer)
+    }
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+impl From<reqwest::Error> for AppError {
+    fn from(err: reqwest::Error) -> Self {
+        let kind = if let Some(status) = err.status() {
+            ErrorKind::from_status(status)
+        } else if err.is_decode() {
+            ErrorKind::Parse
+        } else {
+            ErrorKind::Network
+        };
+        Self::new(kind, "HTTP request failed").with_source(err)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        Self::new(ErrorKind::Io, "Filesystem error").with_source(err)
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::new(ErrorKind::Database, "Database error").with_source(err)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::new(ErrorKind::Parse, "JSON error").with_source(err)
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(err: std::sync::PoisonError<T>) -> Self {
+        Self::internal(format!("Lock error: {}", err))
+    }
+}
+
+/// Attach a kind and context message to any error
+pub trait ResultExt<T> {
+    fn or_error(self, kind: ErrorKind, message: impl Into<String>) -> AppResult<T>;
+}
+
+impl<T, E> ResultExt<T> for Result<T, E>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn or_error(self, kind: ErrorKind, message: impl Into<String>) -> AppResult<T> {
+        self.map_err(|e| AppError::new(kind, message).with_source(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_message() {
+        assert_eq!(ErrorKind::from_message("ERROR: HTTP Error 429: Too Many Requests"), ErrorKind::RateLimited);
+        assert_eq!(ErrorKind::from_message("ERROR: HTTP Error 503: Service Unavailable"), ErrorKind::ServerError);
+        assert_eq!(ErrorKind::from_message("ERROR: [youtube] abc: Video unavailable"), ErrorKind::NotFound);
+        assert_eq!(ErrorKind::from_message("ERROR: Unsupported URL: https://example.com"), ErrorKind::Unsupported);
+        assert_eq!(ErrorKind::from_message("ERROR: Unable to download webpage: timed out"), ErrorKind::Network);
+        assert_eq!(ErrorKind::from_message("ERROR: unable to write data: [Errno 28] No space left on device"), ErrorKind::InsufficientSpace);
+        assert_eq!(ErrorKind::from_message("ERROR: Postprocessing: something broke"), ErrorKind::ProcessFailed);
+    }
+
+    #[test]
+    fn test_serialized_form() {
+        let io_err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let err = AppError::new(ErrorKind::Io, "Failed to write settings file").with_source(io_err);
+
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(json["code"], "io");
+        assert_eq!(json["message"], "Failed to write settings file: denied");
+        assert_eq!(json["causes"][0], "denied");
+    }
+}
@@ -21,6 +21,7 @@ use tauri::{
 mod api_types;
 mod auth;
 mod download;
+mod error;
 mod platform;
 mod utils;
 mod commands;
@@ -104,6 +105,26 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .setup(|app| {
+            // Restore the download queue persisted by the previous session
+            match QueueManager::restore() {
+                Ok(count) => println!("[Queue] Restored {} jobs from disk", count),
+                Err(e) => eprintln!("[Queue] Failed to restore queue: {}", e),
+            }
+
+            // The asset scope in tauri.conf.json only covers ~/Downloads - add the chosen
+            // download directory and any per-job overrides restored with the queue
+            let mut download_dirs = std::collections::HashSet::new();
+            download_dirs.insert(utils::get_download_dir());
+            if let Ok(status) = QueueManager::get_status() {
+                download_dirs.extend(status.jobs.into_iter().filter_map(|job| job.output_dir));
+            }
+            for dir in &download_dirs {
+                utils::filesystem::allow_asset_directory(app.handle(), dir);
+            }
+
+            // Idles until the clipboard watcher is enabled in settings
+            platform::ClipboardWatcher::start(app.handle().clone());
+
             // Create system tray menu items
             let show_item = MenuItem::with_id(app, "show", "Show App", true, None::<&str>)?;
             let toggle_floating_item =
@@ -182,26 +203,66 @@ pub fn run() {
             commands::add_multiple_to_queue,
             commands::add_spotify_album_to_queue,
             commands::add_spotify_playlist_to_queue,
+            commands::add_links_to_queue,
+            commands::import_playlist,
+            commands::get_spotify_artist_releases,
+            commands::add_spotify_artist_releases_to_queue,
+            commands::add_spotify_artist_top_tracks_to_queue,
+            commands::add_apple_music_album_to_queue,
+            commands::add_apple_music_playlist_to_queue,
             commands::add_youtube_playlist_to_queue,
+            commands::add_collection_to_queue,
             commands::get_queue_status,
             commands::clear_completed_jobs,
             commands::clear_all_queue,
             commands::remove_from_queue,
+            commands::cancel_job,
+            commands::pause_job,
+            commands::resume_job,
+            commands::resolve_job_source,
+            commands::retry_failed_jobs,
             commands::start_queue_processing,
+            // Download history
+            commands::get_download_history,
+            commands::remove_history_entry,
+            commands::clear_download_history,
+            commands::redownload_from_history,
             // Legacy download commands
             commands::download_youtube,
             commands::download_spotify,
             commands::get_download_dir,
             commands::create_download_dir,
+            commands::set_download_dir,
+            commands::validate_download_dir,
             commands::open_file_location,
             // Platform-specific
             commands::toggle_floating_window,
             commands::is_floating_window_open,
             commands::get_clipboard_url,
             commands::handle_dropped_link,
+            commands::parse_link,
+            commands::extract_links,
+            commands::preview_link,
+            commands::extract_clipboard_links,
+            commands::offer_dropped_links,
             // Settings
             commands::get_english_only_mode,
             commands::set_english_only_mode,
+            commands::get_max_concurrent_downloads,
+            commands::set_max_concurrent_downloads,
+            commands::get_resource_limits,
+            commands::set_resource_limits,
+            commands::get_duplicate_policy,
+            commands::set_duplicate_policy,
+            commands::get_output_templates,
+            commands::set_output_templates,
+            commands::preview_output_path,
+            commands::get_audio_format,
+            commands::set_audio_format,
+            commands::get_lyrics_settings,
+            commands::set_lyrics_settings,
+            commands::get_clipboard_watch_settings,
+            commands::set_clipboard_watch_settings,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -3,68 +3,94 @@
 
 use std::process::Command;
 
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+
 /// Cross-platform clipboard manager
 /// Uses native shell commands for clipboard access:
 /// - macOS: pbpaste
 /// - Windows: PowerShell Get-Clipboard
-/// - Linux: xclip
+/// - Linux: wl-paste on Wayland, xclip on X11
 pub struct ClipboardManager;
 
 impl ClipboardManager {
     /// Get URL from clipboard (cross-platform)
-    /// Returns Ok(url) if clipboard contains a valid HTTP/HTTPS URL
-    /// Returns Err if clipboard is empty, not a URL, or clipboard access fails
-    pub async fn get_url() -> Result<String, String> {
-        // macOS: use pbpaste
-        #[cfg(target_os = "macos")]
-        {
-            let output = Command::new("pbpaste")
-                .output()
-                .map_err(|e| format!("Failed to read clipboard: {}", e))?;
-
-            let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    /// Returns Ok(url) if clipboard is a HTTP/HTTPS URL, or text containing a music link
+    /// (the first one, e.g. from a copied chat message)
+    /// Returns Err if clipboard is empty, has no link, or clipboard access fails
+    pub async fn get_url() -> AppResult<String> {
+        let text = Self::get_text().await?;
 
-            if text.starts_with("http://") || text.starts_with("https://") {
-                return Ok(text);
-            }
-            return Err("Clipboard does not contain a valid URL".to_string());
+        if text.starts_with("http://") || text.starts_with("https://") {
+            return Ok(text);
         }
+        crate::download::links::find_links(&text)
+            .into_iter()
+            .next()
+            .map(|link| link.url)
+            .ok_or_else(|| AppError::new(ErrorKind::InvalidInput, "Clipboard does not contain a valid URL"))
+    }
 
-        // Windows: use PowerShell
+    /// Get the clipboard's text content, trimmed
+    pub async fn get_text() -> AppResult<String> {
+        // macOS: use pbpaste
+        #[cfg(target_os = "macos")]
+        let output = Command::new("pbpaste")
+            .output()
+            .or_error(ErrorKind::DependencyMissing, "Failed to read clipboard")?;
+
+        // Windows: use PowerShell, without a console window (the clipboard watcher reads every second)
         #[cfg(target_os = "windows")]
-        {
-            let output = Command::new("powershell")
-                .args(["-Command", "Get-Clipboard"])
+        let output = {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+            Command::new("powershell")
+                .args(["-NoProfile", "-Command", "Get-Clipboard"])
+                .creation_flags(CREATE_NO_WINDOW)
                 .output()
-                .map_err(|e| format!("Failed to read clipboard: {}", e))?;
+                .or_error(ErrorKind::DependencyMissing, "Failed to read clipboard")?
+        };
 
-            let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-            if text.starts_with("http://") || text.starts_with("https://") {
-                return Ok(text);
-            }
-            return Err("Clipboard does not contain a valid URL".to_string());
-        }
-
-        // Linux: use xclip
+        // Linux: use wl-paste or xclip
         #[cfg(target_os = "linux")]
-        {
-            let output = Command::new("xclip")
-                .args(["-selection", "clipboard", "-o"])
-                .output()
-                .map_err(|e| format!("Failed to read clipboard: {}", e))?;
+        let output = Self::linux_clipboard_output()?;
 
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        {
             let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-            if text.starts_with("http://") || text.starts_with("https://") {
-                return Ok(text);
+            if text.is_empty() {
+                return Err(AppError::new(ErrorKind::InvalidInput, "Clipboard is empty"));
             }
-            return Err("Clipboard does not contain a valid URL".to_string());
+            Ok(text)
         }
 
         #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
         {
-            Err("Clipboard reading not supported on this platform".to_string())
+            Err(AppError::new(ErrorKind::Unsupported, "Clipboard reading not supported on this platform"))
+        }
+    }
+
+    /// Wayland sessions use wl-paste (wl-clipboard); X11 sessions - and Wayland sessions
+    /// without wl-clipboard installed - use xclip through XWayland
+    #[cfg(target_os = "linux")]
+    fn linux_clipboard_output() -> AppResult<std::process::Output> {
+        let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+        if wayland {
+            // wl-paste exits with an error when the clipboard is empty or holds no text
+            if let Ok(output) = Command::new("wl-paste").args(["--no-newline", "--type", "text"]).output() {
+                if output.status.success() {
+                    return Ok(output);
+                }
+            }
         }
+
+        let message = if wayland {
+            "Failed to read clipboard (install wl-clipboard or xclip)"
+        } else {
+            "Failed to read clipboard"
+        };
+        Command::new("xclip")
+            .args(["-selection", "clipboard", "-o"])
+            .output()
+            .or_error(ErrorKind::DependencyMissing, message)
     }
 }
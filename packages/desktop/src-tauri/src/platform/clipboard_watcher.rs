@@ -0,0 +1,202 @@
+// Clipboard watcher - opt-in background capture of copied music links
+// Polls the clipboard while enabled in settings. Once the clipboard text has stayed the
+// same for the debounce period, the supported links in it are either offered to the UI
+// ("clipboard-links" event) or queued right away, depending on the mode.
+// Auto-queueing needs a valid license; without one, links are only offered.
+
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::download::links::{resolve_links, ParsedLink};
+use crate::download::DownloadContext;
+use crate::platform::ClipboardManager;
+use crate::utils::{get_clipboard_watch_settings, ClipboardWatchMode, ClipboardWatchSettings};
+
+/// How often the clipboard is read while the watcher is enabled
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the settings are checked while the watcher is off
+const IDLE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Watcher settings, loaded once and replaced when they're changed in the UI
+static WATCH_SETTINGS: LazyLock<Mutex<ClipboardWatchSettings>> =
+    LazyLock::new(|| Mutex::new(get_clipboard_watch_settings()));
+
+/// Payload of the "clipboard-links" event
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardLinks {
+    pub links: Vec<ParsedLink>,
+    pub queued: bool,  // AutoQueue mode already queued them
+}
+
+pub struct ClipboardWatcher;
+
+impl ClipboardWatcher {
+    /// Start the background loop (once, at app startup)
+    /// It idles until the watcher is enabled in settings
+    pub fn start(app: AppHandle) {
+        tauri::async_runtime::spawn(async move {
+            let mut state = WatchState::default();
+            loop {
+                let settings = WATCH_SETTINGS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+                if settings.mode == ClipboardWatchMode::Off {
+                    state.enabled = false;
+                    tokio::time::sleep(IDLE_INTERVAL).await;
+                    continue;
+                }
+
+                let text = ClipboardManager::get_text().await.ok();
+                if !state.enabled {
+                    // Whatever was copied before the watcher was turned on isn't captured
+                    println!("[Clipboard] Watching clipboard ({:?})", settings.mode);
+                    state.reset(text);
+                } else if let Some(text) = state.observe(text, Instant::now(), Duration::from_millis(settings.debounce_ms)) {
+                    let links: Vec<ParsedLink> = resolve_links(&text)
+                        .await
+                        .into_iter()
+                        .filter(|link| !is_ignored(link, &settings.ignore) && state.seen.insert(link.url.clone()))
+                        .collect();
+                    if !links.is_empty() {
+                        Self::handle_links(&app, settings.mode, links).await;
+                    }
+                }
+
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Use new settings from the next poll on
+    pub fn update_settings(settings: ClipboardWatchSettings) {
+        *WATCH_SETTINGS.lock().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
+    async fn handle_links(app: &AppHandle, mode: ClipboardWatchMode, links: Vec<ParsedLink>) {
+        println!("[Clipboard] Captured {} link(s)", links.len());
+        let mode = effective_mode(mode, crate::auth::has_valid_cached_license());
+        let mut queued = false;
+
+        if mode == ClipboardWatchMode::AutoQueue {
+            let urls = links.iter().map(|link| link.url.clone()).collect();
+            match crate::commands::queue_links(app, urls, None, &DownloadContext::Single).await {
+                Ok(jobs) => {
+                    println!("[Clipboard] Queued {} jobs", jobs.len());
+                    queued = true;
+                    crate::download::QueueManager::emit_update(app);
+                    tauri::async_runtime::spawn(crate::download::QueueManager::start_processing(app.clone()));
+                }
+                Err(e) => eprintln!("[Clipboard] Failed to queue links: {}", e),
+            }
+        }
+
+        app.emit("clipboard-links", ClipboardLinks { links, queued }).ok();
+    }
+}
+
+/// Auto-queueing downloads without the UI's license check, so it falls back to offering
+/// the links when the last license check didn't find a valid license
+fn effective_mode(mode: ClipboardWatchMode, licensed: bool) -> ClipboardWatchMode {
+    if mode == ClipboardWatchMode::AutoQueue && !licensed {
+        println!("[Clipboard] No valid license - offering links instead of queueing them");
+        return ClipboardWatchMode::Notify;
+    }
+    mode
+}
+
+/// Whether a link matches an ignore list entry (case-insensitive, anywhere in the canonical URL)
+fn is_ignored(link: &ParsedLink, ignore: &[String]) -> bool {
+    let url = link.url.to_lowercase();
+    ignore
+        .iter()
+        .map(|entry| entry.trim().to_lowercase())
+        .any(|entry| !entry.is_empty() && url.contains(&entry))
+}
+
+/// What the watcher has seen so far
+#[derive(Default)]
+struct WatchState {
+    enabled: bool,
+    last_text: Option<String>,             // Last clipboard text that was handled (or skipped)
+    pending: Option<(String, Instant)>,    // New text, and since when it's been on the clipboard
+    seen: HashSet<String>,                 // Links already captured this session
+}
+
+impl WatchState {
+    /// Start watching from the current clipboard content without capturing it
+    fn reset(&mut self, text: Option<String>) {
+        self.enabled = true;
+        self.last_text = text;
+        self.pending = None;
+    }
+
+    /// Feed the current clipboard text; returns it once it's new and has been stable for `debounce`
+    /// (copying several things in a row only captures the last one)
+    fn observe(&mut self, text: Option<String>, now: Instant, debounce: Duration) -> Option<String> {
+        let text = text?;
+        if self.last_text.as_ref() == Some(&text) {
+            self.pending = None;
+            return None;
+        }
+        match &self.pending {
+            Some((pending, since)) if *pending == text => {
+                if now.duration_since(*since) < debounce {
+                    return None;
+                }
+                self.pending = None;
+                self.last_text = Some(text.clone());
+                Some(text)
+            }
+            _ => {
+                self.pending = Some((text, now));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::download::links::parse_link;
+
+    #[test]
+    fn test_observe_debounces() {
+        let debounce = Duration::from_millis(1500);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let text = |s: &str| Some(s.to_string());
+
+        let mut state = WatchState::default();
+        state.reset(text("copied before watching"));
+        assert_eq!(state.observe(text("copied before watching"), at(1000), debounce), None);
+
+        // Replaced before the debounce period ends - only the last copy counts
+        assert_eq!(state.observe(text("first"), at(2000), debounce), None);
+        assert_eq!(state.observe(text("second"), at(3000), debounce), None);
+        assert_eq!(state.observe(text("second"), at(4000), debounce), None);
+        assert_eq!(state.observe(text("second"), at(4500), debounce), text("second"));
+
+        // Handled once, and an unreadable clipboard changes nothing
+        assert_eq!(state.observe(text("second"), at(9000), debounce), None);
+        assert_eq!(state.observe(None, at(9500), debounce), None);
+    }
+
+    #[test]
+    fn test_effective_mode() {
+        assert_eq!(effective_mode(ClipboardWatchMode::AutoQueue, true), ClipboardWatchMode::AutoQueue);
+        assert_eq!(effective_mode(ClipboardWatchMode::AutoQueue, false), ClipboardWatchMode::Notify);
+        assert_eq!(effective_mode(ClipboardWatchMode::Notify, false), ClipboardWatchMode::Notify);
+    }
+
+    #[test]
+    fn test_is_ignored() {
+        let link = parse_link("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap();
+        assert!(is_ignored(&link, &["YouTube.com".to_string()]));
+        assert!(is_ignored(&link, &["dQw4w9WgXcQ".to_string()]));
+        assert!(!is_ignored(&link, &["spotify.com".to_string(), " ".to_string()]));
+    }
+}
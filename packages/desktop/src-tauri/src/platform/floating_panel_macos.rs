@@ -4,6 +4,8 @@
 use std::sync::Mutex;
 use tauri::AppHandle; // Always import AppHandle for update_status API consistency
 
+use crate::error::{AppError, AppResult};
+
 #[cfg(target_os = "macos")]
 use tauri::{Emitter, Manager};
 
@@ -151,13 +153,13 @@ pub struct FloatingPanelManager;
 
 impl FloatingPanelManager {
     /// Toggle the floating window (create or close)
-    pub fn toggle(app: AppHandle) -> Result<(), String> {
+    pub fn toggle(app: AppHandle) -> AppResult<()> {
         // Store app handle for the message handler to use
-        *FLOATING_APP_HANDLE.lock().map_err(|e| format!("Lock error: {}", e))? = Some(app.clone());
+        *FLOATING_APP_HANDLE.lock()? = Some(app.clone());
 
         // Check if panel already exists
         {
-            let panel_guard = FLOATING_PANEL.lock().map_err(|e| format!("Lock error: {}", e))?;
+            let panel_guard = FLOATING_PANEL.lock()?;
             if let Some(panel_ptr) = *panel_guard {
                 // Panel exists - close it
                 let panel = panel_ptr as id;
@@ -165,9 +167,9 @@ impl FloatingPanelManager {
                     let _: () = msg_send![panel, close];
                 }
                 drop(panel_guard);
-                *FLOATING_PANEL.lock().map_err(|e| format!("Lock error: {}", e))? = None;
-                *FLOATING_WEBVIEW.lock().map_err(|e| format!("Lock error: {}", e))? = None;
-                *FLOATING_APP_HANDLE.lock().map_err(|e| format!("Lock error: {}", e))? = None;
+                *FLOATING_PANEL.lock()? = None;
+                *FLOATING_WEBVIEW.lock()? = None;
+                *FLOATING_APP_HANDLE.lock()? = None;
                 println!("[FloatingPanel] Closed existing panel");
                 return Ok(());
             }
@@ -188,6 +190,31 @@ impl FloatingPanelManager {
     /// Update the floating panel status (call JavaScript in webview)
     /// app parameter is for API consistency with Tauri version (not used on macOS)
     pub fn update_status(_app: &AppHandle, state: &str, progress: f32, title: &str, queue_count: usize) {
+        // Create JavaScript to call window.updateStatus
+        let js = format!(
+            r#"window.updateStatus({{state:'{}',progress:{},title:'{}',queueCount:{}}})"#,
+            state,
+            progress,
+            title.replace("'", "\\'"),
+            queue_count
+        );
+        Self::eval_status(&js);
+    }
+
+    /// Show download progress with an extra detail line (speed and ETA)
+    pub fn update_download(_app: &AppHandle, progress: f32, title: &str, detail: Option<&str>, queue_count: usize) {
+        let js = format!(
+            r#"window.updateStatus({{state:'downloading',progress:{},title:'{}',detail:'{}',queueCount:{}}})"#,
+            progress,
+            title.replace("'", "\\'"),
+            detail.unwrap_or_default().replace("'", "\\'"),
+            queue_count
+        );
+        Self::eval_status(&js);
+    }
+
+    /// Execute a status update in the panel's webview, if the panel is open
+    fn eval_status(js: &str) {
         use cocoa::base::{id, nil};
         use cocoa::foundation::NSString;
         #[allow(unused_imports)]
@@ -198,15 +225,7 @@ impl FloatingPanelManager {
             if let Some(webview_ptr) = *webview_guard {
                 let webview = webview_ptr as id;
                 unsafe {
-                    // Create JavaScript to call window.updateStatus
-                    let js = format!(
-                        r#"window.updateStatus({{state:'{}',progress:{},title:'{}',queueCount:{}}})"#,
-                        state,
-                        progress,
-                        title.replace("'", "\\'"),
-                        queue_count
-                    );
-                    let js_string = NSString::alloc(nil).init_str(&js);
+                    let js_string = NSString::alloc(nil).init_str(js);
                     let _: () = msg_send![webview, evaluateJavaScript:js_string completionHandler:nil];
                 }
             }
@@ -214,7 +233,7 @@ impl FloatingPanelManager {
     }
 
     /// Create the native NSPanel with WKWebView
-    fn create_panel(app: AppHandle) -> Result<(), String> {
+    fn create_panel(app: AppHandle) -> AppResult<()> {
         unsafe {
             // NSPanel style masks
             let style_mask: u64 = 0 | (1 << 7); // Borderless + NonactivatingPanel
@@ -233,7 +252,7 @@ impl FloatingPanelManager {
             ];
 
             if panel == nil {
-                return Err("Failed to create NSPanel".to_string());
+                return Err(AppError::internal("Failed to create NSPanel"));
             }
 
             // Set collection behavior: CanJoinAllSpaces | FullScreenAuxiliary
@@ -277,7 +296,7 @@ impl FloatingPanelManager {
 
             if webview == nil {
                 let _: () = msg_send![panel, close];
-                return Err("Failed to create WKWebView".to_string());
+                return Err(AppError::internal("Failed to create WKWebView"));
             }
 
             // Configure webview for transparency - use NSNumber to wrap boolean
@@ -313,8 +332,8 @@ impl FloatingPanelManager {
             }
 
             // Store panel and webview references
-            *FLOATING_PANEL.lock().map_err(|e| format!("Lock error: {}", e))? = Some(panel as usize);
-            *FLOATING_WEBVIEW.lock().map_err(|e| format!("Lock error: {}", e))? = Some(webview as usize);
+            *FLOATING_PANEL.lock()? = Some(panel as usize);
+            *FLOATING_WEBVIEW.lock()? = Some(webview as usize);
 
             println!("[FloatingPanel] Native NSPanel created with original appearance!");
         }
@@ -4,6 +4,8 @@
 
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
 
+use crate::error::{AppResult, ErrorKind, ResultExt};
+
 // ============================================================================
 // Floating Panel Manager (Tauri-based, cross-platform)
 // ============================================================================
@@ -12,11 +14,11 @@ pub struct FloatingPanelManager;
 
 impl FloatingPanelManager {
     /// Toggle the floating window (create or close)
-    pub fn toggle(app: AppHandle) -> Result<(), String> {
+    pub fn toggle(app: AppHandle) -> AppResult<()> {
         // Check if window already exists
         if let Some(window) = app.get_webview_window("floating-panel") {
             // Window exists - close it
-            window.close().map_err(|e| format!("Failed to close window: {}", e))?;
+            window.close().or_error(ErrorKind::Internal, "Failed to close window")?;
             println!("[FloatingPanel] Closed existing panel");
             return Ok(());
         }
@@ -32,25 +34,40 @@ impl FloatingPanelManager {
 
     /// Update the floating panel status (call JavaScript in webview)
     pub fn update_status(app: &AppHandle, state: &str, progress: f32, title: &str, queue_count: usize) {
+        // Create JavaScript to call window.updateStatus
+        let js = format!(
+            r#"window.updateStatus({{state:'{}',progress:{},title:'{}',queueCount:{}}})"#,
+            state,
+            progress,
+            title.replace("'", "\\'"), // Escape single quotes
+            queue_count
+        );
+        Self::eval_status(app, &js);
+    }
+
+    /// Show download progress with an extra detail line (speed and ETA)
+    pub fn update_download(app: &AppHandle, progress: f32, title: &str, detail: Option<&str>, queue_count: usize) {
+        let js = format!(
+            r#"window.updateStatus({{state:'downloading',progress:{},title:'{}',detail:'{}',queueCount:{}}})"#,
+            progress,
+            title.replace("'", "\\'"),
+            detail.unwrap_or_default().replace("'", "\\'"),
+            queue_count
+        );
+        Self::eval_status(app, &js);
+    }
+
+    /// Execute a status update in the panel's webview, if the panel is open
+    fn eval_status(app: &AppHandle, js: &str) {
         if let Some(window) = app.get_webview_window("floating-panel") {
-            // Create JavaScript to call window.updateStatus
-            let js = format!(
-                r#"window.updateStatus({{state:'{}',progress:{},title:'{}',queueCount:{}}})"#,
-                state,
-                progress,
-                title.replace("'", "\\'"), // Escape single quotes
-                queue_count
-            );
-
-            // Execute JavaScript in webview
-            if let Err(e) = window.eval(&js) {
+            if let Err(e) = window.eval(js) {
                 eprintln!("[FloatingPanel] Failed to update status: {}", e);
             }
         }
     }
 
     /// Create the floating panel window using Tauri's WebviewWindow
-    fn create_panel(app: AppHandle) -> Result<(), String> {
+    fn create_panel(app: AppHandle) -> AppResult<()> {
         // Load the HTML file from the platform directory
         // The file is embedded at compile time by Tauri
         let url = WebviewUrl::App("floating_panel.html".into());
@@ -67,7 +84,7 @@ impl FloatingPanelManager {
             .visible(true)                  // Show immediately
             .position(100.0, 100.0)        // Initial position
             .build()
-            .map_err(|e| format!("Failed to create floating panel: {}", e))?;
+            .or_error(ErrorKind::Internal, "Failed to create floating panel")?;
 
         // Enable drag region on the entire window
         // This allows dragging from anywhere on the panel
@@ -1,6 +1,7 @@
 // Platform-specific functionality
 // Clean separation by feature:
 // - clipboard.rs: Cross-platform clipboard (all OS)
+// - clipboard_watcher.rs: Opt-in background capture of copied links (all OS)
 // - floating_panel_macos.rs: macOS native NSPanel
 // - floating_panel_tauri.rs: Windows/Linux Tauri window
 
@@ -11,6 +12,9 @@
 mod clipboard;
 pub use clipboard::ClipboardManager;
 
+mod clipboard_watcher;
+pub use clipboard_watcher::ClipboardWatcher;
+
 // ============================================================================
 // Floating Panel (Platform-specific)
 // ============================================================================
@@ -1,7 +1,16 @@
 // Filesystem utilities for path handling and downloads
 
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+
+/// Free space a download directory must have to be accepted (500 MB)
+pub const MIN_FREE_SPACE_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Extensions of the audio files a download can produce
+pub const AUDIO_EXTENSIONS: &[&str] = &["mp3", "m4a", "opus", "ogg", "flac", "wav", "webm", "aac"];
 
 /// Sanitize a filename by removing/replacing invalid characters
 /// Replaces: / \ : * ? " < > | with underscore
@@ -16,9 +25,15 @@ pub fn sanitize_filename(name: &str) -> String {
         .to_string()
 }
 
+/// Get the download directory for Hasod Downloads
+/// Returns the directory chosen in settings, or ~/Downloads/Hasod Downloads
+pub fn get_download_dir() -> String {
+    crate::utils::get_custom_download_dir().unwrap_or_else(default_download_dir)
+}
+
 /// Get the default download directory for Hasod Downloads
 /// Returns: ~/Downloads/Hasod Downloads
-pub fn get_download_dir() -> String {
+pub fn default_download_dir() -> String {
     dirs::download_dir()
         .unwrap_or_else(|| dirs::home_dir().expect("No home dir").join("Downloads"))
         .join("Hasod Downloads")
@@ -27,64 +42,166 @@ pub fn get_download_dir() -> String {
 }
 
 /// Create the download directory if it doesn't exist
-pub fn create_download_dir() -> Result<String, String> {
+pub fn create_download_dir() -> AppResult<String> {
     let download_dir = get_download_dir();
     fs::create_dir_all(&download_dir)
-        .map_err(|e| format!("Failed to create download directory: {}", e))?;
+        .or_error(ErrorKind::Io, "Failed to create download directory")?;
     Ok(download_dir)
 }
 
+/// Check that a directory can hold downloads, creating it if needed
+/// It must be an absolute path, writable, and have at least MIN_FREE_SPACE_BYTES free
+pub fn validate_download_dir(dir: &str) -> AppResult<PathBuf> {
+    let path = PathBuf::from(dir.trim());
+    if !path.is_absolute() {
+        return Err(AppError::new(
+            ErrorKind::InvalidInput,
+            format!("Download directory must be an absolute path: {}", dir),
+        ));
+    }
+    if path.exists() && !path.is_dir() {
+        return Err(AppError::new(
+            ErrorKind::InvalidInput,
+            format!("Download directory is not a folder: {}", dir),
+        ));
+    }
+
+    fs::create_dir_all(&path)
+        .or_error(ErrorKind::Io, format!("Failed to create download directory {}", path.display()))?;
+
+    // Permissions alone don't tell us about read-only mounts, so try an actual write
+    let probe = path.join(".hasod_write_test");
+    fs::write(&probe, b"")
+        .or_error(ErrorKind::Io, format!("Download directory is not writable: {}", path.display()))?;
+    fs::remove_file(&probe).ok();
+
+    let available = fs2::available_space(&path)
+        .or_error(ErrorKind::Io, format!("Failed to check free space in {}", path.display()))?;
+    if available < MIN_FREE_SPACE_BYTES {
+        return Err(AppError::new(
+            ErrorKind::InsufficientSpace,
+            format!(
+                "Not enough free space in {} ({} MB free, at least {} MB required)",
+                path.display(),
+                available / (1024 * 1024),
+                MIN_FREE_SPACE_BYTES / (1024 * 1024)
+            ),
+        ));
+    }
+
+    Ok(path)
+}
+
+/// Let the webview load files from a download directory through the asset protocol
+/// The static scope in tauri.conf.json only covers ~/Downloads, so custom directories are added at runtime
+pub fn allow_asset_directory(app: &tauri::AppHandle, dir: &str) {
+    use tauri::Manager;
+
+    match app.asset_protocol_scope().allow_directory(dir, true) {
+        Ok(()) => println!("[Filesystem] Asset access allowed for {}", dir),
+        Err(e) => eprintln!("[Filesystem] Failed to allow asset access for {}: {}", dir, e),
+    }
+}
+
+/// Write a file crash-safely: write to a sibling temp file, fsync, then rename over the target
+/// A crash mid-write leaves either the previous contents or the new contents, never a partial file
+pub fn write_atomic(path: &Path, contents: &[u8]) -> AppResult<()> {
+    let tmp_path = path.with_extension("tmp");
+
+    {
+        let mut file = fs::File::create(&tmp_path)
+            .or_error(ErrorKind::Io, "Failed to create temp file")?;
+        file.write_all(contents)
+            .or_error(ErrorKind::Io, "Failed to write temp file")?;
+        file.sync_all()
+            .or_error(ErrorKind::Io, "Failed to sync temp file")?;
+    }
+
+    fs::rename(&tmp_path, path)
+        .or_error(ErrorKind::Io, format!("Failed to replace {}", path.display()))?;
+
+    Ok(())
+}
+
 /// Calculate organized output path based on metadata and context
+/// Layout comes from the output templates in settings, e.g. "{artist}/{album}/{artist} - {title}",
+/// and the extension from the audio format (none when it depends on the source)
 pub fn get_organized_output_path(
     base_dir: &str,
     metadata: &crate::download::TrackMetadata,
     context: &crate::download::DownloadContext,
 ) -> PathBuf {
-    let artist = sanitize_filename(&metadata.artist);
-    let title = sanitize_filename(&metadata.title);
-
-    // Filename is always: "artist - song.mp3"
-    let filename = if artist.is_empty() || artist == "Unknown Artist" {
-        format!("{}.mp3", title)
-    } else {
-        format!("{} - {}.mp3", artist, title)
-    };
-
-    // Determine folder structure based on context
-    let path = match context {
-        crate::download::DownloadContext::Single => {
-            // Single track: /unsorted/
-            PathBuf::from(base_dir).join("unsorted")
-        }
-        crate::download::DownloadContext::Album(_) => {
-            // Album: /artist/album name/
-            // Use metadata.album (which is transliterated) instead of context album_name
-            let album = sanitize_filename(&metadata.album);
-            PathBuf::from(base_dir)
-                .join(if artist.is_empty() || artist == "Unknown Artist" {
-                    "Unknown Artist"
-                } else {
-                    &artist
-                })
-                .join(if album.is_empty() {
-                    "Unknown Album"
-                } else {
-                    &album
-                })
-        }
-        crate::download::DownloadContext::Playlist(playlist_name) => {
-            // Playlist: /playlist_name/
-            let playlist = sanitize_filename(playlist_name);
-            PathBuf::from(base_dir).join(if playlist.is_empty() {
-                "Unknown Playlist"
-            } else {
-                &playlist
-            })
-        }
-    };
+    let templates = crate::utils::get_output_templates();
+    let extension = crate::utils::get_audio_format().extension();
+    let path = render_output_path(base_dir, templates.for_context(context), metadata, context, extension)
+        .unwrap_or_else(|e| {
+            // Templates are validated when saved, but the settings file may have been edited by hand
+            eprintln!("[Filesystem] Invalid output template, using default: {}", e);
+            let defaults = crate::utils::OutputTemplates::default();
+            render_output_path(base_dir, defaults.for_context(context), metadata, context, extension)
+                .unwrap_or_else(|_| PathBuf::from(base_dir).join("unsorted").join("Unknown"))
+        });
 
     // Ensure directory exists
-    fs::create_dir_all(&path).ok();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).ok();
+    }
+
+    path
+}
+
+/// Render an output template to a full file path under base_dir (no directories are created)
+pub fn render_output_path(
+    base_dir: &str,
+    template: &str,
+    metadata: &crate::download::TrackMetadata,
+    context: &crate::download::DownloadContext,
+    extension: Option<&str>,
+) -> AppResult<PathBuf> {
+    let relative = crate::utils::template::render_template(template, metadata, context)?;
+    let mut path = PathBuf::from(base_dir).join(relative);
+
+    // Append rather than set the extension - titles like "Mr. Brightside" contain dots
+    if let Some(extension) = extension {
+        let mut filename = path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+        filename.push(".");
+        filename.push(extension);
+        path.set_file_name(filename);
+    }
+
+    Ok(path)
+}
+
+/// Split a path into everything before the audio extension and the extension itself
+/// Only known audio extensions count: "Mr. Brightside" has no extension, "Mr. Brightside.mp3" has "mp3"
+pub fn split_audio_extension(path: &Path) -> (PathBuf, Option<String>) {
+    match path.extension().map(|e| e.to_string_lossy().to_lowercase()) {
+        Some(ext) if AUDIO_EXTENSIONS.contains(&ext.as_str()) => (path.with_extension(""), Some(ext)),
+        _ => (path.to_path_buf(), None),
+    }
+}
+
+/// Find the audio file for an output path: the exact path, or the same name with any audio extension
+/// (the extension isn't known in advance when the original format is kept)
+pub fn find_audio_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    let (stem, _) = split_audio_extension(path);
+    AUDIO_EXTENSIONS.iter().find_map(|ext| {
+        let mut candidate = stem.clone().into_os_string();
+        candidate.push(".");
+        candidate.push(ext);
+        let candidate = PathBuf::from(candidate);
+        candidate.is_file().then_some(candidate)
+    })
+}
 
-    path.join(filename)
+/// yt-dlp output template that produces `output_path` once audio is extracted
+/// ("/dir/Artist - Title.mp3" -> "/dir/Artist - Title.%(ext)s")
+pub fn ytdlp_output_template(output_path: &Path) -> String {
+    let (stem, _) = split_audio_extension(output_path);
+    let stem = stem.to_string_lossy().to_string();
+    // '%' starts a template field in yt-dlp, so literal percent signs must be doubled
+    format!("{}.%(ext)s", stem.replace('%', "%%"))
 }
@@ -4,9 +4,22 @@ pub mod hardware;
 pub mod filesystem;
 pub mod hebrew;
 pub mod settings;
+pub mod template;
 
 // Re-export commonly used functions for convenience
 pub use hardware::{get_config_dir, get_hardware_id, get_or_create_device_uuid};
-pub use filesystem::{sanitize_filename, get_download_dir, create_download_dir};
+pub use filesystem::{
+    sanitize_filename, get_download_dir, create_download_dir, validate_download_dir, write_atomic,
+};
 pub use hebrew::{contains_hebrew, needs_transliteration};
-pub use settings::{get_english_only_mode, set_english_only_mode};
+pub use settings::{
+    get_english_only_mode, set_english_only_mode,
+    get_max_concurrent_downloads, set_max_concurrent_downloads,
+    get_resource_limits, set_resource_limits, ResourceLimits,
+    get_duplicate_policy, set_duplicate_policy, DuplicatePolicy,
+    get_output_templates, set_output_templates, OutputTemplates,
+    get_custom_download_dir, set_custom_download_dir,
+    get_audio_format, set_audio_format,
+    get_lyrics_settings, set_lyrics_settings, LyricsSettings,
+    get_clipboard_watch_settings, set_clipboard_watch_settings, ClipboardWatchSettings, ClipboardWatchMode,
+};
@@ -4,15 +4,163 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::download::format::AudioFormat;
+use crate::error::{AppError, AppResult, ErrorKind, ResultExt};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)] // Missing fields fall back to defaults so older settings files keep loading
 pub struct AppSettings {
     pub english_only_mode: bool,
+    /// Number of download jobs processed at the same time
+    pub max_concurrent_downloads: usize,
+    /// Per-resource limits applied on top of max_concurrent_downloads
+    pub resource_limits: ResourceLimits,
+    /// What to do when a track has already been downloaded
+    pub duplicate_policy: DuplicatePolicy,
+    /// Folder and filename layout for each download context
+    pub output_templates: OutputTemplates,
+    /// Download directory chosen by the user (None = ~/Downloads/Hasod Downloads)
+    pub download_dir: Option<String>,
+    /// Format and quality of downloaded files
+    pub audio_format: AudioFormat,
+    /// Post-download lyrics lookup
+    pub lyrics: LyricsSettings,
+    /// Background clipboard monitoring for music links
+    pub clipboard_watch: ClipboardWatchSettings,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
             english_only_mode: false,
+            max_concurrent_downloads: 3,
+            resource_limits: ResourceLimits::default(),
+            duplicate_policy: DuplicatePolicy::default(),
+            output_templates: OutputTemplates::default(),
+            download_dir: None,
+            audio_format: AudioFormat::default(),
+            lyrics: LyricsSettings::default(),
+            clipboard_watch: ClipboardWatchSettings::default(),
+        }
+    }
+}
+
+/// Concurrency limits for shared external resources
+/// e.g. YouTube rate-limits searches much more aggressively than Deezer fetches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Concurrent yt-dlp metadata lookups and YouTube searches
+    pub ytdlp_search: usize,
+    /// Concurrent yt-dlp audio downloads
+    pub ytdlp_download: usize,
+    /// Concurrent Deezer file downloads
+    pub deezer: usize,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            ytdlp_search: 1,
+            ytdlp_download: 3,
+            deezer: 4,
+        }
+    }
+}
+
+/// How to handle a track whose file already exists on disk
+/// Duplicates within the queue itself are always skipped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    #[default]
+    Skip,       // Don't download it again
+    Overwrite,  // Replace the existing file
+    KeepBoth,   // Download to "Artist - Title (1).mp3"
+}
+
+/// Path templates (relative to the download directory, without extension) per download context
+/// See utils::template for the placeholder syntax
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputTemplates {
+    pub single: String,
+    pub album: String,
+    pub playlist: String,
+}
+
+impl Default for OutputTemplates {
+    fn default() -> Self {
+        Self {
+            single: "unsorted/{artist} - {title}".to_string(),
+            album: "{artist|Unknown Artist}/{album|Unknown Album}/{artist} - {title}".to_string(),
+            playlist: "{playlist|Unknown Playlist}/{artist} - {title}".to_string(),
+        }
+    }
+}
+
+impl OutputTemplates {
+    /// The template that applies to a download context
+    pub fn for_context(&self, context: &crate::download::DownloadContext) -> &str {
+        match context {
+            crate::download::DownloadContext::Single => &self.single,
+            crate::download::DownloadContext::Album(_) => &self.album,
+            crate::download::DownloadContext::Playlist(_) => &self.playlist,
+        }
+    }
+}
+
+/// Lyrics lookup after each download
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LyricsSettings {
+    pub enabled: bool,
+    /// Embed lyrics in the file's tags (USLT/SYLT for MP3)
+    pub embed: bool,
+    /// Write an .lrc file next to the audio file
+    pub write_lrc: bool,
+    /// Folder of .lrc/.txt files searched before online providers
+    pub local_dir: Option<String>,
+}
+
+impl Default for LyricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            embed: true,
+            write_lrc: false,
+            local_dir: None,
+        }
+    }
+}
+
+/// What the clipboard watcher does with new music links
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardWatchMode {
+    #[default]
+    Off,
+    Notify,     // Emit "clipboard-links" so the UI can offer them
+    AutoQueue,  // Queue them right away
+}
+
+/// Clipboard watcher (opt-in)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardWatchSettings {
+    pub mode: ClipboardWatchMode,
+    /// Clipboard text must stay unchanged this long before it's picked up
+    pub debounce_ms: u64,
+    /// Links containing any of these (a host, an ID, a whole link) are never captured
+    pub ignore: Vec<String>,
+}
+
+impl Default for ClipboardWatchSettings {
+    fn default() -> Self {
+        Self {
+            mode: ClipboardWatchMode::Off,
+            debounce_ms: 1500,
+            ignore: Vec::new(),
         }
     }
 }
@@ -39,13 +187,13 @@ fn load_settings() -> AppSettings {
 }
 
 /// Save app settings to file
-fn save_settings(settings: &AppSettings) -> Result<(), String> {
+fn save_settings(settings: &AppSettings) -> AppResult<()> {
     let path = get_settings_path();
     let json = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("JSON serialize error: {}", e))?;
+        .or_error(ErrorKind::Internal, "JSON serialize error")?;
 
     fs::write(&path, json)
-        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+        .or_error(ErrorKind::Io, "Failed to write settings file")?;
 
     Ok(())
 }
@@ -56,10 +204,151 @@ pub fn get_english_only_mode() -> bool {
 }
 
 /// Set English Only mode setting
-pub fn set_english_only_mode(enabled: bool) -> Result<(), String> {
+pub fn set_english_only_mode(enabled: bool) -> AppResult<()> {
     let mut settings = load_settings();
     settings.english_only_mode = enabled;
     save_settings(&settings)?;
     println!("[Settings] English Only mode set to: {}", enabled);
     Ok(())
 }
+
+/// Get the maximum number of concurrent download jobs (always at least 1)
+pub fn get_max_concurrent_downloads() -> usize {
+    load_settings().max_concurrent_downloads.max(1)
+}
+
+/// Set the maximum number of concurrent download jobs
+pub fn set_max_concurrent_downloads(value: usize) -> AppResult<()> {
+    if value == 0 {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Concurrent downloads must be at least 1"));
+    }
+    let mut settings = load_settings();
+    settings.max_concurrent_downloads = value;
+    save_settings(&settings)?;
+    println!("[Settings] Max concurrent downloads set to: {}", value);
+    Ok(())
+}
+
+/// Get per-resource concurrency limits
+pub fn get_resource_limits() -> ResourceLimits {
+    load_settings().resource_limits
+}
+
+/// Set per-resource concurrency limits
+pub fn set_resource_limits(limits: ResourceLimits) -> AppResult<()> {
+    if limits.ytdlp_search == 0 || limits.ytdlp_download == 0 || limits.deezer == 0 {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Resource limits must be at least 1"));
+    }
+    let mut settings = load_settings();
+    settings.resource_limits = limits;
+    save_settings(&settings)?;
+    println!("[Settings] Resource limits set to: {:?}", settings.resource_limits);
+    Ok(())
+}
+
+/// Get the duplicate handling policy
+pub fn get_duplicate_policy() -> DuplicatePolicy {
+    load_settings().duplicate_policy
+}
+
+/// Set the duplicate handling policy
+pub fn set_duplicate_policy(policy: DuplicatePolicy) -> AppResult<()> {
+    let mut settings = load_settings();
+    settings.duplicate_policy = policy;
+    save_settings(&settings)?;
+    println!("[Settings] Duplicate policy set to: {:?}", policy);
+    Ok(())
+}
+
+/// Get the output path templates
+pub fn get_output_templates() -> OutputTemplates {
+    load_settings().output_templates
+}
+
+/// Set the output path templates (each one must be a valid template)
+pub fn set_output_templates(templates: OutputTemplates) -> AppResult<()> {
+    for template in [&templates.single, &templates.album, &templates.playlist] {
+        crate::utils::template::validate_template(template)?;
+    }
+    let mut settings = load_settings();
+    settings.output_templates = templates;
+    save_settings(&settings)?;
+    println!("[Settings] Output templates set to: {:?}", settings.output_templates);
+    Ok(())
+}
+
+/// Get the download directory chosen by the user, if any
+pub fn get_custom_download_dir() -> Option<String> {
+    load_settings().download_dir.filter(|dir| !dir.trim().is_empty())
+}
+
+/// Set the download directory (None resets to the default)
+/// The directory is created if needed and must be writable with enough free space
+pub fn set_custom_download_dir(dir: Option<String>) -> AppResult<()> {
+    let dir = match dir.filter(|dir| !dir.trim().is_empty()) {
+        Some(dir) => {
+            let path = crate::utils::filesystem::validate_download_dir(&dir)?;
+            Some(path.to_string_lossy().to_string())
+        }
+        None => None,
+    };
+    let mut settings = load_settings();
+    settings.download_dir = dir;
+    save_settings(&settings)?;
+    println!("[Settings] Download directory set to: {:?}", settings.download_dir);
+    Ok(())
+}
+
+/// Get the output audio format
+pub fn get_audio_format() -> AudioFormat {
+    load_settings().audio_format
+}
+
+/// Set the output audio format
+pub fn set_audio_format(format: AudioFormat) -> AppResult<()> {
+    let mut settings = load_settings();
+    settings.audio_format = format;
+    save_settings(&settings)?;
+    println!("[Settings] Audio format set to: {:?}", format);
+    Ok(())
+}
+
+/// Get the lyrics settings
+pub fn get_lyrics_settings() -> LyricsSettings {
+    load_settings().lyrics
+}
+
+/// Set the lyrics settings
+pub fn set_lyrics_settings(lyrics: LyricsSettings) -> AppResult<()> {
+    if lyrics.enabled && !lyrics.embed && !lyrics.write_lrc {
+        return Err(AppError::new(
+            ErrorKind::InvalidInput,
+            "Lyrics must be embedded, saved as .lrc, or both",
+        ));
+    }
+    let mut settings = load_settings();
+    settings.lyrics = lyrics;
+    save_settings(&settings)?;
+    println!("[Settings] Lyrics settings set to: {:?}", settings.lyrics);
+    Ok(())
+}
+
+/// Get the clipboard watcher settings
+pub fn get_clipboard_watch_settings() -> ClipboardWatchSettings {
+    load_settings().clipboard_watch
+}
+
+/// Set the clipboard watcher settings
+pub fn set_clipboard_watch_settings(mut watch: ClipboardWatchSettings) -> AppResult<()> {
+    watch.ignore = watch
+        .ignore
+        .iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    let mut settings = load_settings();
+    settings.clipboard_watch = watch;
+    save_settings(&settings)?;
+    println!("[Settings] Clipboard watcher set to: {:?}", settings.clipboard_watch);
+    Ok(())
+}
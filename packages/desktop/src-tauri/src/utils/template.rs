@@ -0,0 +1,402 @@
+// Path templates for organizing downloaded files
+// Syntax: "{artist}/{album}/{artist} - {title}"
+//   {field}          - metadata value, sanitized so it can't create extra folders
+//   {field:02}       - zero-pad numeric values to the given width
+//   {field|Fallback} - literal text used when the field is empty
+//   {{ and }}        - literal braces
+// "/" in the template separates folders; the file extension is added by the caller.
+
+use std::path::PathBuf;
+
+use crate::download::{DownloadContext, TrackMetadata};
+use crate::error::{AppError, AppResult, ErrorKind};
+use crate::utils::sanitize_filename;
+
+/// Every placeholder a template may use
+pub const TEMPLATE_FIELDS: &[&str] = &[
+    "title",
+    "artist",
+    "album",
+    "album_artist",
+    "track",
+    "track_total",
+    "disc",
+    "disc_total",
+    "year",
+    "release_date",
+    "genre",
+    "isrc",
+    "duration",
+    "playlist",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Field {
+        name: String,
+        width: Option<usize>,
+        fallback: Option<String>,
+    },
+}
+
+/// Parse a template into literal and placeholder tokens
+fn parse(template: &str) -> AppResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut body = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => body.push(c),
+                        None => {
+                            return Err(AppError::new(
+                                ErrorKind::InvalidInput,
+                                format!("Unclosed '{{' in template: {}", template),
+                            ))
+                        }
+                    }
+                }
+                if !literal.is_empty() {
+                    tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(parse_field(&body)?);
+            }
+            '}' => {
+                return Err(AppError::new(
+                    ErrorKind::InvalidInput,
+                    format!("Unmatched '}}' in template: {}", template),
+                ))
+            }
+            _ => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        tokens.push(Token::Literal(literal));
+    }
+    Ok(tokens)
+}
+
+/// Parse the inside of a placeholder: "name", "name:02", "name|Fallback"
+fn parse_field(body: &str) -> AppResult<Token> {
+    let (spec, fallback) = match body.split_once('|') {
+        Some((spec, fallback)) => (spec, Some(fallback.to_string())),
+        None => (body, None),
+    };
+    let (name, format) = match spec.split_once(':') {
+        Some((name, format)) => (name.trim(), Some(format.trim())),
+        None => (spec.trim(), None),
+    };
+
+    if !TEMPLATE_FIELDS.contains(&name) {
+        return Err(AppError::new(
+            ErrorKind::InvalidInput,
+            format!("Unknown template field '{{{}}}' (available: {})", name, TEMPLATE_FIELDS.join(", ")),
+        ));
+    }
+
+    // Only zero padding is supported, e.g. {duration:04}
+    let width = match format {
+        None => None,
+        Some(format) => match format.strip_prefix('0').and_then(|w| w.parse::<usize>().ok()) {
+            Some(width) => Some(width),
+            None => {
+                return Err(AppError::new(
+                    ErrorKind::InvalidInput,
+                    format!("Unsupported format '{}' for field '{}' (use e.g. {{{}:02}})", format, name, name),
+                ))
+            }
+        },
+    };
+
+    Ok(Token::Field {
+        name: name.to_string(),
+        width,
+        fallback,
+    })
+}
+
+/// Check that a template parses and renders to at least a filename
+pub fn validate_template(template: &str) -> AppResult<()> {
+    parse(template)?;
+    if template.trim().trim_end_matches(['/', '\\']).is_empty() {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Template must not be empty"));
+    }
+    if template.trim_end().ends_with(['/', '\\']) {
+        return Err(AppError::new(ErrorKind::InvalidInput, "Template must end with a filename, not a folder"));
+    }
+    Ok(())
+}
+
+/// Raw value of a field, or None when the metadata doesn't have it
+fn field_value(name: &str, metadata: &TrackMetadata, context: &DownloadContext) -> Option<String> {
+    // Placeholder values from TrackMetadata::default() count as missing
+    let known = |value: &str, placeholder: &str| {
+        let value = value.trim();
+        (!value.is_empty() && value != placeholder).then(|| value.to_string())
+    };
+
+    match name {
+        "title" => known(&metadata.title, ""),
+        "artist" => known(&metadata.artist, "Unknown Artist"),
+        // Singles and YouTube uploads rarely have an album artist - the track artist is the closest match
+        "album_artist" => metadata
+            .album_artist
+            .as_deref()
+            .and_then(|a| known(a, "Unknown Artist"))
+            .or_else(|| known(&metadata.artist, "Unknown Artist")),
+        "album" => known(&metadata.album, "Unknown Album").or_else(|| match context {
+            DownloadContext::Album(name) => known(name, ""),
+            _ => None,
+        }),
+        "track" => metadata.track_number.map(|n| n.to_string()),
+        "track_total" => metadata.track_total.map(|n| n.to_string()),
+        "disc" => metadata.disc_number.map(|n| n.to_string()),
+        "disc_total" => metadata.disc_total.map(|n| n.to_string()),
+        "year" => metadata.year().map(|y| y.to_string()),
+        "release_date" => metadata.release_date.as_deref().and_then(|d| known(d, "")),
+        "genre" => metadata.genre.as_deref().and_then(|g| known(g, "")),
+        "isrc" => metadata.isrc.as_deref().and_then(|i| known(i, "")),
+        "duration" => metadata.duration.map(|d| d.to_string()),
+        "playlist" => match context {
+            DownloadContext::Playlist(name) => known(name, ""),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A rendered part of one folder or file name
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Literal(String),
+    Value(String),
+    /// A field with no value and no fallback
+    Missing,
+    /// Separator kept between the text on either side of missing fields
+    Gap(String),
+}
+
+impl Piece {
+    fn has_text(&self) -> bool {
+        match self {
+            Piece::Literal(text) | Piece::Value(text) => !text.trim().is_empty(),
+            Piece::Missing | Piece::Gap(_) => false,
+        }
+    }
+}
+
+/// Join a segment's pieces, dropping the separators that only surrounded missing fields
+/// ("{artist} - {album} - {title}" without an album -> "Artist - Title"). Values are never trimmed.
+fn join_segment(mut pieces: Vec<Piece>) -> String {
+    let is_separator = |c: char| c.is_whitespace() || c == '-';
+
+    for i in 0..pieces.len() {
+        if pieces[i] != Piece::Missing {
+            continue;
+        }
+        let mut gap = String::new();
+        if let Some(Piece::Literal(before)) = i.checked_sub(1).and_then(|j| pieces.get_mut(j)) {
+            let kept = before.trim_end_matches(is_separator).len();
+            gap = before.split_off(kept);
+        }
+        if let Some(Piece::Literal(after)) = pieces.get_mut(i + 1) {
+            let cut = after.len() - after.trim_start_matches(is_separator).len();
+            let leading: String = after.drain(..cut).collect();
+            if gap.is_empty() {
+                gap = leading;
+            }
+        }
+        let text_on_both_sides = pieces[..i].iter().any(Piece::has_text) && pieces[i + 1..].iter().any(Piece::has_text);
+        pieces[i] = if text_on_both_sides { Piece::Gap(gap) } else { Piece::Missing };
+    }
+
+    let mut joined = String::new();
+    let mut last_was_gap = false;
+    for piece in pieces {
+        match piece {
+            Piece::Literal(text) | Piece::Value(text) => {
+                if !text.is_empty() {
+                    last_was_gap = false;
+                }
+                joined.push_str(&text);
+            }
+            // Several missing fields in a row leave a single separator
+            Piece::Gap(gap) if !last_was_gap => {
+                joined.push_str(&gap);
+                last_was_gap = true;
+            }
+            Piece::Gap(_) | Piece::Missing => {}
+        }
+    }
+    joined.trim().to_string()
+}
+
+/// Render a template to a path relative to the download directory (without extension)
+pub fn render_template(
+    template: &str,
+    metadata: &TrackMetadata,
+    context: &DownloadContext,
+) -> AppResult<PathBuf> {
+    let mut segments: Vec<Vec<Piece>> = vec![Vec::new()];
+    for token in parse(template)? {
+        match token {
+            Token::Literal(text) => {
+                // "/" in the template starts a new folder
+                for (i, part) in text.split(['/', '\\']).enumerate() {
+                    if i > 0 {
+                        segments.push(Vec::new());
+                    }
+                    segments.last_mut().unwrap().push(Piece::Literal(part.to_string()));
+                }
+            }
+            Token::Field { name, width, fallback } => {
+                let piece = match field_value(&name, metadata, context) {
+                    Some(value) => {
+                        let value = match width {
+                            Some(width) if value.chars().all(|c| c.is_ascii_digit()) => {
+                                format!("{:0>width$}", value, width = width)
+                            }
+                            _ => value,
+                        };
+                        // Separators inside values must not become folders
+                        Piece::Value(sanitize_filename(&value))
+                    }
+                    None => match fallback {
+                        Some(fallback) => Piece::Value(sanitize_filename(&fallback)),
+                        None => Piece::Missing,
+                    },
+                };
+                segments.last_mut().unwrap().push(piece);
+            }
+        }
+    }
+
+    // Refuse anything that could escape the download directory
+    let segments: Vec<String> = segments
+        .into_iter()
+        .map(join_segment)
+        .filter(|segment| !segment.is_empty() && segment != "." && segment != "..")
+        .collect();
+
+    if segments.is_empty() {
+        return Err(AppError::new(
+            ErrorKind::InvalidInput,
+            format!("Template '{}' rendered an empty path", template),
+        ));
+    }
+
+    Ok(segments.iter().collect())
+}
+
+/// Metadata used to preview templates in settings
+pub fn sample_metadata() -> TrackMetadata {
+    TrackMetadata {
+        title: "Song Title".to_string(),
+        artist: "Artist".to_string(),
+        album: "Album".to_string(),
+        duration: Some(215),
+        artists: vec!["Artist".to_string()],
+        album_artist: Some("Album Artist".to_string()),
+        track_number: Some(3),
+        track_total: Some(12),
+        disc_number: Some(1),
+        disc_total: Some(1),
+        release_date: Some("2024-05-17".to_string()),
+        isrc: Some("USABC2400001".to_string()),
+        genre: Some("Pop".to_string()),
+        ..TrackMetadata::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(artist: &str, album: &str) -> TrackMetadata {
+        TrackMetadata {
+            title: "Title".to_string(),
+            artist: artist.to_string(),
+            album: album.to_string(),
+            duration: Some(7),
+            track_number: Some(4),
+            release_date: Some("1980-07-25".to_string()),
+            ..TrackMetadata::default()
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let single = DownloadContext::Single;
+        let playlist = DownloadContext::Playlist("Road Trip".to_string());
+
+        assert_eq!(
+            render_template("{artist}/{album}/{artist} - {title}", &metadata("AC/DC", "Back in Black"), &single).unwrap(),
+            PathBuf::from("AC_DC/Back in Black/AC_DC - Title")
+        );
+        assert_eq!(
+            render_template("{playlist}/{duration:03} {title}", &metadata("A", "B"), &playlist).unwrap(),
+            PathBuf::from("Road Trip/007 Title")
+        );
+        // Unknown artist falls back and leaves no dangling separator
+        assert_eq!(
+            render_template("{artist|Unknown Artist}/{artist} - {title}", &TrackMetadata::default(), &single).unwrap(),
+            PathBuf::from("Unknown Artist/Unknown")
+        );
+        assert_eq!(
+            render_template("../{{x}}/./{title}", &metadata("A", "B"), &single).unwrap(),
+            PathBuf::from("{x}/Title")
+        );
+    }
+
+    #[test]
+    fn test_render_template_separators() {
+        let single = DownloadContext::Single;
+        let mut hyphens = metadata("-M-", "");
+        hyphens.title = "Intro -".to_string();
+
+        // Hyphens that belong to a value are kept
+        assert_eq!(
+            render_template("{artist}/{artist} - {title}", &hyphens, &single).unwrap(),
+            PathBuf::from("-M-/-M- - Intro -")
+        );
+        // Only the separators around missing fields go, one is kept between the remaining text
+        assert_eq!(
+            render_template("{artist} - {album} - {title}", &hyphens, &single).unwrap(),
+            PathBuf::from("-M- - Intro -")
+        );
+        assert_eq!(
+            render_template("{artist} - {album} - {genre} - {title}", &metadata("A", ""), &single).unwrap(),
+            PathBuf::from("A - Title")
+        );
+        assert_eq!(
+            render_template("{album} - {track:02} {title}", &metadata("A", ""), &single).unwrap(),
+            PathBuf::from("04 Title")
+        );
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(validate_template("{artist}/{album}/{artist} - {title}").is_ok());
+        assert!(validate_template("{album_artist}/{year} - {album}/{track:02} {title}").is_ok());
+        assert!(validate_template("{label} - {title}").is_err());
+        assert!(validate_template("{title:x2}").is_err());
+        assert!(validate_template("{title").is_err());
+        assert!(validate_template("{artist}/").is_err());
+        assert!(validate_template("").is_err());
+    }
+}
@@ -5,13 +5,16 @@ import { useLanguage } from './i18n';
 import LanguageSwitcher from './components/LanguageSwitcher';
 import { LicenseTab } from './components/tabs/LicenseTab';
 import { DownloadTab } from './components/tabs/DownloadTab';
+import { LibraryTab } from './components/tabs/LibraryTab';
 import { useAuth, useQueue, useFloatingPanel } from './hooks';
+import type { ParsedLink } from './api/tauri';
 import './App.css';
 
 function App() {
   const { t } = useLanguage();
-  const [activeTab, setActiveTab] = useState<'download' | 'license'>('license');
+  const [activeTab, setActiveTab] = useState<'download' | 'library' | 'license'>('license');
   const [downloadProgress, setDownloadProgress] = useState('');
+  const [droppedLinks, setDroppedLinks] = useState<ParsedLink[] | null>(null);
 
   // Use custom hooks for logic
   const {
@@ -29,6 +32,9 @@ function App() {
   const {
     queueStatus,
     addToQueue,
+    addLinks,
+    importPlaylist,
+    addArtistReleases,
     clearCompleted,
     clearAll,
     removeJob,
@@ -45,8 +51,15 @@ function App() {
       setDownloadProgress(prev => prev + '\n' + event.payload);
     });
 
+    // Text dropped on the floating button - confirm its links on the download tab
+    const unlistenDropped = listen<ParsedLink[]>('dropped-links', (event) => {
+      setActiveTab('download');
+      setDroppedLinks(event.payload);
+    });
+
     return () => {
       unlistenDownload.then(fn => fn());
+      unlistenDropped.then(fn => fn());
     };
   }, []);
 
@@ -104,6 +117,12 @@ function App() {
             </span>
           )}
         </button>
+        <button
+          className={activeTab === 'library' ? 'tab active' : 'tab'}
+          onClick={() => setActiveTab('library')}
+        >
+          {t.tabs.library}
+        </button>
         <button
           className={activeTab === 'license' ? 'tab active' : 'tab'}
           onClick={() => setActiveTab('license')}
@@ -132,12 +151,21 @@ function App() {
             isLicenseValid={licenseStatus?.is_valid || false}
             queueStatus={queueStatus}
             onAddToQueue={addToQueue}
+            onAddLinks={addLinks}
+            onImportPlaylist={importPlaylist}
+            onAddArtistReleases={addArtistReleases}
             onRemoveJob={removeJob}
             onClearCompleted={clearCompleted}
             onClearAll={clearAll}
+            droppedLinks={droppedLinks}
+            onDroppedLinksShown={() => setDroppedLinks(null)}
           />
         )}
 
+        {activeTab === 'library' && (
+          <LibraryTab isLicenseValid={licenseStatus?.is_valid || false} />
+        )}
+
         {/* Progress Log (collapsible) - only show if there's progress */}
         {downloadProgress && activeTab === 'download' && (
           <details className="progress-box">
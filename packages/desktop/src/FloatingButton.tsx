@@ -123,6 +123,12 @@ function FloatingButton() {
       const textPlain = dt.getData('text/plain');
       const urlData = dt.getData('URL');
 
+      // Dropped text (a chat message, several links) - confirm its links in the main window
+      if (!uriList && textPlain && /\s/.test(textPlain.trim())) {
+        await offerLinks(textPlain);
+        return;
+      }
+
       let url = '';
       if (uriList) {
         url = firstUriFromUriList(uriList);
@@ -175,6 +181,14 @@ function FloatingButton() {
     }
   };
 
+  const offerLinks = async (text: string) => {
+    try {
+      await api.queue.offerDroppedLinks(text);
+    } catch (error) {
+      console.error('Failed to offer dropped links:', error);
+    }
+  };
+
   const handleClose = async () => {
     const appWindow = getCurrentWindow();
     await appWindow.close();
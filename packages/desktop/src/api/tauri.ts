@@ -8,12 +8,134 @@ import type { components } from '../../../webapp/src/api/schema';
 
 // Re-export types for convenience
 export type LicenseStatus = components['schemas']['LicenseStatus'];
-export type DownloadJob = components['schemas']['DownloadJob'];
-export type QueueStatus = components['schemas']['QueueStatus'];
+// The desktop queue has statuses and fields the shared schema doesn't describe yet
+export type DownloadStatus =
+  | components['schemas']['DownloadStatus']
+  | 'Paused'
+  | 'Cancelled'
+  | 'Skipped'
+  | 'NeedsReview';
+export type DownloadJob = Omit<components['schemas']['DownloadJob'], 'status'> & {
+  status: DownloadStatus;
+  review_candidates?: SourceCandidate[]; // Set while status is 'NeedsReview'
+};
+export type QueueStatus = Omit<components['schemas']['QueueStatus'], 'jobs'> & {
+  jobs: DownloadJob[];
+  needs_review_count?: number;
+};
 export type StoredAuth = components['schemas']['StoredAuth'];
 export type OAuthStartResult = components['schemas']['OAuthStartResult'];
 export type TrackMetadata = components['schemas']['TrackMetadata'];
 
+// Search result offered when a job needs review (status 'NeedsReview', event 'job-needs-review')
+export interface SourceCandidate {
+  url: string;
+  title: string;
+  uploader: string;
+  tier: 'regular' | 'official_audio' | 'vevo' | 'topic';
+  duration_secs: number | null;
+  audio_bitrate: number | null;
+  confidence: number;
+}
+
+// Link parsed by the backend (parse_link) - short links are already followed
+export type LinkKind = 'track' | 'album' | 'playlist' | 'artist' | 'short_link';
+
+export interface ParsedLink {
+  service: DownloadJob['service'];
+  kind: LinkKind;
+  id: string;
+  url: string; // Canonical URL
+}
+
+// Title and author of a link before it is queued (preview_link)
+export interface LinkPreview {
+  title: string;
+  author: string | null;
+}
+
+// Completed download recorded in the local history database (get_download_history)
+export interface HistoryEntry {
+  id: string;
+  url: string;
+  service: DownloadJob['service'];
+  isrc: string | null;
+  output_path: string;
+  metadata: TrackMetadata;
+  source: { service: DownloadJob['service']; url: string | null } | null;
+  file_size: number | null; // bytes
+  created_at: number;
+  started_at: number | null;
+  completed_at: number; // unix seconds
+}
+
+// All fields optional, combined with AND
+export interface HistoryFilter {
+  search?: string; // Matches title, artist or album
+  artist?: string;
+  album?: string;
+  from?: number; // Completed at or after (unix seconds)
+  to?: number; // Completed at or before (unix seconds)
+  limit?: number;
+  offset?: number;
+}
+
+// Release on a Spotify artist's page (get_spotify_artist_releases)
+export interface SpotifyRelease {
+  id: string;
+  name: string;
+  url: string;
+  album_type: 'album' | 'single' | 'compilation';
+  album_group: 'album' | 'single' | 'compilation' | 'appears_on';
+  artist: string;
+  release_date: string;
+  total_tracks: number;
+  image_url: string | null;
+}
+
+export interface ArtistReleaseFilter {
+  albums: boolean;
+  singles: boolean;
+  compilations: boolean;
+  appears_on: boolean;
+}
+
+export interface SpotifyArtistReleases {
+  artist_id: string;
+  artist_name: string;
+  releases: SpotifyRelease[];
+}
+
+// Clipboard watcher (get_clipboard_watch_settings)
+export type ClipboardWatchMode = 'off' | 'notify' | 'auto_queue';
+
+export interface ClipboardWatchSettings {
+  mode: ClipboardWatchMode;
+  debounce_ms: number;
+  ignore: string[]; // Links containing any of these are skipped
+}
+
+// Payload of the 'clipboard-links' event
+export interface ClipboardLinksEvent {
+  links: ParsedLink[];
+  queued: boolean; // Already queued (auto_queue mode)
+}
+
+// Error returned by failing commands ({ code, message, causes })
+export interface AppError {
+  code: string;
+  message: string;
+  causes: string[];
+}
+
+/** Human-readable message for an error thrown by invoke() */
+export function errorMessage(error: unknown): string {
+  if (error && typeof error === 'object' && 'message' in error) {
+    return String((error as AppError).message);
+  }
+  return String(error);
+}
+
 // ============================================================================
 // Auth & License API
 // ============================================================================
@@ -77,10 +199,65 @@ export const queueApi = {
     return invoke<DownloadJob[]>('add_spotify_playlist_to_queue', { playlistUrl });
   },
 
+  async parseLink(url: string): Promise<ParsedLink> {
+    return invoke<ParsedLink>('parse_link', { url });
+  },
+
+  async extractLinks(text: string): Promise<ParsedLink[]> {
+    return invoke<ParsedLink[]>('extract_links', { text });
+  },
+
+  async extractClipboardLinks(): Promise<ParsedLink[]> {
+    return invoke<ParsedLink[]>('extract_clipboard_links');
+  },
+
+  // Text dropped on the floating button - its links open in the main window's link picker
+  async offerDroppedLinks(text: string): Promise<number> {
+    return invoke<number>('offer_dropped_links', { text });
+  },
+
+  // Title and author of a YouTube, SoundCloud or Spotify link
+  async previewLink(url: string): Promise<LinkPreview> {
+    return invoke<LinkPreview>('preview_link', { url });
+  },
+
+  async addLinks(urls: string[]): Promise<DownloadJob[]> {
+    return invoke<DownloadJob[]>('add_links_to_queue', { urls });
+  },
+
+  // M3U/M3U8, CSV or text playlist file, queued as a playlist named after the file
+  async importPlaylist(fileName: string, contents: string): Promise<DownloadJob[]> {
+    return invoke<DownloadJob[]>('import_playlist', { fileName, contents });
+  },
+
+  async getSpotifyArtistReleases(artistUrl: string, filter?: ArtistReleaseFilter): Promise<SpotifyArtistReleases> {
+    return invoke<SpotifyArtistReleases>('get_spotify_artist_releases', { artistUrl, filter });
+  },
+
+  async addSpotifyArtistReleases(artistName: string, releaseUrls: string[]): Promise<DownloadJob[]> {
+    return invoke<DownloadJob[]>('add_spotify_artist_releases_to_queue', { artistName, releaseUrls });
+  },
+
+  async addSpotifyArtistTopTracks(artistUrl: string): Promise<DownloadJob[]> {
+    return invoke<DownloadJob[]>('add_spotify_artist_top_tracks_to_queue', { artistUrl });
+  },
+
+  async addAppleMusicAlbum(albumUrl: string): Promise<DownloadJob[]> {
+    return invoke<DownloadJob[]>('add_apple_music_album_to_queue', { albumUrl });
+  },
+
+  async addAppleMusicPlaylist(playlistUrl: string): Promise<DownloadJob[]> {
+    return invoke<DownloadJob[]>('add_apple_music_playlist_to_queue', { playlistUrl });
+  },
+
   async addYoutubePlaylist(playlistUrl: string): Promise<DownloadJob[]> {
     return invoke<DownloadJob[]>('add_youtube_playlist_to_queue', { playlistUrl });
   },
 
+  async addCollection(url: string): Promise<DownloadJob[]> {
+    return invoke<DownloadJob[]>('add_collection_to_queue', { url });
+  },
+
   async getQueueStatus(): Promise<QueueStatus> {
     return invoke<QueueStatus>('get_queue_status');
   },
@@ -96,6 +273,33 @@ export const queueApi = {
   async startProcessing(): Promise<void> {
     return invoke('start_queue_processing');
   },
+
+  async resolveJobSource(jobId: string, candidateUrl: string): Promise<void> {
+    return invoke('resolve_job_source', { jobId, candidateUrl });
+  },
+};
+
+// ============================================================================
+// History API
+// ============================================================================
+
+export const historyApi = {
+  async getHistory(filter?: HistoryFilter): Promise<HistoryEntry[]> {
+    return invoke<HistoryEntry[]>('get_download_history', { filter });
+  },
+
+  async removeEntry(entryId: string): Promise<boolean> {
+    return invoke<boolean>('remove_history_entry', { entryId });
+  },
+
+  async clearHistory(): Promise<number> {
+    return invoke<number>('clear_download_history');
+  },
+
+  // Queue the entry's link again; the existing file is replaced
+  async redownload(entryId: string): Promise<DownloadJob> {
+    return invoke<DownloadJob>('redownload_from_history', { entryId });
+  },
 };
 
 // ============================================================================
@@ -115,6 +319,14 @@ export const platformApi = {
     return invoke<string>('get_clipboard_url');
   },
 
+  async getClipboardWatchSettings(): Promise<ClipboardWatchSettings> {
+    return invoke<ClipboardWatchSettings>('get_clipboard_watch_settings');
+  },
+
+  async setClipboardWatchSettings(watch: ClipboardWatchSettings): Promise<void> {
+    return invoke('set_clipboard_watch_settings', { watch });
+  },
+
   getDownloadDir(): Promise<string> {
     return invoke<string>('get_download_dir');
   },
@@ -131,6 +343,7 @@ export const platformApi = {
 export const api = {
   auth: authApi,
   queue: queueApi,
+  history: historyApi,
   platform: platformApi,
 };
 
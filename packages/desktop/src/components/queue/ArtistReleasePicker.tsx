@@ -0,0 +1,153 @@
+// Artist Release Picker - Choose which of a Spotify artist's releases to queue
+import { useState, useEffect } from 'react';
+import api, { errorMessage } from '../../api/tauri';
+import type { ArtistReleaseFilter, SpotifyArtistReleases } from '../../api/tauri';
+import { useLanguage } from '../../i18n';
+
+interface ArtistReleasePickerProps {
+  artistUrl: string;
+  onQueue: (artistName: string, releaseUrls: string[]) => Promise<void>;
+  onQueueTopTracks: () => Promise<void>;
+  onClose: () => void;
+}
+
+const defaultFilter: ArtistReleaseFilter = {
+  albums: true,
+  singles: true,
+  compilations: false,
+  appears_on: false,
+};
+
+export function ArtistReleasePicker({
+  artistUrl,
+  onQueue,
+  onQueueTopTracks,
+  onClose,
+}: ArtistReleasePickerProps) {
+  const { t } = useLanguage();
+  const [filter, setFilter] = useState<ArtistReleaseFilter>(defaultFilter);
+  const [artist, setArtist] = useState<SpotifyArtistReleases | null>(null);
+  const [selected, setSelected] = useState<Set<string>>(new Set());
+  const [loading, setLoading] = useState(true);
+  const [queueing, setQueueing] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  // Reload the list whenever the filter changes; everything listed starts selected
+  useEffect(() => {
+    let cancelled = false;
+    setLoading(true);
+    setError(null);
+    api.queue.getSpotifyArtistReleases(artistUrl, filter)
+      .then((result) => {
+        if (cancelled) return;
+        setArtist(result);
+        setSelected(new Set(result.releases.map((release) => release.url)));
+      })
+      .catch((err) => !cancelled && setError(errorMessage(err)))
+      .finally(() => !cancelled && setLoading(false));
+    return () => {
+      cancelled = true;
+    };
+  }, [artistUrl, filter]);
+
+  const toggleFilter = (key: keyof ArtistReleaseFilter) => {
+    setFilter({ ...filter, [key]: !filter[key] });
+  };
+
+  const toggleRelease = (url: string) => {
+    const next = new Set(selected);
+    if (next.has(url)) {
+      next.delete(url);
+    } else {
+      next.add(url);
+    }
+    setSelected(next);
+  };
+
+  const runQueue = async (queue: () => Promise<void>) => {
+    setQueueing(true);
+    try {
+      await queue();
+      onClose();
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setQueueing(false);
+    }
+  };
+
+  const releases = artist?.releases ?? [];
+  const filterLabels: [keyof ArtistReleaseFilter, string][] = [
+    ['albums', t.artist.albums],
+    ['singles', t.artist.singles],
+    ['compilations', t.artist.compilations],
+    ['appears_on', t.artist.appearsOn],
+  ];
+
+  return (
+    <div className="picker">
+      <div className="queue-header">
+        <h3>{artist ? artist.artist_name : t.common.loading}</h3>
+        <button onClick={onClose} className="btn-clear" disabled={queueing}>
+          {t.common.cancel}
+        </button>
+      </div>
+
+      <div className="picker-filters">
+        {filterLabels.map(([key, label]) => (
+          <label key={key}>
+            <input
+              type="checkbox"
+              checked={filter[key]}
+              onChange={() => toggleFilter(key)}
+              disabled={loading || queueing}
+            />
+            {label}
+          </label>
+        ))}
+      </div>
+
+      {error && <div className="warning-box">{error}</div>}
+
+      <div className="picker-list">
+        {releases.map((release) => (
+          <label key={release.id} className="picker-item">
+            <input
+              type="checkbox"
+              checked={selected.has(release.url)}
+              onChange={() => toggleRelease(release.url)}
+              disabled={queueing}
+            />
+            {release.image_url && <img src={release.image_url} alt="" />}
+            <span className="picker-item-name">{release.name}</span>
+            <span className="picker-item-info">
+              {release.release_date.slice(0, 4)} · {release.total_tracks} {t.artist.tracks}
+            </span>
+          </label>
+        ))}
+        {!loading && releases.length === 0 && !error && (
+          <p className="note">{t.artist.noReleases}</p>
+        )}
+      </div>
+
+      <div className="picker-actions">
+        <button
+          onClick={() => runQueue(onQueueTopTracks)}
+          className="btn-clear"
+          disabled={loading || queueing}
+        >
+          {t.artist.topTracks}
+        </button>
+        <button
+          onClick={() => runQueue(() => onQueue(artist!.artist_name, releases
+            .map((release) => release.url)
+            .filter((url) => selected.has(url))))}
+          className="btn-download"
+          disabled={loading || queueing || selected.size === 0}
+        >
+          {queueing ? t.common.loading : `${t.common.queueSelected} (${selected.size})`}
+        </button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,109 @@
+// Link Picker - Confirm which links found in pasted text to queue
+import { useState, useEffect } from 'react';
+import api, { errorMessage } from '../../api/tauri';
+import type { ParsedLink } from '../../api/tauri';
+import { useLanguage } from '../../i18n';
+
+interface LinkPickerProps {
+  links: ParsedLink[];
+  serviceStyles: Record<string, { icon: string; color: string; name: string }>;
+  onQueue: (urls: string[]) => Promise<void>;
+  onClose: () => void;
+}
+
+export function LinkPicker({ links, serviceStyles, onQueue, onClose }: LinkPickerProps) {
+  const { t } = useLanguage();
+  const [selected, setSelected] = useState<Set<string>>(new Set(links.map((link) => link.url)));
+  const [queueing, setQueueing] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [titles, setTitles] = useState<Record<string, string>>({});
+
+  // Show "Author - Title" once the service's preview arrives (one small request per link)
+  useEffect(() => {
+    let cancelled = false;
+    links.forEach((link) => {
+      api.queue.previewLink(link.url)
+        .then((preview) => {
+          if (cancelled) return;
+          const title = preview.author ? `${preview.author} - ${preview.title}` : preview.title;
+          setTitles((current) => ({ ...current, [link.url]: title }));
+        })
+        .catch(() => {}); // Keep showing the URL
+    });
+    return () => {
+      cancelled = true;
+    };
+  }, [links]);
+
+  const kindLabels: Record<ParsedLink['kind'], string> = {
+    track: t.paste.kindTrack,
+    album: t.paste.kindAlbum,
+    playlist: t.paste.kindPlaylist,
+    artist: t.paste.kindArtist,
+    short_link: t.paste.kindTrack,
+  };
+
+  const toggleLink = (url: string) => {
+    const next = new Set(selected);
+    if (next.has(url)) {
+      next.delete(url);
+    } else {
+      next.add(url);
+    }
+    setSelected(next);
+  };
+
+  const handleQueue = async () => {
+    setQueueing(true);
+    try {
+      await onQueue(links.map((link) => link.url).filter((url) => selected.has(url)));
+      onClose();
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setQueueing(false);
+    }
+  };
+
+  return (
+    <div className="picker">
+      <div className="queue-header">
+        <h3>{t.paste.title} ({links.length})</h3>
+        <button onClick={onClose} className="btn-clear" disabled={queueing}>
+          {t.common.cancel}
+        </button>
+      </div>
+
+      {error && <div className="warning-box">{error}</div>}
+
+      <div className="picker-list">
+        {links.map((link) => {
+          const style = serviceStyles[link.service] || serviceStyles.Unknown;
+          return (
+            <label key={link.url} className="picker-item">
+              <input
+                type="checkbox"
+                checked={selected.has(link.url)}
+                onChange={() => toggleLink(link.url)}
+                disabled={queueing}
+              />
+              <span title={style.name}>{style.icon}</span>
+              <span className="picker-item-name" title={link.url}>{titles[link.url] || link.url}</span>
+              <span className="picker-item-info">{kindLabels[link.kind]}</span>
+            </label>
+          );
+        })}
+      </div>
+
+      <div className="picker-actions">
+        <button
+          onClick={handleQueue}
+          className="btn-download"
+          disabled={queueing || selected.size === 0}
+        >
+          {queueing ? t.common.loading : `${t.common.queueSelected} (${selected.size})`}
+        </button>
+      </div>
+    </div>
+  );
+}
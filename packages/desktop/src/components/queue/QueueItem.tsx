@@ -8,10 +8,11 @@ interface QueueItemProps {
   serviceStyle: { icon: string; color: string; name: string };
   onRemove: (jobId: string) => void;
   onPlay: (filePath: string) => void;
+  onReview: (job: DownloadJob) => void;
   isPlaying: boolean;
 }
 
-export function QueueItem({ job, serviceStyle, onRemove, onPlay, isPlaying }: QueueItemProps) {
+export function QueueItem({ job, serviceStyle, onRemove, onPlay, onReview, isPlaying }: QueueItemProps) {
   const { t } = useLanguage();
 
   const handleOpenFile = async () => {
@@ -82,6 +83,12 @@ export function QueueItem({ job, serviceStyle, onRemove, onPlay, isPlaying }: Qu
             </div>
           )}
 
+          {job.status === 'NeedsReview' && (
+            <button className="status-label review" onClick={() => onReview(job)} title={job.message}>
+              {t.review.needsReview}
+            </button>
+          )}
+
           {job.status === 'Error' && (
             <span className="status-label error" title={job.error}>
               {t.download.statusError}
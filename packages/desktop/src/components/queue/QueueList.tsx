@@ -1,7 +1,7 @@
 // Queue List Component - Shows queue with stats and controls
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import type { QueueStatus } from '../../api/tauri';
+import type { DownloadJob, QueueStatus } from '../../api/tauri';
 import { useLanguage } from '../../i18n';
 import { QueueItem } from './QueueItem';
 import { AudioPlayer } from '../AudioPlayer';
@@ -10,6 +10,7 @@ interface QueueListProps {
   queueStatus: QueueStatus;
   serviceStyles: Record<string, { icon: string; color: string; name: string }>;
   onRemove: (jobId: string) => void;
+  onReview: (job: DownloadJob) => void;
   onClearCompleted: () => void;
   onClearAll: () => void;
 }
@@ -18,6 +19,7 @@ export function QueueList({
   queueStatus,
   serviceStyles,
   onRemove,
+  onReview,
   onClearCompleted,
   onClearAll,
 }: QueueListProps) {
@@ -78,6 +80,11 @@ export function QueueList({
               {queueStatus.completed_count} {t.download.done}
             </span>
           )}
+          {(queueStatus.needs_review_count ?? 0) > 0 && (
+            <span className="stat review">
+              {queueStatus.needs_review_count} {t.review.needsReview}
+            </span>
+          )}
           {queueStatus.error_count > 0 && (
             <span className="stat error">
               {queueStatus.error_count} {t.download.failed}
@@ -121,6 +128,7 @@ export function QueueList({
               serviceStyle={style}
               onRemove={onRemove}
               onPlay={handlePlay}
+              onReview={onReview}
               isPlaying={currentAudioPath === job.output_path}
             />
           );
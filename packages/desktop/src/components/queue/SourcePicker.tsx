@@ -0,0 +1,98 @@
+// Source Picker - Choose the YouTube video for a job whose search had no clear match
+import { useState } from 'react';
+import api, { errorMessage } from '../../api/tauri';
+import type { DownloadJob } from '../../api/tauri';
+import { useLanguage } from '../../i18n';
+
+interface SourcePickerProps {
+  job: DownloadJob;
+  onResolved: () => Promise<void>;
+  onClose: () => void;
+}
+
+// "3:45" from seconds
+function formatDuration(seconds: number | null): string {
+  if (seconds === null) return '';
+  return `${Math.floor(seconds / 60)}:${String(seconds % 60).padStart(2, '0')}`;
+}
+
+export function SourcePicker({ job, onResolved, onClose }: SourcePickerProps) {
+  const { t } = useLanguage();
+  const candidates = job.review_candidates ?? [];
+  const [selected, setSelected] = useState<string>(candidates[0]?.url ?? '');
+  const [otherUrl, setOtherUrl] = useState('');
+  const [resolving, setResolving] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const chosenUrl = otherUrl.trim() || selected;
+
+  const handleResolve = async () => {
+    setResolving(true);
+    try {
+      await api.queue.resolveJobSource(job.id, chosenUrl);
+      await onResolved();
+      onClose();
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setResolving(false);
+    }
+  };
+
+  const trackName = job.metadata.artist ? `${job.metadata.artist} - ${job.metadata.title}` : job.metadata.title;
+
+  return (
+    <div className="picker">
+      <div className="queue-header">
+        <h3>{t.review.title}: {trackName}</h3>
+        <button onClick={onClose} className="btn-clear" disabled={resolving}>
+          {t.common.cancel}
+        </button>
+      </div>
+
+      {error && <div className="warning-box">{error}</div>}
+
+      <div className="picker-filters">{t.review.hint}</div>
+
+      <div className="picker-list">
+        {candidates.map((candidate) => (
+          <label key={candidate.url} className="picker-item">
+            <input
+              type="radio"
+              name={`source-${job.id}`}
+              checked={!otherUrl.trim() && selected === candidate.url}
+              onChange={() => {
+                setSelected(candidate.url);
+                setOtherUrl('');
+              }}
+              disabled={resolving}
+            />
+            <span className="picker-item-name" title={candidate.url}>
+              {candidate.title}
+              <span className="picker-item-info"> - {candidate.uploader}</span>
+            </span>
+            <span className="picker-item-info">
+              {formatDuration(candidate.duration_secs)} · {Math.round(candidate.confidence * 100)}% {t.review.confidence}
+            </span>
+          </label>
+        ))}
+      </div>
+
+      <div className="picker-filters">
+        <input
+          type="text"
+          value={otherUrl}
+          onChange={(e) => setOtherUrl(e.target.value)}
+          placeholder={t.review.otherLink}
+          disabled={resolving}
+        />
+      </div>
+
+      <div className="picker-actions">
+        <button onClick={handleResolve} className="btn-download" disabled={resolving || !chosenUrl}>
+          {resolving ? t.common.loading : t.review.useSource}
+        </button>
+      </div>
+    </div>
+  );
+}
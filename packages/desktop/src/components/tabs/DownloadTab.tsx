@@ -1,21 +1,38 @@
 // Download Tab Component - Main download interface
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import type { QueueStatus } from '../../api/tauri';
+import { listen } from '@tauri-apps/api/event';
+import api, { errorMessage } from '../../api/tauri';
+import type {
+  ClipboardLinksEvent,
+  ClipboardWatchMode,
+  ClipboardWatchSettings,
+  DownloadJob,
+  ParsedLink,
+  QueueStatus,
+} from '../../api/tauri';
 import { useLanguage } from '../../i18n';
 import { QueueList } from '../queue/QueueList';
+import { ArtistReleasePicker } from '../queue/ArtistReleasePicker';
+import { LinkPicker } from '../queue/LinkPicker';
+import { SourcePicker } from '../queue/SourcePicker';
 
 interface DownloadTabProps {
   isLicenseValid: boolean;
   queueStatus: QueueStatus | null;
   onAddToQueue: (url: string) => Promise<void>;
+  onAddLinks: (urls: string[]) => Promise<void>;
+  onImportPlaylist: (file: File) => Promise<void>;
+  onAddArtistReleases: (artistName: string, releaseUrls: string[]) => Promise<void>;
   onRemoveJob: (jobId: string) => void;
   onClearCompleted: () => void;
   onClearAll: () => void;
+  droppedLinks: ParsedLink[] | null;
+  onDroppedLinksShown: () => void;
 }
 
 // Service icons and colors
-const serviceStyles: Record<string, { icon: string; color: string; name: string }> = {
+export const serviceStyles: Record<string, { icon: string; color: string; name: string }> = {
   YouTube: { icon: '🎬', color: '#FF0000', name: 'YouTube' },
   Spotify: { icon: '🟢', color: '#1DB954', name: 'Spotify' },
   SoundCloud: { icon: '🟠', color: '#FF5500', name: 'SoundCloud' },
@@ -30,14 +47,25 @@ export function DownloadTab({
   isLicenseValid,
   queueStatus,
   onAddToQueue,
+  onAddLinks,
+  onImportPlaylist,
+  onAddArtistReleases,
   onRemoveJob,
   onClearCompleted,
   onClearAll,
+  droppedLinks,
+  onDroppedLinksShown,
 }: DownloadTabProps) {
   const { t } = useLanguage();
   const [downloadUrl, setDownloadUrl] = useState('');
   const [adding, setAdding] = useState(false);
   const [englishOnlyMode, setEnglishOnlyMode] = useState(false);
+  const [artistUrls, setArtistUrls] = useState<string[]>([]);
+  const [pastedLinks, setPastedLinks] = useState<ParsedLink[] | null>(null);
+  const [reviewJob, setReviewJob] = useState<DownloadJob | null>(null);
+  const [clipboardWatch, setClipboardWatch] = useState<ClipboardWatchSettings | null>(null);
+  const [ignoreText, setIgnoreText] = useState('');
+  const playlistFileInput = useRef<HTMLInputElement>(null);
 
   // Load English Only mode on mount
   useEffect(() => {
@@ -46,6 +74,69 @@ export function DownloadTab({
       .catch(console.error);
   }, []);
 
+  // Load clipboard watcher settings on mount
+  useEffect(() => {
+    api.platform.getClipboardWatchSettings()
+      .then((watch) => {
+        setClipboardWatch(watch);
+        setIgnoreText(watch.ignore.join(', '));
+      })
+      .catch(console.error);
+  }, []);
+
+  // Links captured by the clipboard watcher - offer them unless they were queued already
+  useEffect(() => {
+    const unlisten = listen<ClipboardLinksEvent>('clipboard-links', (event) => {
+      if (!event.payload.queued) {
+        setPastedLinks(event.payload.links);
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // A job whose YouTube search had no clear match - let the user pick the source
+  useEffect(() => {
+    const unlisten = listen<DownloadJob>('job-needs-review', (event) => {
+      setReviewJob((current) => current ?? event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Links from text dropped on the floating button
+  useEffect(() => {
+    if (droppedLinks) {
+      setPastedLinks(droppedLinks);
+      onDroppedLinksShown();
+    }
+  }, [droppedLinks]);
+
+  const saveClipboardWatch = async (watch: ClipboardWatchSettings) => {
+    try {
+      await api.platform.setClipboardWatchSettings(watch);
+      setClipboardWatch(watch);
+    } catch (error) {
+      console.error('Failed to save clipboard watcher settings:', error);
+    }
+  };
+
+  const handleClipboardWatchMode = (mode: ClipboardWatchMode) => {
+    if (clipboardWatch) {
+      saveClipboardWatch({ ...clipboardWatch, mode });
+    }
+  };
+
+  const handleIgnoreBlur = () => {
+    if (!clipboardWatch) return;
+    const ignore = ignoreText.split(',').map((entry) => entry.trim()).filter(Boolean);
+    if (ignore.join(',') !== clipboardWatch.ignore.join(',')) {
+      saveClipboardWatch({ ...clipboardWatch, ignore });
+    }
+  };
+
   const handleToggleEnglishOnly = async () => {
     const newValue = !englishOnlyMode;
     try {
@@ -69,11 +160,88 @@ export function DownloadTab({
 
     setAdding(true);
     try {
+      // Pasted text (a chat message, a list of links) - confirm the links found in it
+      if (/\s/.test(downloadUrl.trim())) {
+        showLinks(await api.queue.extractLinks(downloadUrl));
+        return;
+      }
+
+      // Spotify artist links open the release picker instead of queueing right away
+      const link = await api.queue.parseLink(downloadUrl).catch(() => null);
+      if (link?.service === 'Spotify' && link.kind === 'artist') {
+        setArtistUrls([link.url]);
+        setDownloadUrl('');
+        return;
+      }
+
       await onAddToQueue(downloadUrl);
       setDownloadUrl('');
     } catch (error) {
       console.error('Failed to add to queue:', error);
-      alert(t.common.failedToAddToQueue + ' ' + error);
+      alert(t.common.failedToAddToQueue + ' ' + errorMessage(error));
+    } finally {
+      setAdding(false);
+    }
+  };
+
+  // Links picked from pasted text - Spotify artists go to the release picker, one at a time,
+  // like a single pasted artist link; the rest are queued
+  const handleQueueLinks = async (urls: string[]) => {
+    const isSpotifyArtist = (url: string) =>
+      pastedLinks?.some((link) => link.url === url && link.service === 'Spotify' && link.kind === 'artist') ?? false;
+    const artists = urls.filter(isSpotifyArtist);
+    const others = urls.filter((url) => !isSpotifyArtist(url));
+    if (others.length > 0) {
+      await onAddLinks(others);
+    }
+    if (artists.length > 0) {
+      setArtistUrls(artists);
+    }
+  };
+
+  const handleImportPlaylist = async (file: File | undefined) => {
+    if (!file) return;
+    if (!isLicenseValid) {
+      alert(t.common.licenseNotValid);
+      return;
+    }
+
+    setAdding(true);
+    try {
+      await onImportPlaylist(file);
+    } catch (error) {
+      console.error('Failed to import playlist:', error);
+      alert(t.importPlaylist.failed + ' ' + errorMessage(error));
+    } finally {
+      setAdding(false);
+      // Let the same file be picked again
+      if (playlistFileInput.current) {
+        playlistFileInput.current.value = '';
+      }
+    }
+  };
+
+  const showLinks = (links: ParsedLink[]) => {
+    if (links.length === 0) {
+      alert(t.paste.noLinks);
+      return;
+    }
+    setPastedLinks(links);
+    setDownloadUrl('');
+  };
+
+  const handlePasteFromClipboard = async () => {
+    if (!isLicenseValid) {
+      alert(t.common.licenseNotValid);
+      return;
+    }
+
+    setAdding(true);
+    try {
+      showLinks(await api.queue.extractClipboardLinks());
+    } catch (error) {
+      console.error('Failed to read clipboard:', error);
+      alert(errorMessage(error));
     } finally {
       setAdding(false);
     }
@@ -107,24 +275,110 @@ export function DownloadTab({
           >
             {adding ? t.common.loading + '...' : t.download.addToQueue}
           </button>
+          <button
+            onClick={handlePasteFromClipboard}
+            disabled={!isLicenseValid || adding}
+            className="btn-clear"
+            title={t.paste.fromClipboard}
+          >
+            📋
+          </button>
+          <button
+            onClick={() => playlistFileInput.current?.click()}
+            disabled={!isLicenseValid || adding}
+            className="btn-clear"
+            title={t.importPlaylist.button}
+          >
+            📂
+          </button>
+          <input
+            ref={playlistFileInput}
+            type="file"
+            accept=".m3u,.m3u8,.csv,.tsv,.txt"
+            style={{ display: 'none' }}
+            onChange={(e) => handleImportPlaylist(e.target.files?.[0])}
+          />
         </div>
 
         {/* Supported Services - Compact */}
         <div className="services-compact">
           {Object.entries(serviceStyles).slice(0, 7).map(([key, style]) => (
-            <span key={key} className="service-icon-small" title={style.name}>
+            <span
+              key={key}
+              className="service-icon-small"
+              title={key === 'Bandcamp' ? `${style.name} - ${t.download.bandcampStreamOnly}` : style.name}
+            >
               {style.icon}
             </span>
           ))}
         </div>
+
+        {/* Clipboard watcher */}
+        {clipboardWatch && (
+          <div className="clipboard-watch">
+            <label>
+              {t.clipboardWatch.label}
+              <select
+                value={clipboardWatch.mode}
+                onChange={(e) => handleClipboardWatchMode(e.target.value as ClipboardWatchMode)}
+              >
+                <option value="off">{t.clipboardWatch.off}</option>
+                <option value="notify">{t.clipboardWatch.notify}</option>
+                <option value="auto_queue">{t.clipboardWatch.autoQueue}</option>
+              </select>
+            </label>
+            {clipboardWatch.mode !== 'off' && (
+              <input
+                type="text"
+                value={ignoreText}
+                onChange={(e) => setIgnoreText(e.target.value)}
+                onBlur={handleIgnoreBlur}
+                placeholder={t.clipboardWatch.ignorePlaceholder}
+                title={t.clipboardWatch.ignoreHint}
+              />
+            )}
+          </div>
+        )}
       </div>
 
+      {/* Links found in pasted text */}
+      {pastedLinks && (
+        <LinkPicker
+          links={pastedLinks}
+          serviceStyles={serviceStyles}
+          onQueue={handleQueueLinks}
+          onClose={() => setPastedLinks(null)}
+        />
+      )}
+
+      {/* Source picker for a job that needs review */}
+      {reviewJob && (
+        <SourcePicker
+          key={reviewJob.id}
+          job={reviewJob}
+          onResolved={() => api.queue.startProcessing()}
+          onClose={() => setReviewJob(null)}
+        />
+      )}
+
+      {/* Artist release picker */}
+      {artistUrls.length > 0 && (
+        <ArtistReleasePicker
+          key={artistUrls[0]}
+          artistUrl={artistUrls[0]}
+          onQueue={onAddArtistReleases}
+          onQueueTopTracks={() => onAddToQueue(artistUrls[0])}
+          onClose={() => setArtistUrls((current) => current.slice(1))}
+        />
+      )}
+
       {/* Queue Status */}
       {queueStatus && queueStatus.jobs.length > 0 && (
         <QueueList
           queueStatus={queueStatus}
           serviceStyles={serviceStyles}
           onRemove={onRemoveJob}
+          onReview={setReviewJob}
           onClearCompleted={onClearCompleted}
           onClearAll={onClearAll}
         />
@@ -0,0 +1,166 @@
+// Library Tab Component - Search the download history and download tracks again
+import { useState, useEffect } from 'react';
+import api, { errorMessage } from '../../api/tauri';
+import type { HistoryEntry, HistoryFilter } from '../../api/tauri';
+import { useLanguage } from '../../i18n';
+import { serviceStyles } from './DownloadTab';
+
+interface LibraryTabProps {
+  isLicenseValid: boolean;
+}
+
+// Wait for the user to stop typing before querying
+const SEARCH_DELAY_MS = 300;
+
+// Unix seconds at the start (or end) of a date input's day, in local time
+function dayToUnix(day: string, endOfDay: boolean): number | undefined {
+  if (!day) return undefined;
+  const date = new Date(`${day}T00:00:00`);
+  if (endOfDay) date.setDate(date.getDate() + 1);
+  return Math.floor(date.getTime() / 1000) - (endOfDay ? 1 : 0);
+}
+
+export function LibraryTab({ isLicenseValid }: LibraryTabProps) {
+  const { t } = useLanguage();
+  const [search, setSearch] = useState('');
+  const [artist, setArtist] = useState('');
+  const [album, setAlbum] = useState('');
+  const [fromDay, setFromDay] = useState('');
+  const [toDay, setToDay] = useState('');
+  const [entries, setEntries] = useState<HistoryEntry[]>([]);
+  const [loading, setLoading] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [reloadCount, setReloadCount] = useState(0);
+
+  useEffect(() => {
+    const filter: HistoryFilter = {
+      search: search.trim() || undefined,
+      artist: artist.trim() || undefined,
+      album: album.trim() || undefined,
+      from: dayToUnix(fromDay, false),
+      to: dayToUnix(toDay, true),
+    };
+
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      setLoading(true);
+      api.history.getHistory(filter)
+        .then((found) => {
+          if (cancelled) return;
+          setEntries(found);
+          setError(null);
+        })
+        .catch((err) => {
+          if (!cancelled) setError(errorMessage(err));
+        })
+        .finally(() => {
+          if (!cancelled) setLoading(false);
+        });
+    }, SEARCH_DELAY_MS);
+
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [search, artist, album, fromDay, toDay, reloadCount]);
+
+  const runAction = async (action: () => Promise<unknown>) => {
+    try {
+      await action();
+      setReloadCount((count) => count + 1);
+    } catch (err) {
+      alert(t.library.failed + ' ' + errorMessage(err));
+    }
+  };
+
+  const handleClear = () => {
+    if (confirm(t.library.confirmClear)) {
+      runAction(() => api.history.clearHistory());
+    }
+  };
+
+  return (
+    <div className="library-tab">
+      <div className="queue-header">
+        <h2>{t.library.title} ({entries.length})</h2>
+        <button onClick={handleClear} className="btn-clear" disabled={entries.length === 0}>
+          {t.library.clearHistory}
+        </button>
+      </div>
+
+      <div className="library-filters">
+        <input
+          type="text"
+          value={search}
+          onChange={(e) => setSearch(e.target.value)}
+          placeholder={t.library.searchPlaceholder}
+          className="url-input"
+        />
+        <input
+          type="text"
+          value={artist}
+          onChange={(e) => setArtist(e.target.value)}
+          placeholder={t.library.artist}
+          className="url-input"
+        />
+        <input
+          type="text"
+          value={album}
+          onChange={(e) => setAlbum(e.target.value)}
+          placeholder={t.library.album}
+          className="url-input"
+        />
+        <label>
+          {t.library.from}
+          <input type="date" value={fromDay} onChange={(e) => setFromDay(e.target.value)} />
+        </label>
+        <label>
+          {t.library.to}
+          <input type="date" value={toDay} onChange={(e) => setToDay(e.target.value)} />
+        </label>
+      </div>
+
+      {error && <div className="warning-box">{error}</div>}
+      {loading && entries.length === 0 && <p className="loading">{t.common.loading}</p>}
+      {!loading && !error && entries.length === 0 && <p className="library-empty">{t.library.empty}</p>}
+
+      <div className="library-list">
+        {entries.map((entry) => {
+          const style = serviceStyles[entry.service] || serviceStyles.Unknown;
+          const source = entry.source && entry.source.service !== entry.service
+            ? serviceStyles[entry.source.service] || serviceStyles.Unknown
+            : null;
+          return (
+            <div key={entry.id} className="library-item">
+              <span title={style.name}>{style.icon}</span>
+              <div className="library-item-info" title={entry.output_path}>
+                <div className="library-item-title">
+                  {entry.metadata.artist} - {entry.metadata.title}
+                </div>
+                <div className="picker-item-info">
+                  {entry.metadata.album && `${entry.metadata.album} · `}
+                  {new Date(entry.completed_at * 1000).toLocaleDateString()}
+                  {source && ` · ${t.library.source}: ${source.name}`}
+                </div>
+              </div>
+              <button
+                onClick={() => runAction(() => api.history.redownload(entry.id))}
+                className="btn-clear"
+                disabled={!isLicenseValid}
+              >
+                {t.library.redownload}
+              </button>
+              <button
+                onClick={() => runAction(() => api.history.removeEntry(entry.id))}
+                className="btn-remove"
+                title={t.library.remove}
+              >
+                ✕
+              </button>
+            </div>
+          );
+        })}
+      </div>
+    </div>
+  );
+}
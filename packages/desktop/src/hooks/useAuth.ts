@@ -1,7 +1,7 @@
 // Authentication hook - handles all auth-related logic
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import api from '../api/tauri';
+import api, { errorMessage } from '../api/tauri';
 import type { LicenseStatus, StoredAuth } from '../api/tauri';
 
 export function useAuth() {
@@ -74,7 +74,7 @@ export function useAuth() {
     } catch (error) {
       console.error('Login failed:', error);
       setLoginMessage('');
-      alert(t.login.loginFailed + ' ' + error);
+      alert(t.login.loginFailed + ' ' + errorMessage(error));
     } finally {
       setLoggingIn(false);
     }
@@ -29,25 +29,56 @@ export function useQueue(isLicenseValid: boolean) {
       throw new Error('License not valid');
     }
 
-    // Detect if URL is a Spotify album
-    if (url.includes('spotify.com/album') || url.startsWith('spotify:album:')) {
-      console.log('[Queue] Detected Spotify album, fetching all tracks...');
-      await api.queue.addSpotifyAlbum(url);
-    }
-    // Detect if URL is a Spotify playlist
-    else if (url.includes('spotify.com/playlist') || url.startsWith('spotify:playlist:')) {
-      console.log('[Queue] Detected Spotify playlist, fetching all tracks...');
-      await api.queue.addSpotifyPlaylist(url);
+    // Unrecognized links still go to the queue, which reports what's wrong with them
+    const link = await api.queue.parseLink(url).catch(() => null);
+
+    // Spotify artist links added without choosing releases queue the artist's top tracks
+    if (link?.service === 'Spotify' && link.kind === 'artist') {
+      console.log('[Queue] Detected Spotify artist, fetching top tracks...');
+      await api.queue.addSpotifyArtistTopTracks(link.url);
     }
-    // Detect if URL is a YouTube playlist
-    else if (url.includes('youtube.com') && url.includes('list=')) {
-      console.log('[Queue] Detected YouTube playlist, extracting all videos...');
-      await api.queue.addYoutubePlaylist(url);
+    // Albums, playlists and artist track lists are expanded into one job per track
+    else if (link && (link.kind === 'album' || link.kind === 'playlist' || link.kind === 'artist')) {
+      console.log(`[Queue] Detected ${link.service} ${link.kind}, fetching all tracks...`);
+      await api.queue.addCollection(link.url);
     }
     else {
-      await invoke<DownloadJob>('add_to_queue', { url });
+      await invoke<DownloadJob>('add_to_queue', { url: link?.url ?? url });
+    }
+
+    await api.queue.startProcessing();
+    const status = await api.queue.getQueueStatus();
+    setQueueStatus(status);
+  };
+
+  // Queue links picked from pasted text - each is routed to the right queue command
+  const addLinks = async (urls: string[]): Promise<void> => {
+    if (!isLicenseValid) {
+      throw new Error('License not valid');
+    }
+    await api.queue.addLinks(urls);
+    await api.queue.startProcessing();
+    const status = await api.queue.getQueueStatus();
+    setQueueStatus(status);
+  };
+
+  // Queue every track of a playlist file exported from another player
+  const importPlaylist = async (file: File): Promise<void> => {
+    if (!isLicenseValid) {
+      throw new Error('License not valid');
     }
+    await api.queue.importPlaylist(file.name, await file.text());
+    await api.queue.startProcessing();
+    const status = await api.queue.getQueueStatus();
+    setQueueStatus(status);
+  };
 
+  // Queue the chosen releases of an artist (from the release picker)
+  const addArtistReleases = async (artistName: string, releaseUrls: string[]): Promise<void> => {
+    if (!isLicenseValid) {
+      throw new Error('License not valid');
+    }
+    await api.queue.addSpotifyArtistReleases(artistName, releaseUrls);
     await api.queue.startProcessing();
     const status = await api.queue.getQueueStatus();
     setQueueStatus(status);
@@ -91,6 +122,9 @@ export function useQueue(isLicenseValid: boolean) {
   return {
     queueStatus,
     addToQueue,
+    addLinks,
+    importPlaylist,
+    addArtistReleases,
     clearCompleted,
     clearAll,
     removeJob,
@@ -14,6 +14,7 @@ export const translations = {
     // Tabs
     tabs: {
       downloads: 'הורדות',
+      library: 'ספרייה',
       license: 'רישיון',
     },
     // License Tab
@@ -73,6 +74,66 @@ export const translations = {
       saveLocation: 'קבצים נשמרים ב: ~/Downloads/Hasod Downloads/',
       englishOnlyMode: 'שמות קבצים באנגלית בלבד',
       englishOnlyHint: '(מתרגם עברית לאנגלית)',
+      bandcampStreamOnly: 'מוריד את הסטרים החינמי (128kbps), לא קבצים שנרכשו',
+    },
+    // Spotify artist release picker
+    artist: {
+      albums: 'אלבומים',
+      singles: 'סינגלים',
+      compilations: 'אוספים',
+      appearsOn: 'מופיע ב',
+      tracks: 'שירים',
+      noReleases: 'לא נמצאו הוצאות',
+      topTracks: 'השירים המובילים',
+    },
+    // Links found in pasted text
+    paste: {
+      title: 'קישורים שנמצאו',
+      fromClipboard: 'הדבק קישורים מהלוח',
+      noLinks: 'לא נמצאו קישורי מוסיקה בטקסט',
+      kindTrack: 'שיר',
+      kindAlbum: 'אלבום',
+      kindPlaylist: 'פלייליסט',
+      kindArtist: 'אמן',
+    },
+    // Playlist file import
+    importPlaylist: {
+      button: 'ייבא פלייליסט (M3U, CSV, טקסט)',
+      failed: 'ייבוא הפלייליסט נכשל:',
+    },
+    // Clipboard watcher
+    clipboardWatch: {
+      label: 'מעקב אחר הלוח:',
+      off: 'כבוי',
+      notify: 'הצע קישורים שהועתקו',
+      autoQueue: 'הוסף לתור אוטומטית',
+      ignorePlaceholder: 'התעלם מ... (מופרד בפסיקים)',
+      ignoreHint: 'קישורים שמכילים אחד מהערכים האלה לא ייקלטו',
+    },
+    // Source picker for jobs without a clear YouTube match
+    review: {
+      title: 'בחר מקור',
+      needsReview: 'בחר מקור',
+      hint: 'לא נמצאה התאמה ברורה ביוטיוב. בחר את הסרטון הנכון:',
+      otherLink: 'או הדבק קישור יוטיוב אחר',
+      confidence: 'התאמה',
+      useSource: 'הורד מהמקור הזה',
+    },
+    // Library Tab
+    library: {
+      title: 'היסטוריית הורדות',
+      searchPlaceholder: 'חפש לפי שם, אמן או אלבום',
+      artist: 'אמן',
+      album: 'אלבום',
+      from: 'מתאריך',
+      to: 'עד תאריך',
+      empty: 'אין הורדות שמתאימות לחיפוש',
+      redownload: 'הורד שוב',
+      remove: 'הסר מההיסטוריה',
+      clearHistory: 'נקה היסטוריה',
+      confirmClear: 'למחוק את כל היסטוריית ההורדות? הקבצים עצמם לא יימחקו.',
+      source: 'מקור',
+      failed: 'הפעולה נכשלה:',
     },
     // Login progress messages
     login: {
@@ -89,6 +150,8 @@ export const translations = {
     // Common
     common: {
       loading: 'טוען...',
+      cancel: 'ביטול',
+      queueSelected: 'הוסף נבחרים לתור',
       pleaseEnterUrl: 'אנא הזן URL',
       licenseNotValid: 'הרישיון לא תקף. אנא התחבר קודם.',
       failedToAddToQueue: 'נכשל בהוספה לתור:',
@@ -125,6 +188,7 @@ export const translations = {
     // Tabs
     tabs: {
       downloads: 'Downloads',
+      library: 'Library',
       license: 'License',
     },
     // License Tab
@@ -189,6 +253,66 @@ export const translations = {
       saveLocation: 'Files saved to: ~/Downloads/Hasod Downloads/',
       englishOnlyMode: 'English Only Filenames',
       englishOnlyHint: '(Transliterates Hebrew to English)',
+      bandcampStreamOnly: 'Downloads the free 128 kbps stream, not purchased files',
+    },
+    // Spotify artist release picker
+    artist: {
+      albums: 'Albums',
+      singles: 'Singles',
+      compilations: 'Compilations',
+      appearsOn: 'Appears on',
+      tracks: 'tracks',
+      noReleases: 'No releases found',
+      topTracks: 'Top Tracks',
+    },
+    // Links found in pasted text
+    paste: {
+      title: 'Links Found',
+      fromClipboard: 'Paste links from clipboard',
+      noLinks: 'No music links found in the text',
+      kindTrack: 'Track',
+      kindAlbum: 'Album',
+      kindPlaylist: 'Playlist',
+      kindArtist: 'Artist',
+    },
+    // Playlist file import
+    importPlaylist: {
+      button: 'Import playlist (M3U, CSV, text)',
+      failed: 'Failed to import playlist:',
+    },
+    // Clipboard watcher
+    clipboardWatch: {
+      label: 'Watch clipboard:',
+      off: 'Off',
+      notify: 'Offer copied links',
+      autoQueue: 'Queue automatically',
+      ignorePlaceholder: 'Ignore... (comma separated)',
+      ignoreHint: 'Links containing any of these are not captured',
+    },
+    // Source picker for jobs without a clear YouTube match
+    review: {
+      title: 'Choose a Source',
+      needsReview: 'Choose source',
+      hint: 'No clear YouTube match was found. Pick the right video:',
+      otherLink: 'Or paste another YouTube link',
+      confidence: 'match',
+      useSource: 'Download from this source',
+    },
+    // Library Tab
+    library: {
+      title: 'Download History',
+      searchPlaceholder: 'Search by title, artist or album',
+      artist: 'Artist',
+      album: 'Album',
+      from: 'From',
+      to: 'To',
+      empty: 'No downloads match your search',
+      redownload: 'Download again',
+      remove: 'Remove from history',
+      clearHistory: 'Clear History',
+      confirmClear: 'Delete the whole download history? The files themselves are kept.',
+      source: 'Source',
+      failed: 'Action failed:',
     },
     // Login progress messages
     login: {
@@ -205,6 +329,8 @@ export const translations = {
     // Common
     common: {
       loading: 'Loading...',
+      cancel: 'Cancel',
+      queueSelected: 'Queue Selected',
       pleaseEnterUrl: 'Please enter a URL',
       licenseNotValid: 'License not valid. Please login first.',
       failedToAddToQueue: 'Failed to add to queue:',